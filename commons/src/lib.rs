@@ -19,5 +19,3 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 pub mod pre_deploy_contracts;
-
-
@@ -18,4 +18,82 @@ interface Did {
         address user,
         string calldata provider
     ) external view returns (string memory);
+
+    /// @dev Link an external id with a provider to a user. An expiry of zero never expires.
+    /// The caller must manage the provider.
+    function addUserAddress(
+        address user,
+        string calldata provider,
+        string calldata externalId,
+        uint32 expiresAt
+    ) external;
+
+    /// @dev Unlink the external id a user holds with a provider.
+    function removeUserAddress(
+        address user,
+        string calldata provider
+    ) external;
+
+    /// @dev Add a user to a list. An expiry of zero never expires.
+    /// The caller must manage the list, or own it and still hold a DID manager role that is not
+    /// read-only.
+    function addUserToList(
+        string calldata listName,
+        address user,
+        uint32 expiresAt
+    ) external;
+
+    /// @dev Remove a user from a list.
+    function removeUserFromList(
+        string calldata listName,
+        address user
+    ) external;
+
+    /// @dev Whether a user is an unexpired member of a list.
+    function isInList(
+        string calldata listName,
+        address user
+    ) external view returns (bool);
+
+    /// @dev Whether an account holds any DID manager role, read-only included.
+    function isManager(address manager) external view returns (bool);
+
+    /// @dev Add users to a list in one call. An expiry of zero never expires.
+    function addUsersToList(
+        string calldata listName,
+        address[] calldata users,
+        uint32[] calldata expiresAt
+    ) external;
+
+    /// @dev Remove users from a list in one call.
+    function removeUsersFromList(
+        string calldata listName,
+        address[] calldata users
+    ) external;
+
+    /// @dev Link external ids with one provider to many users. An expiry of zero never expires.
+    function addUserAddresses(
+        string calldata provider,
+        address[] calldata users,
+        string[] calldata externalIds,
+        uint32[] calldata expiresAt
+    ) external;
+
+    /// @dev Check that an external id and salt open the commitment stored for a user.
+    /// @param proof Merkle proof for commitments covering several attributes, empty otherwise.
+    function verifyExternalId(
+        address user,
+        string calldata provider,
+        bytes calldata externalId,
+        bytes32 salt,
+        bytes32[] calldata proof
+    ) external view returns (bool);
+
+    /// @dev Pay the storage deposit of the caller's external id with a provider, releasing the
+    /// deposit of whoever paid it before.
+    function payExternalIdDeposit(string calldata provider) external;
+
+    /// @dev Pay the storage deposit of the caller's membership of a list, releasing the deposit
+    /// of whoever paid it before.
+    function payListDeposit(string calldata listName) external;
 }
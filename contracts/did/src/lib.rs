@@ -8,16 +8,23 @@ use frame_support::{
 	dispatch::{GetDispatchInfo, PostDispatchInfo},
 	traits::{Currency, DefensiveTruncateFrom},
 };
+use frame_system::pallet_prelude::BlockNumberFor;
 use pallet_evm::AddressMapping;
 use precompile_utils::prelude::*;
-use sp_core::{ConstU32, U256};
-use sp_std::{convert::TryInto, marker::PhantomData, vec::Vec};
+use scale_codec::MaxEncodedLen;
+use sp_core::{ConstU32, H256, U256};
 use sp_runtime::traits::{Dispatchable, Hash, StaticLookup};
+use sp_std::{convert::TryInto, marker::PhantomData, vec::Vec};
 
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
 
 pub const ARRAY_LIMIT: u32 = 2u32.pow(9);
 
 type GetArrayLimit = ConstU32<ARRAY_LIMIT>;
+type GetProofLimit = ConstU32<{ pallet_did::MAX_PROOF_DEPTH }>;
 
 pub struct DidPrecompile<Runtime>(PhantomData<Runtime>);
 
@@ -40,7 +47,272 @@ where
 		// AccountsPayable: Twox64Concat(8) + AccountId(20) + RewardPoint(32) 128
 		handle.record_db_read::<Runtime>(188)?;
 		let user = Runtime::AddressMapping::into_account_id(user.0);
-		let provider: pallet_did::Provider = pallet_did::Provider::defensive_truncate_from(provider.as_bytes().to_vec());
-		Ok(pallet_did::Pallet::<Runtime>::external_id(user, provider).to_vec().into())
+		let provider: pallet_did::Provider =
+			pallet_did::Provider::defensive_truncate_from(provider.as_bytes().to_vec());
+		Ok(pallet_did::Pallet::<Runtime>::external_id(user, provider)
+			.to_vec()
+			.into())
+	}
+
+	#[precompile::public("addUserAddress(address,string,string,uint32)")]
+	fn add_user_address(
+		handle: &mut impl PrecompileHandle,
+		user: Address,
+		provider: BoundedString<GetArrayLimit>,
+		external_id: BoundedString<GetArrayLimit>,
+		expires_at: u32,
+	) -> EvmResult {
+		let user = Runtime::AddressMapping::into_account_id(user.0);
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_did::Call::<Runtime>::add_user_address {
+			user,
+			provider: provider.into(),
+			external_id: external_id.into(),
+			expires_at: Self::expiry(expires_at),
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	#[precompile::public("removeUserAddress(address,string)")]
+	fn remove_user_address(
+		handle: &mut impl PrecompileHandle,
+		user: Address,
+		provider: BoundedString<GetArrayLimit>,
+	) -> EvmResult {
+		let user = Runtime::AddressMapping::into_account_id(user.0);
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_did::Call::<Runtime>::remove_user_address {
+			user,
+			provider: provider.into(),
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	#[precompile::public("addUserToList(string,address,uint32)")]
+	fn add_user_to_list(
+		handle: &mut impl PrecompileHandle,
+		list_name: BoundedString<GetArrayLimit>,
+		user: Address,
+		expires_at: u32,
+	) -> EvmResult {
+		let user = Runtime::AddressMapping::into_account_id(user.0);
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_did::Call::<Runtime>::add_user_to_list {
+			list_name: list_name.into(),
+			user,
+			expires_at: Self::expiry(expires_at),
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	#[precompile::public("removeUserFromList(string,address)")]
+	fn remove_user_from_list(
+		handle: &mut impl PrecompileHandle,
+		list_name: BoundedString<GetArrayLimit>,
+		user: Address,
+	) -> EvmResult {
+		let user = Runtime::AddressMapping::into_account_id(user.0);
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_did::Call::<Runtime>::remove_user_from_list {
+			list_name: list_name.into(),
+			user,
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	#[precompile::public("isInList(string,address)")]
+	#[precompile::view]
+	fn is_in_list(
+		handle: &mut impl PrecompileHandle,
+		list_name: BoundedString<GetArrayLimit>,
+		user: Address,
+	) -> EvmResult<bool> {
+		// UserList: Twox64Concat(8) + ListName(33) + Twox64Concat(8) + AccountId(20)
+		// + ListMembership
+		handle.record_db_read::<Runtime>(
+			69 + pallet_did::ListMembership::<BlockNumberFor<Runtime>>::max_encoded_len(),
+		)?;
+		let user = Runtime::AddressMapping::into_account_id(user.0);
+		let list_name =
+			pallet_did::ListName::defensive_truncate_from(list_name.as_bytes().to_vec());
+		Ok(pallet_did::Pallet::<Runtime>::user_list(list_name, user))
+	}
+
+	#[precompile::public("isManager(address)")]
+	#[precompile::view]
+	fn is_manager(handle: &mut impl PrecompileHandle, manager: Address) -> EvmResult<bool> {
+		// PalletManager: Twox64Concat(8) + AccountId(20) + ManagerRole
+		handle.record_db_read::<Runtime>(28 + pallet_did::ManagerRole::max_encoded_len())?;
+		let manager = Runtime::AddressMapping::into_account_id(manager.0);
+		Ok(pallet_did::Pallet::<Runtime>::is_manager(&manager))
+	}
+
+	#[precompile::public("addUsersToList(string,address[],uint32[])")]
+	fn add_users_to_list(
+		handle: &mut impl PrecompileHandle,
+		list_name: BoundedString<GetArrayLimit>,
+		users: BoundedVec<Address, GetArrayLimit>,
+		expires_at: BoundedVec<u32, GetArrayLimit>,
+	) -> EvmResult {
+		let users: Vec<Address> = users.into();
+		let expires_at: Vec<u32> = expires_at.into();
+		if users.len() != expires_at.len() {
+			return Err(revert("users and expiries differ in length"));
+		}
+		let users: Vec<_> = users
+			.into_iter()
+			.zip(expires_at)
+			.map(|(user, expires_at)| {
+				(
+					Runtime::AddressMapping::into_account_id(user.0),
+					Self::expiry(expires_at),
+				)
+			})
+			.collect();
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_did::Call::<Runtime>::add_users_to_list {
+			list_name: list_name.into(),
+			users: users.try_into().map_err(|_| revert("too many users"))?,
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	#[precompile::public("removeUsersFromList(string,address[])")]
+	fn remove_users_from_list(
+		handle: &mut impl PrecompileHandle,
+		list_name: BoundedString<GetArrayLimit>,
+		users: BoundedVec<Address, GetArrayLimit>,
+	) -> EvmResult {
+		let users: Vec<Address> = users.into();
+		let users: Vec<Runtime::AccountId> = users
+			.into_iter()
+			.map(|user| Runtime::AddressMapping::into_account_id(user.0))
+			.collect();
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_did::Call::<Runtime>::remove_users_from_list {
+			list_name: list_name.into(),
+			users: users.try_into().map_err(|_| revert("too many users"))?,
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	#[precompile::public("addUserAddresses(string,address[],string[],uint32[])")]
+	fn add_user_addresses(
+		handle: &mut impl PrecompileHandle,
+		provider: BoundedString<GetArrayLimit>,
+		users: BoundedVec<Address, GetArrayLimit>,
+		external_ids: BoundedVec<BoundedString<GetArrayLimit>, GetArrayLimit>,
+		expires_at: BoundedVec<u32, GetArrayLimit>,
+	) -> EvmResult {
+		let users: Vec<Address> = users.into();
+		let external_ids: Vec<BoundedString<GetArrayLimit>> = external_ids.into();
+		let expires_at: Vec<u32> = expires_at.into();
+		if users.len() != external_ids.len() || users.len() != expires_at.len() {
+			return Err(revert("users, external ids and expiries differ in length"));
+		}
+		let mut entries = Vec::with_capacity(users.len());
+		for ((user, external_id), expires_at) in users.into_iter().zip(external_ids).zip(expires_at)
+		{
+			let external_id = pallet_did::ExternalId::try_from(external_id.as_bytes().to_vec())
+				.map_err(|_| revert("external id too long"))?;
+			entries.push((
+				Runtime::AddressMapping::into_account_id(user.0),
+				external_id,
+				Self::expiry(expires_at),
+			));
+		}
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_did::Call::<Runtime>::add_user_addresses {
+			provider: provider.into(),
+			entries: entries.try_into().map_err(|_| revert("too many entries"))?,
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	#[precompile::public("verifyExternalId(address,string,bytes,bytes32,bytes32[])")]
+	#[precompile::view]
+	fn verify_external_id(
+		handle: &mut impl PrecompileHandle,
+		user: Address,
+		provider: BoundedString<GetArrayLimit>,
+		external_id: BoundedBytes<GetArrayLimit>,
+		salt: H256,
+		proof: BoundedVec<H256, GetProofLimit>,
+	) -> EvmResult<bool> {
+		// ExternalIdAddress: Twox64Concat(8) + AccountId(20) + Twox64Concat(8) + Provider(32)
+		// + ExternalIdRecord(129 + 5)
+		handle.record_db_read::<Runtime>(202)?;
+		let user = Runtime::AddressMapping::into_account_id(user.0);
+		let provider: pallet_did::Provider =
+			pallet_did::Provider::defensive_truncate_from(provider.as_bytes().to_vec());
+		let external_id: Vec<u8> = external_id.into();
+		let proof: Vec<H256> = proof.into();
+		Ok(pallet_did::Pallet::<Runtime>::verify_external_id(
+			user,
+			provider,
+			&external_id,
+			salt,
+			&proof,
+		))
+	}
+
+	#[precompile::public("payExternalIdDeposit(string)")]
+	fn pay_external_id_deposit(
+		handle: &mut impl PrecompileHandle,
+		provider: BoundedString<GetArrayLimit>,
+	) -> EvmResult {
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let provider: pallet_did::Provider =
+			pallet_did::Provider::defensive_truncate_from(provider.as_bytes().to_vec());
+		let call = pallet_did::Call::<Runtime>::pay_deposit {
+			record: pallet_did::DidRecord::ExternalId {
+				who: origin.clone(),
+				provider,
+			},
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	#[precompile::public("payListDeposit(string)")]
+	fn pay_list_deposit(
+		handle: &mut impl PrecompileHandle,
+		list_name: BoundedString<GetArrayLimit>,
+	) -> EvmResult {
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let list_name =
+			pallet_did::ListName::defensive_truncate_from(list_name.as_bytes().to_vec());
+		let call = pallet_did::Call::<Runtime>::pay_deposit {
+			record: pallet_did::DidRecord::ListMembership {
+				list_name,
+				who: origin.clone(),
+			},
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	/// Solidity has no optional integers, so a zero block means no expiry.
+	fn expiry(block: u32) -> Option<BlockNumberFor<Runtime>> {
+		match block {
+			0 => None,
+			block => Some(block.into()),
+		}
 	}
 }
@@ -0,0 +1,351 @@
+use crate::mock::{
+	list_name, provider, ExtBuilder, PCall, Precompile, Precompiles, PrecompilesValue, Runtime,
+};
+use pallet_did::ManagerRole;
+use precompile_utils::{prelude::*, testing::*};
+
+fn precompiles() -> Precompiles<Runtime> {
+	PrecompilesValue::get()
+}
+
+fn reverts_with(error: &'static str) -> impl Fn(&[u8]) -> bool {
+	move |output| String::from_utf8_lossy(output).contains(error)
+}
+
+#[test]
+fn selectors() {
+	assert!(PCall::external_id_address_selectors().contains(&0x3b20cdb7));
+	assert!(PCall::add_user_address_selectors().contains(&0xab0fb6f4));
+	assert!(PCall::remove_user_address_selectors().contains(&0xd2f3447b));
+	assert!(PCall::add_user_to_list_selectors().contains(&0x68ece9a5));
+	assert!(PCall::remove_user_from_list_selectors().contains(&0xe9ddbc8a));
+	assert!(PCall::is_in_list_selectors().contains(&0x57090bc3));
+	assert!(PCall::is_manager_selectors().contains(&0xf3ae2415));
+	assert!(PCall::add_users_to_list_selectors().contains(&0x8531744d));
+	assert!(PCall::remove_users_from_list_selectors().contains(&0xab1290a0));
+	assert!(PCall::add_user_addresses_selectors().contains(&0x4263626c));
+	assert!(PCall::verify_external_id_selectors().contains(&0x3a257603));
+	assert!(PCall::pay_external_id_deposit_selectors().contains(&0x0ac1efbb));
+	assert!(PCall::pay_list_deposit_selectors().contains(&0x4ead9b7d));
+}
+
+#[test]
+fn modifiers() {
+	ExtBuilder::default()
+		.with_balances(vec![(Alice.into(), 1000)])
+		.build()
+		.execute_with(|| {
+			let mut tester = PrecompilesModifierTester::new(precompiles(), Alice, Precompile);
+
+			tester.test_view_modifier(PCall::external_id_address_selectors());
+			tester.test_default_modifier(PCall::add_user_address_selectors());
+			tester.test_default_modifier(PCall::remove_user_address_selectors());
+			tester.test_default_modifier(PCall::add_user_to_list_selectors());
+			tester.test_default_modifier(PCall::remove_user_from_list_selectors());
+			tester.test_view_modifier(PCall::is_in_list_selectors());
+			tester.test_view_modifier(PCall::is_manager_selectors());
+			tester.test_default_modifier(PCall::add_users_to_list_selectors());
+			tester.test_default_modifier(PCall::remove_users_from_list_selectors());
+			tester.test_default_modifier(PCall::add_user_addresses_selectors());
+			tester.test_view_modifier(PCall::verify_external_id_selectors());
+			tester.test_default_modifier(PCall::pay_external_id_deposit_selectors());
+			tester.test_default_modifier(PCall::pay_list_deposit_selectors());
+		});
+}
+
+#[test]
+fn add_user_address_links_the_external_id() {
+	ExtBuilder::default()
+		.with_balances(vec![(Alice.into(), 1000)])
+		.with_managers(vec![(Alice.into(), ManagerRole::full())])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(
+					Alice,
+					Precompile,
+					PCall::add_user_address {
+						user: Address(Bob.into()),
+						provider: "fractal".into(),
+						external_id: "bob-id".into(),
+						expires_at: 0,
+					},
+				)
+				.execute_returns(());
+
+			assert_eq!(
+				pallet_did::Pallet::<Runtime>::external_id(Bob.into(), provider("fractal"))
+					.to_vec(),
+				b"bob-id".to_vec()
+			);
+			precompiles()
+				.prepare_test(
+					Charlie,
+					Precompile,
+					PCall::external_id_address {
+						user: Address(Bob.into()),
+						provider: "fractal".into(),
+					},
+				)
+				.expect_no_logs()
+				.execute_returns(UnboundedBytes::from(b"bob-id".to_vec()));
+		})
+}
+
+#[test]
+fn add_user_address_requires_a_manager() {
+	ExtBuilder::default()
+		.with_balances(vec![(Bob.into(), 1000)])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(
+					Bob,
+					Precompile,
+					PCall::add_user_address {
+						user: Address(Bob.into()),
+						provider: "fractal".into(),
+						external_id: "bob-id".into(),
+						expires_at: 0,
+					},
+				)
+				.execute_reverts(reverts_with("InvalidOrigin"));
+		})
+}
+
+#[test]
+fn add_user_address_requires_the_provider_in_scope() {
+	let role = ManagerRole {
+		providers: pallet_did::Scope::Only(vec![provider("other")].try_into().unwrap()),
+		lists: pallet_did::Scope::None,
+	};
+	ExtBuilder::default()
+		.with_balances(vec![(Alice.into(), 1000)])
+		.with_managers(vec![(Alice.into(), role)])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(
+					Alice,
+					Precompile,
+					PCall::add_user_address {
+						user: Address(Bob.into()),
+						provider: "fractal".into(),
+						external_id: "bob-id".into(),
+						expires_at: 0,
+					},
+				)
+				.execute_reverts(reverts_with("ProviderNotInScope"));
+		})
+}
+
+#[test]
+fn remove_user_address_unlinks_the_external_id() {
+	ExtBuilder::default()
+		.with_balances(vec![(Alice.into(), 1000)])
+		.with_managers(vec![(Alice.into(), ManagerRole::full())])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(
+					Alice,
+					Precompile,
+					PCall::add_user_address {
+						user: Address(Bob.into()),
+						provider: "fractal".into(),
+						external_id: "bob-id".into(),
+						expires_at: 0,
+					},
+				)
+				.execute_returns(());
+			precompiles()
+				.prepare_test(
+					Alice,
+					Precompile,
+					PCall::remove_user_address {
+						user: Address(Bob.into()),
+						provider: "fractal".into(),
+					},
+				)
+				.execute_returns(());
+
+			assert!(
+				pallet_did::Pallet::<Runtime>::external_id(Bob.into(), provider("fractal"))
+					.is_empty()
+			);
+		})
+}
+
+#[test]
+fn list_owner_adds_and_removes_members() {
+	// The owner needs a live role, but not one covering the list.
+	let role = ManagerRole {
+		providers: pallet_did::Scope::All,
+		lists: pallet_did::Scope::None,
+	};
+	ExtBuilder::default()
+		.with_balances(vec![(Alice.into(), 1000)])
+		.with_managers(vec![(Alice.into(), role)])
+		.with_lists(vec![("kyc", Alice.into())])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(
+					Alice,
+					Precompile,
+					PCall::add_user_to_list {
+						list_name: "kyc".into(),
+						user: Address(Bob.into()),
+						expires_at: 0,
+					},
+				)
+				.execute_returns(());
+			precompiles()
+				.prepare_test(
+					Charlie,
+					Precompile,
+					PCall::is_in_list {
+						list_name: "kyc".into(),
+						user: Address(Bob.into()),
+					},
+				)
+				.expect_no_logs()
+				.execute_returns(true);
+
+			precompiles()
+				.prepare_test(
+					Alice,
+					Precompile,
+					PCall::remove_user_from_list {
+						list_name: "kyc".into(),
+						user: Address(Bob.into()),
+					},
+				)
+				.execute_returns(());
+			precompiles()
+				.prepare_test(
+					Charlie,
+					Precompile,
+					PCall::is_in_list {
+						list_name: "kyc".into(),
+						user: Address(Bob.into()),
+					},
+				)
+				.expect_no_logs()
+				.execute_returns(false);
+			assert!(!pallet_did::Pallet::<Runtime>::user_list(
+				list_name("kyc"),
+				Bob.into()
+			));
+		})
+}
+
+#[test]
+fn add_user_to_list_requires_the_list_in_scope() {
+	ExtBuilder::default()
+		.with_balances(vec![(Bob.into(), 1000)])
+		.with_managers(vec![(Bob.into(), ManagerRole::read_only())])
+		.with_lists(vec![("kyc", Alice.into())])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(
+					Bob,
+					Precompile,
+					PCall::add_user_to_list {
+						list_name: "kyc".into(),
+						user: Address(Bob.into()),
+						expires_at: 0,
+					},
+				)
+				.execute_reverts(reverts_with("ListNotInScope"));
+		})
+}
+
+#[test]
+fn is_manager_reports_any_role() {
+	ExtBuilder::default()
+		.with_managers(vec![(Alice.into(), ManagerRole::read_only())])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(
+					Charlie,
+					Precompile,
+					PCall::is_manager {
+						manager: Address(Alice.into()),
+					},
+				)
+				.expect_no_logs()
+				.execute_returns(true);
+			precompiles()
+				.prepare_test(
+					Charlie,
+					Precompile,
+					PCall::is_manager {
+						manager: Address(Bob.into()),
+					},
+				)
+				.expect_no_logs()
+				.execute_returns(false);
+		})
+}
+
+#[test]
+fn test_solidity_interface_has_all_function_selectors_documented_and_implemented() {
+	check_precompile_implements_solidity_interfaces(&["Did.sol"], PCall::supports_selector)
+}
+
+#[test]
+fn users_pay_the_deposits_of_their_own_records() {
+	ExtBuilder::default()
+		.with_balances(vec![(Alice.into(), 1000), (Bob.into(), 1000)])
+		.with_managers(vec![(Alice.into(), ManagerRole::full())])
+		.with_lists(vec![("kyc", Alice.into())])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(
+					Alice,
+					Precompile,
+					PCall::add_user_to_list {
+						list_name: "kyc".into(),
+						user: Address(Bob.into()),
+						expires_at: 0,
+					},
+				)
+				.execute_returns(());
+			let record = pallet_did::DidRecord::ListMembership {
+				list_name: list_name("kyc"),
+				who: Bob.into(),
+			};
+			assert_eq!(
+				pallet_did::Deposits::<Runtime>::get(&record).map(|(payer, _)| payer),
+				Some(Alice.into())
+			);
+
+			precompiles()
+				.prepare_test(
+					Bob,
+					Precompile,
+					PCall::pay_list_deposit {
+						list_name: "kyc".into(),
+					},
+				)
+				.execute_returns(());
+			assert_eq!(
+				pallet_did::Deposits::<Runtime>::get(&record).map(|(payer, _)| payer),
+				Some(Bob.into())
+			);
+
+			precompiles()
+				.prepare_test(
+					Bob,
+					Precompile,
+					PCall::pay_external_id_deposit {
+						provider: "fractal".into(),
+					},
+				)
+				.execute_reverts(reverts_with("RecordNotFound"));
+		})
+}
@@ -14,6 +14,10 @@ Giveaway constant GIVEAWAY_CONTRACT = Giveaway(GIVEAWAY_ADDRESS);
 /// wraps the pallet
 /// @custom:address 0x0000000000000000000000000000000000000805
 interface Giveaway {
+    /// @dev randomType 0 draws off chain, 1 from local chain randomness, 2 from VRF randomness.
+    /// assetType 0 gives away `amount` of the token `assetId`, 1 the NFT `amount` of the
+    /// collection `assetId`. kycStatus is the identity tier entrants must reach, and reverts
+    /// above the highest tier of the runtime.
     function createGiveaway(
         string calldata name,
         uint32 startBlock,
@@ -26,6 +30,29 @@ interface Giveaway {
         uint32 maxJoin
     ) external;
 
+    /// @dev Like createGiveaway, with `winners` sharing the prize equally when `prizeShares` is
+    /// empty, and otherwise one winner per percentage in `prizeShares`, adding up to 100.
+    function createGiveawayWithPrizes(
+        string calldata name,
+        uint32 startBlock,
+        uint32 endBlock,
+        uint8 kycStatus,
+        uint8 randomType,
+        uint8 assetType,
+        uint32 assetId,
+        uint256 amount,
+        uint32 maxJoin,
+        uint32 winners,
+        uint8[] calldata prizeShares
+    ) external;
+
     function participate(uint32 index) external;
+    /// @dev Buys `extraEntries` on top of the first one, at the entry fee of the giveaway each.
+    function participateWithEntries(uint32 index, uint32 extraEntries) external;
     function claimReward(uint32 index) external;
+    /// @dev Returns the prize to the creator. Entry fees are refunded over the next blocks.
+    /// Open giveaways are only cancelled by governance.
+    function cancelGiveaway(uint32 index) external;
+    /// @dev Queues a refund that failed again. Anyone may call it.
+    function retryRefund(uint32 index) external;
 }
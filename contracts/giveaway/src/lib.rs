@@ -8,11 +8,20 @@ use frame_support::{
 	dispatch::{GetDispatchInfo, PostDispatchInfo},
 	traits::Currency,
 };
+use pallet_did::VerifyIdentity;
 use pallet_evm::AddressMapping;
 use precompile_utils::prelude::*;
 use sp_core::{ConstU32, U256};
+use sp_runtime::{
+	traits::{Dispatchable, Hash, StaticLookup},
+	Percent,
+};
 use sp_std::{convert::TryInto, marker::PhantomData, vec::Vec};
-use sp_runtime::traits::{Dispatchable, Hash, StaticLookup};
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
 
 type BalanceOf<Runtime> = <<Runtime as pallet_ocw_giveaway::Config>::Currency as Currency<
 	<Runtime as frame_system::Config>::AccountId,
@@ -22,6 +31,8 @@ pub const ARRAY_LIMIT: u32 = 2u32.pow(9);
 
 type GetArrayLimit = ConstU32<ARRAY_LIMIT>;
 
+type GetPrizeSharesLimit = ConstU32<{ pallet_ocw_giveaway::MAX_WINNERS }>;
+
 pub struct GiveawayPrecompile<Runtime>(PhantomData<Runtime>);
 
 #[precompile_utils::precompile]
@@ -32,8 +43,15 @@ where
 	<Runtime::RuntimeCall as Dispatchable>::RuntimeOrigin: From<Option<Runtime::AccountId>>,
 	Runtime::RuntimeCall: From<pallet_ocw_giveaway::Call<Runtime>>,
 	BalanceOf<Runtime>: TryFrom<U256> + Into<U256>,
+	Runtime::AssetId: From<u32>,
+	Runtime::NftCollectionId: From<u32>,
+	Runtime::NftId: TryFrom<U256>,
 {
-	#[precompile::public("createGiveaway(string,uint32,uint32,uint8,uint8,uint8,uint32,uint256,uint32)")]
+	/// `assetType` 0 gives away `amount` of the token `assetId`, 1 the NFT `amount` of the
+	/// collection `assetId`.
+	#[precompile::public(
+		"createGiveaway(string,uint32,uint32,uint8,uint8,uint8,uint32,uint256,uint32)"
+	)]
 	fn create_giveaway(
 		handle: &mut impl PrecompileHandle,
 		name: BoundedString<GetArrayLimit>,
@@ -46,34 +64,134 @@ where
 		amount: U256,
 		max_join: u32,
 	) -> EvmResult {
-		// Build call with origin.
-		let amount = Self::u256_to_amount(amount).in_field("amount")?;
+		Self::inner_create(
+			handle,
+			name,
+			start,
+			end,
+			kyc_status,
+			random_type,
+			asset_type,
+			asset_id,
+			amount,
+			max_join,
+			pallet_ocw_giveaway::PrizeSplit::default(),
+		)
+	}
 
-		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
-		let kyc = match kyc_status {
-			0 => pallet_ocw_giveaway::KYCStatus::Tier0,
-			_ => pallet_ocw_giveaway::KYCStatus::Tier1,
+	/// Like `createGiveaway`, with `winners` sharing the prize equally when `prizeShares` is
+	/// empty, and otherwise one winner per percentage in `prizeShares`.
+	#[precompile::public(
+		"createGiveawayWithPrizes(string,uint32,uint32,uint8,uint8,uint8,uint32,uint256,uint32,uint32,uint8[])"
+	)]
+	fn create_giveaway_with_prizes(
+		handle: &mut impl PrecompileHandle,
+		name: BoundedString<GetArrayLimit>,
+		start: u32,
+		end: u32,
+		kyc_status: u8,
+		random_type: u8,
+		asset_type: u8,
+		asset_id: u32,
+		amount: U256,
+		max_join: u32,
+		winners: u32,
+		prize_shares: BoundedVec<u8, GetPrizeSharesLimit>,
+	) -> EvmResult {
+		let prize_shares = Vec::from(prize_shares);
+		let prize_split = if prize_shares.is_empty() {
+			pallet_ocw_giveaway::PrizeSplit::Equal(winners)
+		} else {
+			let shares = prize_shares
+				.into_iter()
+				.map(Percent::from_percent)
+				.collect::<Vec<_>>();
+			pallet_ocw_giveaway::PrizeSplit::Tiered(
+				pallet_ocw_giveaway::PrizeShares::truncate_from(shares),
+			)
 		};
+		Self::inner_create(
+			handle,
+			name,
+			start,
+			end,
+			kyc_status,
+			random_type,
+			asset_type,
+			asset_id,
+			amount,
+			max_join,
+			prize_split,
+		)
+	}
 
+	fn inner_create(
+		handle: &mut impl PrecompileHandle,
+		name: BoundedString<GetArrayLimit>,
+		start: u32,
+		end: u32,
+		kyc_status: u8,
+		random_type: u8,
+		asset_type: u8,
+		asset_id: u32,
+		amount: U256,
+		max_join: u32,
+		prize_split: pallet_ocw_giveaway::PrizeSplit,
+	) -> EvmResult {
+		// `kycStatus` is the identity tier entrants must reach.
+		if kyc_status > <Runtime as pallet_ocw_giveaway::Config>::Identity::max_tier() {
+			return Err(revert("kycStatus is above the highest identity tier"));
+		}
+		// Build call with origin.
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
 		let random_type = match random_type {
+			1 => pallet_ocw_giveaway::RandomType::LocalChain,
+			2 => pallet_ocw_giveaway::RandomType::Vrf,
 			_ => pallet_ocw_giveaway::RandomType::Chainlink,
 		};
 
-		let asset_type = match asset_type {
-			_ => pallet_ocw_giveaway::AssetType::FungibleToken,
+		let (asset_type, token, nft) = match asset_type {
+			1 => {
+				let nft_id = amount
+					.try_into()
+					.map_err(|_| Revert::from(RevertReason::value_is_too_large("item id type")))
+					.in_field("amount")?;
+				(
+					pallet_ocw_giveaway::AssetType::NonFungibleToken,
+					None,
+					Some(pallet_ocw_giveaway::NftInfo {
+						collection_id: asset_id.into(),
+						nft_id,
+					}),
+				)
+			}
+			_ => {
+				let amount = Self::u256_to_amount(amount).in_field("amount")?;
+				(
+					pallet_ocw_giveaway::AssetType::FungibleToken,
+					Some(pallet_ocw_giveaway::TokenInfo {
+						asset_id: asset_id.into(),
+						amount,
+					}),
+					None,
+				)
+			}
 		};
 
 		let call = pallet_ocw_giveaway::Call::<Runtime>::create_give_away {
 			name: name.into(),
 			start_block: start.into(),
 			end_block: end.into(),
-			kyc,
+			tier: kyc_status,
 			random_type,
 			asset_type,
-			token: Some(pallet_ocw_giveaway::TokenInfo {
-				asset_id,
-				amount
-			}),
+			token,
+			nft,
+			prize_split,
+			requirements: Default::default(),
+			fee: None,
+			weight_rule: pallet_ocw_giveaway::WeightRule::Flat,
+			fallback: pallet_ocw_giveaway::RandomnessFallback::Refund,
 			max_join,
 		};
 		// Dispatch call (if enough gas).
@@ -91,6 +209,26 @@ where
 		Ok(())
 	}
 
+	#[precompile::public("cancelGiveaway(uint32)")]
+	fn cancel_giveaway(handle: &mut impl PrecompileHandle, index: u32) -> EvmResult {
+		// Build call with origin.
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_ocw_giveaway::Call::<Runtime>::cancel_give_away { index };
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
+	#[precompile::public("retryRefund(uint32)")]
+	fn retry_refund(handle: &mut impl PrecompileHandle, index: u32) -> EvmResult {
+		// Build call with origin.
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_ocw_giveaway::Call::<Runtime>::retry_refund { index };
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
 	#[precompile::public("participate(uint32)")]
 	fn participate(handle: &mut impl PrecompileHandle, index: u32) -> EvmResult {
 		// Build call with origin.
@@ -101,6 +239,23 @@ where
 		Ok(())
 	}
 
+	#[precompile::public("participateWithEntries(uint32,uint32)")]
+	fn participate_with_entries(
+		handle: &mut impl PrecompileHandle,
+		index: u32,
+		extra_entries: u32,
+	) -> EvmResult {
+		// Build call with origin.
+		let origin = Runtime::AddressMapping::into_account_id(handle.context().caller);
+		let call = pallet_ocw_giveaway::Call::<Runtime>::participate_with_entries {
+			index,
+			extra_entries,
+		};
+		// Dispatch call (if enough gas).
+		RuntimeHelper::<Runtime>::try_dispatch(handle, Some(origin).into(), call)?;
+		Ok(())
+	}
+
 	fn u256_to_amount(value: U256) -> MayRevert<BalanceOf<Runtime>> {
 		value
 			.try_into()
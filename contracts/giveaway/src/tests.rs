@@ -0,0 +1,218 @@
+use crate::mock::{
+	account, ExtBuilder, PCall, Precompile, Precompiles, PrecompilesValue, Runtime, System,
+};
+use pallet_ocw_giveaway::{GiveawayStatus, RefundQueue, StalledRefunds};
+use precompile_utils::{prelude::*, testing::*};
+
+fn precompiles() -> Precompiles<Runtime> {
+	PrecompilesValue::get()
+}
+
+fn reverts_with(error: &'static str) -> impl Fn(&[u8]) -> bool {
+	move |output| String::from_utf8_lossy(output).contains(error)
+}
+
+/// A native token giveaway of 100 from block 5 to 10, split by `winners` and `prize_shares`.
+fn create_with_prizes(winners: u32, prize_shares: Vec<u8>) -> PCall {
+	PCall::create_giveaway_with_prizes {
+		name: "giveaway".into(),
+		start: 5,
+		end: 10,
+		kyc_status: 0,
+		random_type: 1,
+		asset_type: 0,
+		asset_id: 0,
+		amount: 100.into(),
+		max_join: 10,
+		winners,
+		prize_shares: prize_shares.into(),
+	}
+}
+
+/// Alice creates the giveaway `0` of [`create_with_prizes`] with a single winner.
+fn create_giveaway() {
+	precompiles()
+		.prepare_test(Alice, Precompile, create_with_prizes(1, vec![]))
+		.execute_returns(());
+}
+
+#[test]
+fn selectors() {
+	assert!(PCall::create_giveaway_selectors().contains(&0x8f788af4));
+	assert!(PCall::create_giveaway_with_prizes_selectors().contains(&0xa67c4dd7));
+	assert!(PCall::claim_reward_selectors().contains(&0x61313b39));
+	assert!(PCall::cancel_giveaway_selectors().contains(&0x081a95ac));
+	assert!(PCall::retry_refund_selectors().contains(&0x2425762f));
+	assert!(PCall::participate_selectors().contains(&0x35052e1e));
+	assert!(PCall::participate_with_entries_selectors().contains(&0x8fb400e3));
+}
+
+#[test]
+fn modifiers() {
+	ExtBuilder::default()
+		.with_balances(vec![(account(Alice), 1000)])
+		.build()
+		.execute_with(|| {
+			let mut tester = PrecompilesModifierTester::new(precompiles(), Alice, Precompile);
+
+			tester.test_default_modifier(PCall::create_giveaway_selectors());
+			tester.test_default_modifier(PCall::create_giveaway_with_prizes_selectors());
+			tester.test_default_modifier(PCall::claim_reward_selectors());
+			tester.test_default_modifier(PCall::cancel_giveaway_selectors());
+			tester.test_default_modifier(PCall::retry_refund_selectors());
+			tester.test_default_modifier(PCall::participate_selectors());
+			tester.test_default_modifier(PCall::participate_with_entries_selectors());
+		});
+}
+
+#[test]
+fn create_giveaway_with_prizes_splits_equally_without_shares() {
+	ExtBuilder::default()
+		.with_balances(vec![(account(Alice), 1000)])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(Alice, Precompile, create_with_prizes(3, vec![]))
+				.execute_returns(());
+
+			assert_eq!(
+				pallet_ocw_giveaway::Pallet::<Runtime>::giveaway_status(0),
+				Some(GiveawayStatus::Scheduled)
+			);
+			precompiles()
+				.prepare_test(Alice, Precompile, create_with_prizes(0, vec![]))
+				.execute_reverts(reverts_with("InvalidPrizeSplit"));
+		})
+}
+
+#[test]
+fn create_giveaway_with_prizes_splits_by_shares() {
+	ExtBuilder::default()
+		.with_balances(vec![(account(Alice), 1000)])
+		.build()
+		.execute_with(|| {
+			precompiles()
+				.prepare_test(Alice, Precompile, create_with_prizes(0, vec![60, 40]))
+				.execute_returns(());
+
+			assert_eq!(
+				pallet_ocw_giveaway::Pallet::<Runtime>::giveaway_status(0),
+				Some(GiveawayStatus::Scheduled)
+			);
+			precompiles()
+				.prepare_test(Alice, Precompile, create_with_prizes(0, vec![60, 30]))
+				.execute_reverts(reverts_with("InvalidPrizeSplit"));
+		})
+}
+
+#[test]
+fn create_giveaway_with_prizes_rejects_unknown_tiers() {
+	ExtBuilder::default()
+		.with_balances(vec![(account(Alice), 1000)])
+		.build()
+		.execute_with(|| {
+			let mut call = create_with_prizes(1, vec![]);
+			if let PCall::create_giveaway_with_prizes { kyc_status, .. } = &mut call {
+				*kyc_status = 1;
+			}
+			precompiles()
+				.prepare_test(Alice, Precompile, call)
+				.execute_reverts(reverts_with("kycStatus is above the highest identity tier"));
+		})
+}
+
+#[test]
+fn cancel_giveaway_by_the_creator() {
+	ExtBuilder::default()
+		.with_balances(vec![(account(Alice), 1000), (account(Bob), 1000)])
+		.build()
+		.execute_with(|| {
+			create_giveaway();
+
+			precompiles()
+				.prepare_test(Bob, Precompile, PCall::cancel_giveaway { index: 0 })
+				.execute_reverts(reverts_with("NotCreator"));
+			precompiles()
+				.prepare_test(Alice, Precompile, PCall::cancel_giveaway { index: 0 })
+				.execute_returns(());
+
+			assert_eq!(
+				pallet_ocw_giveaway::Pallet::<Runtime>::giveaway_status(0),
+				Some(GiveawayStatus::Cancelled)
+			);
+		})
+}
+
+#[test]
+fn retry_refund_queues_a_stalled_refund() {
+	ExtBuilder::default()
+		.with_balances(vec![(account(Alice), 1000), (account(Bob), 1000)])
+		.build()
+		.execute_with(|| {
+			create_giveaway();
+
+			precompiles()
+				.prepare_test(Bob, Precompile, PCall::retry_refund { index: 0 })
+				.execute_reverts(reverts_with("RefundNotStalled"));
+
+			StalledRefunds::<Runtime>::insert(0, 3);
+			precompiles()
+				.prepare_test(Bob, Precompile, PCall::retry_refund { index: 0 })
+				.execute_returns(());
+
+			assert!(!StalledRefunds::<Runtime>::contains_key(0));
+			assert_eq!(RefundQueue::<Runtime>::get(0), Some((0, 3)));
+		})
+}
+
+#[test]
+fn participate_with_entries_joins_the_giveaway() {
+	ExtBuilder::default()
+		.with_balances(vec![(account(Alice), 1000), (account(Bob), 1000)])
+		.build()
+		.execute_with(|| {
+			create_giveaway();
+
+			precompiles()
+				.prepare_test(
+					Bob,
+					Precompile,
+					PCall::participate_with_entries {
+						index: 0,
+						extra_entries: 0,
+					},
+				)
+				.execute_reverts(reverts_with("GiveawayNotStarted"));
+
+			System::set_block_number(5);
+			// Entries are only bought in giveaways weighted by purchased entries.
+			precompiles()
+				.prepare_test(
+					Bob,
+					Precompile,
+					PCall::participate_with_entries {
+						index: 0,
+						extra_entries: 1,
+					},
+				)
+				.execute_reverts(reverts_with("InvalidEntries"));
+			precompiles()
+				.prepare_test(
+					Bob,
+					Precompile,
+					PCall::participate_with_entries {
+						index: 0,
+						extra_entries: 0,
+					},
+				)
+				.execute_returns(());
+
+			assert_eq!(
+				pallet_ocw_giveaway::Pallet::<Runtime>::participants(0, 0),
+				Some(account(Bob))
+			);
+			precompiles()
+				.prepare_test(Bob, Precompile, PCall::participate { index: 0 })
+				.execute_reverts(reverts_with("AlreadyJoined"));
+		})
+}
@@ -1,27 +1,26 @@
-use std::{ collections::BTreeMap, str::FromStr };
+use std::{collections::BTreeMap, path::Path, str::FromStr};
 
 use hex_literal::hex;
-use serde::{ Deserialize, Serialize };
+use serde::{Deserialize, Serialize};
 // Substrate
-use sc_chain_spec::{ ChainType, Properties };
+use sc_chain_spec::{ChainType, Properties};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_consensus_grandpa::AuthorityId as GrandpaId;
 #[allow(unused_imports)]
 use sp_core::ecdsa;
-use sp_core::{ storage::Storage, Pair, Public, H160, U256 };
-use sp_runtime::traits::{ IdentifyAccount, Verify };
+use sp_core::{storage::Storage, Get, Pair, Public, H160, H256, U256};
+use sp_runtime::{
+	traits::{IdentifyAccount, Verify},
+	BoundedVec,
+};
 use sp_state_machine::BasicExternalities;
 // Frontier
+use commons::pre_deploy_contracts::{ERC1820_REGISTRY, MULTICALL2_BYTECODE, MULTICALL3_BYTECODE};
 use impetus_runtime::{
-	AccountId,
-	Balance,
-	EnableManualSeal,
-	RuntimeGenesisConfig,
-	SS58Prefix,
-	Signature,
+	AccountId, Balance, BlockNumber, EnableManualSeal, RuntimeGenesisConfig, SS58Prefix, Signature,
 	WASM_BINARY,
 };
-use commons::pre_deploy_contracts::{ ERC1820_REGISTRY, MULTICALL2_BYTECODE, MULTICALL3_BYTECODE };
+use pallet_did::{ExternalIdValue, GenesisList, ListKind};
 
 // The URL for the telemetry server.
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
@@ -66,7 +65,8 @@ type AccountPublic = <Signature as Verify>::Signer;
 /// For use with `AccountId32`, `dead_code` if `AccountId20`.
 #[allow(dead_code)]
 pub fn get_account_id_from_seed<TPublic: Public>(seed: &str) -> AccountId
-	where AccountPublic: From<<TPublic::Pair as Pair>::Public>
+where
+	AccountPublic: From<<TPublic::Pair as Pair>::Public>,
 {
 	AccountPublic::from(get_from_seed::<TPublic>(seed)).into_account()
 }
@@ -85,7 +85,146 @@ fn properties() -> Properties {
 
 const UNITS: Balance = 1_000_000_000_000_000_000;
 
-pub fn development_config(enable_manual_seal: Option<bool>) -> DevChainSpec {
+/// DID records to seed the genesis with, loaded from a JSON fixture file.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DidFixture {
+	#[serde(default)]
+	pub providers: Vec<DidProviderFixture>,
+	#[serde(default)]
+	pub lists: Vec<DidListFixture>,
+	#[serde(default)]
+	pub external_ids: Vec<DidExternalIdFixture>,
+}
+
+/// A provider and the accounts allowed to write its external ids.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DidProviderFixture {
+	pub name: String,
+	pub managers: Vec<H160>,
+}
+
+/// A list definition together with its initial members.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DidListFixture {
+	pub name: String,
+	pub owner: H160,
+	#[serde(default)]
+	pub description: String,
+	#[serde(default)]
+	pub kind: ListKind,
+	/// Zero leaves the list unbounded.
+	#[serde(default)]
+	pub max_size: u32,
+	#[serde(default)]
+	pub members: Vec<H160>,
+}
+
+/// An external id link. Exactly one of `externalId`, `commitment` or `merkleRoot` must be set.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DidExternalIdFixture {
+	pub account: H160,
+	pub provider: String,
+	pub external_id: Option<String>,
+	pub commitment: Option<H256>,
+	pub merkle_root: Option<H256>,
+	pub expires_at: Option<BlockNumber>,
+}
+
+fn bounded<S: Get<u32>>(value: &str, what: &str) -> Result<BoundedVec<u8, S>, String> {
+	value.as_bytes().to_vec().try_into().map_err(|_| {
+		format!(
+			"DID fixture {} `{}` is longer than {} bytes",
+			what,
+			value,
+			S::get()
+		)
+	})
+}
+
+impl DidFixture {
+	/// Read a fixture from a JSON file.
+	pub fn from_json_file(path: &Path) -> Result<Self, String> {
+		let file = std::fs::File::open(path)
+			.map_err(|e| format!("Error opening DID fixture `{}`: {}", path.display(), e))?;
+		let fixture: Self = serde_json::from_reader(std::io::BufReader::new(file))
+			.map_err(|e| format!("Error parsing DID fixture `{}`: {}", path.display(), e))?;
+		// Surface bad entries when the node starts rather than when the genesis is built.
+		fixture.to_genesis()?;
+		Ok(fixture)
+	}
+
+	/// Convert the fixture into the genesis config of `pallet-did`.
+	pub fn to_genesis(&self) -> Result<impetus_runtime::DidConfig, String> {
+		let providers = self
+			.providers
+			.iter()
+			.map(|provider| {
+				Ok((
+					bounded(&provider.name, "provider")?,
+					provider
+						.managers
+						.iter()
+						.cloned()
+						.map(AccountId::from)
+						.collect(),
+				))
+			})
+			.collect::<Result<_, String>>()?;
+		let lists = self
+			.lists
+			.iter()
+			.map(|list| {
+				Ok(GenesisList {
+					name: bounded(&list.name, "list name")?,
+					owner: list.owner.into(),
+					description: bounded(&list.description, "list description")?,
+					kind: list.kind.clone(),
+					max_size: list.max_size,
+					members: list.members.iter().cloned().map(AccountId::from).collect(),
+				})
+			})
+			.collect::<Result<_, String>>()?;
+		let external_ids = self.external_ids
+			.iter()
+			.map(|entry| {
+				let value = match (&entry.external_id, entry.commitment, entry.merkle_root) {
+					(Some(external_id), None, None) =>
+						ExternalIdValue::Plain(bounded(external_id, "external id")?),
+					(None, Some(commitment), None) => ExternalIdValue::Commitment(commitment),
+					(None, None, Some(root)) => ExternalIdValue::MerkleRoot(root),
+					_ => {
+						return Err(
+							format!(
+								"DID fixture external id of {:?} for `{}` must set exactly one of externalId, commitment or merkleRoot",
+								entry.account,
+								entry.provider
+							)
+						);
+					}
+				};
+				Ok((
+					entry.account.into(),
+					bounded(&entry.provider, "provider")?,
+					value,
+					entry.expires_at,
+				))
+			})
+			.collect::<Result<_, String>>()?;
+
+		Ok(impetus_runtime::DidConfig {
+			managers: vec![],
+			providers,
+			lists,
+			external_ids,
+		})
+	}
+}
+
+pub fn development_config(enable_manual_seal: Option<bool>, did: DidFixture) -> DevChainSpec {
 	let wasm_binary = WASM_BINARY.expect("WASM not available");
 
 	DevChainSpec::from_genesis(
@@ -107,12 +246,14 @@ pub fn development_config(enable_manual_seal: Option<bool>) -> DevChainSpec {
 						AccountId::from(hex!("798d4Ba9baf0064Ec19eB4F0a1a45785ae9D6DFc")), // Charleth
 						AccountId::from(hex!("773539d4Ac0e786233D90A233654ccEE26a613D9")), // Dorothy
 						AccountId::from(hex!("Ff64d3F6efE2317EE2807d223a0Bdc4c0c49dfDB")), // Ethan
-						AccountId::from(hex!("C0F0f4ab324C46e55D02D0033343B4Be8A55532d")) // Faith
+						AccountId::from(hex!("C0F0f4ab324C46e55D02D0033343B4Be8A55532d")), // Faith
 					],
 					// Initial PoA authorities
 					vec![authority_keys_from_seed("Alice")],
 					// Ethereum chain ID
-					SS58Prefix::get() as u64
+					SS58Prefix::get() as u64,
+					did.to_genesis()
+						.expect("DID fixture is checked when loaded; qed"),
 				),
 				enable_manual_seal,
 			}
@@ -128,11 +269,11 @@ pub fn development_config(enable_manual_seal: Option<bool>) -> DevChainSpec {
 		// Properties
 		Some(properties()),
 		// Extensions
-		None
+		None,
 	)
 }
 
-pub fn local_testnet_config() -> ChainSpec {
+pub fn local_testnet_config(did: DidFixture) -> ChainSpec {
 	let wasm_binary = WASM_BINARY.expect("WASM not available");
 
 	ChainSpec::from_genesis(
@@ -154,10 +295,15 @@ pub fn local_testnet_config() -> ChainSpec {
 					AccountId::from(hex!("798d4Ba9baf0064Ec19eB4F0a1a45785ae9D6DFc")), // Charleth
 					AccountId::from(hex!("773539d4Ac0e786233D90A233654ccEE26a613D9")), // Dorothy
 					AccountId::from(hex!("Ff64d3F6efE2317EE2807d223a0Bdc4c0c49dfDB")), // Ethan
-					AccountId::from(hex!("C0F0f4ab324C46e55D02D0033343B4Be8A55532d")) // Faith
+					AccountId::from(hex!("C0F0f4ab324C46e55D02D0033343B4Be8A55532d")), // Faith
+				],
+				vec![
+					authority_keys_from_seed("Alice"),
+					authority_keys_from_seed("Bob"),
 				],
-				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
-				42
+				42,
+				did.to_genesis()
+					.expect("DID fixture is checked when loaded; qed"),
 			)
 		},
 		// Bootnodes
@@ -171,7 +317,7 @@ pub fn local_testnet_config() -> ChainSpec {
 		// Properties
 		None,
 		// Extensions
-		None
+		None,
 	)
 }
 
@@ -181,15 +327,11 @@ fn testnet_genesis(
 	sudo_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
-	chain_id: u64
+	chain_id: u64,
+	did: impetus_runtime::DidConfig,
 ) -> RuntimeGenesisConfig {
 	use impetus_runtime::{
-		AuraConfig,
-		BalancesConfig,
-		EVMChainIdConfig,
-		EVMConfig,
-		GrandpaConfig,
-		SudoConfig,
+		AuraConfig, BalancesConfig, EVMChainIdConfig, EVMConfig, GrandpaConfig, SudoConfig,
 		SystemConfig,
 	};
 
@@ -217,10 +359,7 @@ fn testnet_genesis(
 
 		// Consensus
 		aura: AuraConfig {
-			authorities: initial_authorities
-				.iter()
-				.map(|x| x.0.clone())
-				.collect(),
+			authorities: initial_authorities.iter().map(|x| x.0.clone()).collect(),
 		},
 		grandpa: GrandpaConfig {
 			authorities: initial_authorities
@@ -244,79 +383,71 @@ fn testnet_genesis(
 					// SS58: 5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY
 					// hex: 0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d
 					// Using the full hex key, truncating to the first 20 bytes (the first 40 hex chars)
-					H160::from_str("d43593c715fdd31c61141abd04a99fd6822c8558").expect(
-						"internal H160 is valid; qed"
-					),
+					H160::from_str("d43593c715fdd31c61141abd04a99fd6822c8558")
+						.expect("internal H160 is valid; qed"),
 					fp_evm::GenesisAccount {
-						balance: U256::from_str("0xffffffffffffffffffffffffffffffff").expect(
-							"internal U256 is valid; qed"
-						),
+						balance: U256::from_str("0xffffffffffffffffffffffffffffffff")
+							.expect("internal U256 is valid; qed"),
 						code: Default::default(),
 						nonce: Default::default(),
 						storage: Default::default(),
-					}
+					},
 				);
 				map.insert(
 					// H160 address of CI test runner account
-					H160::from_str("6be02d1d3665660d22ff9624b7be0551ee1ac91b").expect(
-						"internal H160 is valid; qed"
-					),
+					H160::from_str("6be02d1d3665660d22ff9624b7be0551ee1ac91b")
+						.expect("internal H160 is valid; qed"),
 					fp_evm::GenesisAccount {
-						balance: U256::from_str("0xffffffffffffffffffffffffffffffff").expect(
-							"internal U256 is valid; qed"
-						),
+						balance: U256::from_str("0xffffffffffffffffffffffffffffffff")
+							.expect("internal U256 is valid; qed"),
 						code: Default::default(),
 						nonce: Default::default(),
 						storage: Default::default(),
-					}
+					},
 				);
 				map.insert(
 					// H160 address of multicall2
-					H160::from_str("5BA1e12693Dc8F9c48aAD8770482f4739bEeD696").expect(
-						"internal H160 is valid; qed"
-					),
+					H160::from_str("5BA1e12693Dc8F9c48aAD8770482f4739bEeD696")
+						.expect("internal H160 is valid; qed"),
 					fp_evm::GenesisAccount {
 						balance: U256::zero(),
 						code: hex::decode(MULTICALL2_BYTECODE.trim_end()).unwrap(),
 						nonce: Default::default(),
 						storage: Default::default(),
-					}
+					},
 				);
 				map.insert(
 					// H160 address of multicall3
-					H160::from_str("cA11bde05977b3631167028862bE2a173976CA11").expect(
-						"internal H160 is valid; qed"
-					),
+					H160::from_str("cA11bde05977b3631167028862bE2a173976CA11")
+						.expect("internal H160 is valid; qed"),
 					fp_evm::GenesisAccount {
 						balance: U256::zero(),
 						code: hex::decode(MULTICALL3_BYTECODE.trim_end()).unwrap(),
 						nonce: Default::default(),
 						storage: Default::default(),
-					}
+					},
 				);
 				map.insert(
 					// H160 address of 1820 registry
-					H160::from_str("1820a4B7618BdE71Dce8cdc73aAB6C95905faD24").expect(
-						"internal H160 is valid; qed"
-					),
+					H160::from_str("1820a4B7618BdE71Dce8cdc73aAB6C95905faD24")
+						.expect("internal H160 is valid; qed"),
 					fp_evm::GenesisAccount {
 						balance: U256::zero(),
 						code: hex::decode(ERC1820_REGISTRY.trim_end()).unwrap(),
 						nonce: Default::default(),
 						storage: Default::default(),
-					}
+					},
 				);
 				map.insert(
 					// H160 address for benchmark usage
-					H160::from_str("1000000000000000000000000000000000000001").expect(
-						"internal H160 is valid; qed"
-					),
+					H160::from_str("1000000000000000000000000000000000000001")
+						.expect("internal H160 is valid; qed"),
 					fp_evm::GenesisAccount {
 						nonce: U256::from(1),
 						balance: U256::from(1_000_000_000_000_000_000_000_000u128),
 						storage: Default::default(),
 						code: vec![0x00],
-					}
+					},
 				);
 				map
 			},
@@ -325,5 +456,11 @@ fn testnet_genesis(
 		ethereum: Default::default(),
 		dynamic_fee: Default::default(),
 		base_fee: Default::default(),
+
+		// Identity
+		did,
+
+		// Giveaway
+		assets: Default::default(),
 	}
 }
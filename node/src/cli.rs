@@ -23,6 +23,10 @@ pub struct Cli {
 	#[arg(long, value_enum, ignore_case = true)]
 	pub sealing: Option<Sealing>,
 
+	/// JSON fixture with DID providers, lists and external ids to seed the dev and local genesis.
+	#[arg(long, value_name = "PATH")]
+	pub did_genesis: Option<std::path::PathBuf>,
+
 	#[command(flatten)]
 	pub eth: EthConfiguration,
 }
@@ -2,7 +2,9 @@
 use sc_executor::{NativeElseWasmExecutor, NativeExecutionDispatch, NativeVersion};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 // Local
-use impetus_runtime::{opaque::Block, AccountId, Balance, Nonce};
+use impetus_runtime::{
+	opaque::Block, AccountId, AssetId, Balance, BlockNumber, CollectionId, Nonce,
+};
 
 use crate::eth::EthCompatRuntimeApiCollection;
 
@@ -63,6 +65,15 @@ pub trait RuntimeApiCollection:
 	+ sp_consensus_grandpa::GrandpaApi<Block>
 	+ frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Nonce>
 	+ pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance>
+	+ pallet_did_runtime_api::DidApi<Block, AccountId, BlockNumber>
+	+ pallet_ocw_giveaway_runtime_api::GiveawayApi<
+		Block,
+		AccountId,
+		BlockNumber,
+		Balance,
+		AssetId,
+		CollectionId,
+	>
 {
 }
 
@@ -73,5 +84,14 @@ impl<Api> RuntimeApiCollection for Api where
 		+ sp_consensus_grandpa::GrandpaApi<Block>
 		+ frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Nonce>
 		+ pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance>
+		+ pallet_did_runtime_api::DidApi<Block, AccountId, BlockNumber>
+		+ pallet_ocw_giveaway_runtime_api::GiveawayApi<
+			Block,
+			AccountId,
+			BlockNumber,
+			Balance,
+			AssetId,
+			CollectionId,
+		>
 {
 }
@@ -57,12 +57,19 @@ impl SubstrateCli for Cli {
 	}
 
 	fn load_spec(&self, id: &str) -> Result<Box<dyn ChainSpec>, String> {
+		let did_fixture = match &self.did_genesis {
+			Some(path) => chain_spec::DidFixture::from_json_file(path)?,
+			None => Default::default(),
+		};
 		Ok(match id {
 			"dev" => {
 				let enable_manual_seal = self.sealing.map(|_| true);
-				Box::new(chain_spec::development_config(enable_manual_seal))
+				Box::new(chain_spec::development_config(
+					enable_manual_seal,
+					did_fixture,
+				))
 			}
-			"" | "local" => Box::new(chain_spec::local_testnet_config()),
+			"" | "local" => Box::new(chain_spec::local_testnet_config(did_fixture)),
 			path => Box::new(chain_spec::ChainSpec::from_json_file(
 				std::path::PathBuf::from(path),
 			)?),
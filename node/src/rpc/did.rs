@@ -0,0 +1,278 @@
+//! RPC methods for reading DID data, backed by the `DidApi` runtime API.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpsee::{
+	core::RpcResult,
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use scale_codec::Codec;
+use serde::{Deserialize, Serialize};
+// Substrate
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{H160, H256};
+use sp_runtime::{traits::Block as BlockT, BoundedVec};
+// Local
+use pallet_did::{ExternalIdRecord, ExternalIdValue, ListMembership, Scope};
+use pallet_did_runtime_api::DidApi as DidRuntimeApi;
+
+/// An external id linked to an account. Exactly one of `externalId`, `commitment` and
+/// `merkleRoot` is set.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalIdLink<BlockNumber> {
+	pub provider: String,
+	pub external_id: Option<String>,
+	pub commitment: Option<H256>,
+	pub merkle_root: Option<H256>,
+	pub expires_at: Option<BlockNumber>,
+}
+
+/// A member of a list.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListMember<BlockNumber> {
+	pub account: H160,
+	pub expires_at: Option<BlockNumber>,
+}
+
+/// The providers or lists a manager may write.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ManagerScope {
+	All,
+	Only(Vec<String>),
+	None,
+}
+
+/// The role of a DID manager.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manager {
+	pub providers: ManagerScope,
+	pub lists: ManagerScope,
+	/// The manager can write nothing, see `pallet_did::ManagerRole::read_only`.
+	pub read_only: bool,
+}
+
+#[rpc(server)]
+pub trait DidApi<BlockHash, BlockNumber> {
+	/// The external ids `who` holds, by provider.
+	#[method(name = "did_externalIds")]
+	fn external_ids(
+		&self,
+		who: H160,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<ExternalIdLink<BlockNumber>>>;
+
+	/// The accounts holding `external_id` with `provider`.
+	#[method(name = "did_accountsOf")]
+	fn accounts_of(
+		&self,
+		provider: String,
+		external_id: String,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<H160>>;
+
+	/// The membership of `who` in `list_name`, if any.
+	#[method(name = "did_membership")]
+	fn membership(
+		&self,
+		list_name: String,
+		who: H160,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<ListMember<BlockNumber>>>;
+
+	/// Up to `limit` members of `list_name` after `start_after`. Pass the last account of a page
+	/// to get the next one.
+	#[method(name = "did_listMembers")]
+	fn list_members(
+		&self,
+		list_name: String,
+		start_after: Option<H160>,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<ListMember<BlockNumber>>>;
+
+	/// The role of the manager `who`, if it is one.
+	#[method(name = "did_managerRole")]
+	fn manager_role(&self, who: H160, at: Option<BlockHash>) -> RpcResult<Option<Manager>>;
+}
+
+/// Error codes of the DID RPC.
+const RUNTIME_ERROR: i32 = 1;
+const INVALID_PARAMS: i32 = 2;
+
+fn runtime_error(e: impl std::fmt::Display) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(
+		RUNTIME_ERROR,
+		"Unable to query DID data.",
+		Some(e.to_string()),
+	))
+	.into()
+}
+
+fn bounded<S: sp_core::Get<u32>>(value: String, what: &str) -> RpcResult<BoundedVec<u8, S>> {
+	value.into_bytes().try_into().map_err(|_| {
+		CallError::Custom(ErrorObject::owned(
+			INVALID_PARAMS,
+			format!("The {} is longer than {} bytes.", what, S::get()),
+			None::<()>,
+		))
+		.into()
+	})
+}
+
+fn text(bytes: &[u8]) -> String {
+	String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn manager_scope<S>(scope: Scope<BoundedVec<u8, S>>) -> ManagerScope {
+	match scope {
+		Scope::All => ManagerScope::All,
+		Scope::Only(items) => ManagerScope::Only(items.iter().map(|item| text(item)).collect()),
+		Scope::None => ManagerScope::None,
+	}
+}
+
+/// Serves the DID RPC from the `DidApi` of the client.
+pub struct Did<C, B> {
+	client: Arc<C>,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> Did<C, B> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self {
+			client,
+			_marker: Default::default(),
+		}
+	}
+}
+
+impl<C, Block, AccountId, BlockNumber> DidApiServer<<Block as BlockT>::Hash, BlockNumber>
+	for Did<C, (Block, AccountId)>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: DidRuntimeApi<Block, AccountId, BlockNumber>,
+	AccountId: Codec + From<H160> + Into<H160> + Send + Sync + 'static,
+	BlockNumber: Codec + Serialize + Send + Sync + 'static,
+{
+	fn external_ids(
+		&self,
+		who: H160,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<ExternalIdLink<BlockNumber>>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let records = self
+			.client
+			.runtime_api()
+			.external_ids(at, who.into())
+			.map_err(runtime_error)?;
+		Ok(records
+			.into_iter()
+			.map(|(provider, ExternalIdRecord { value, expires_at })| {
+				let mut link = ExternalIdLink {
+					provider: text(&provider),
+					external_id: None,
+					commitment: None,
+					merkle_root: None,
+					expires_at,
+				};
+				match value {
+					ExternalIdValue::Plain(external_id) => {
+						link.external_id = Some(text(&external_id))
+					}
+					ExternalIdValue::Commitment(commitment) => link.commitment = Some(commitment),
+					ExternalIdValue::MerkleRoot(root) => link.merkle_root = Some(root),
+				}
+				link
+			})
+			.collect())
+	}
+
+	fn accounts_of(
+		&self,
+		provider: String,
+		external_id: String,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<H160>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let accounts = self
+			.client
+			.runtime_api()
+			.accounts_of(
+				at,
+				bounded(provider, "provider")?,
+				bounded(external_id, "external id")?,
+			)
+			.map_err(runtime_error)?;
+		Ok(accounts.into_iter().map(Into::into).collect())
+	}
+
+	fn membership(
+		&self,
+		list_name: String,
+		who: H160,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<ListMember<BlockNumber>>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let membership = self
+			.client
+			.runtime_api()
+			.membership(at, bounded(list_name, "list name")?, who.into())
+			.map_err(runtime_error)?;
+		Ok(membership.map(|ListMembership { expires_at }| ListMember {
+			account: who,
+			expires_at,
+		}))
+	}
+
+	fn list_members(
+		&self,
+		list_name: String,
+		start_after: Option<H160>,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<ListMember<BlockNumber>>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let members = self
+			.client
+			.runtime_api()
+			.list_members(
+				at,
+				bounded(list_name, "list name")?,
+				start_after.map(Into::into),
+				limit,
+			)
+			.map_err(runtime_error)?;
+		Ok(members
+			.into_iter()
+			.map(|(who, ListMembership { expires_at })| ListMember {
+				account: who.into(),
+				expires_at,
+			})
+			.collect())
+	}
+
+	fn manager_role(
+		&self,
+		who: H160,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<Manager>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let role = self
+			.client
+			.runtime_api()
+			.manager_role(at, who.into())
+			.map_err(runtime_error)?;
+		Ok(role.map(|role| Manager {
+			read_only: role.is_read_only(),
+			providers: manager_scope(role.providers),
+			lists: manager_scope(role.lists),
+		}))
+	}
+}
@@ -21,10 +21,14 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_inherents::CreateInherentDataProviders;
 use sp_runtime::traits::Block as BlockT;
 // Runtime
-use impetus_runtime::{opaque::Block, AccountId, Balance, Hash, Nonce};
+use impetus_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Nonce};
 
+mod did;
 mod eth;
-pub use self::eth::{create_eth, overrides_handle, EthDeps};
+pub use self::{
+	did::{Did, DidApiServer},
+	eth::{create_eth, overrides_handle, EthDeps},
+};
 
 /// Full client dependencies.
 pub struct FullDeps<C, P, A: ChainApi, CT, CIDP> {
@@ -68,6 +72,7 @@ where
 	C::Api: sp_consensus_aura::AuraApi<Block, AuraId>,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_did_runtime_api::DidApi<Block, AccountId, BlockNumber>,
 	C::Api: fp_rpc::ConvertTransactionRuntimeApi<Block>,
 	C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
@@ -92,7 +97,8 @@ where
 	} = deps;
 
 	io.merge(System::new(client.clone(), pool, deny_unsafe).into_rpc())?;
-	io.merge(TransactionPayment::new(client).into_rpc())?;
+	io.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+	io.merge(Did::<_, (Block, AccountId)>::new(client).into_rpc())?;
 
 	if let Some(command_sink) = command_sink {
 		io.merge(
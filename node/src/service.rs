@@ -693,10 +693,8 @@ pub async fn build_full(
 	eth_config: EthConfiguration,
 	sealing: Option<Sealing>,
 ) -> Result<TaskManager, ServiceError> {
-	new_full::<impetus_runtime::RuntimeApi, TemplateRuntimeExecutor>(
-		config, eth_config, sealing,
-	)
-	.await
+	new_full::<impetus_runtime::RuntimeApi, TemplateRuntimeExecutor>(config, eth_config, sealing)
+		.await
 }
 
 pub fn new_chain_ops(
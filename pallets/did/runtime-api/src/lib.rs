@@ -0,0 +1,35 @@
+//! Runtime API for reading the DID data of `pallet-did`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use pallet_did::{ExternalId, ExternalIdRecord, ListMembership, ListName, ManagerRole, Provider};
+use scale_codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// Reads of DID links, lists and managers. Expired records are left out.
+	pub trait DidApi<AccountId, BlockNumber>
+	where
+		AccountId: Codec,
+		BlockNumber: Codec,
+	{
+		/// The external ids `who` holds, by provider.
+		fn external_ids(who: AccountId) -> Vec<(Provider, ExternalIdRecord<BlockNumber>)>;
+
+		/// The accounts holding the plain `external_id` with `provider`.
+		fn accounts_of(provider: Provider, external_id: ExternalId) -> Vec<AccountId>;
+
+		/// The membership of `who` in `list_name`, if any.
+		fn membership(list_name: ListName, who: AccountId) -> Option<ListMembership<BlockNumber>>;
+
+		/// Up to `limit` members of `list_name` stored after `start_after`.
+		fn list_members(
+			list_name: ListName,
+			start_after: Option<AccountId>,
+			limit: u32,
+		) -> Vec<(AccountId, ListMembership<BlockNumber>)>;
+
+		/// The role of the manager `who`, if it is one.
+		fn manager_role(who: AccountId) -> Option<ManagerRole>;
+	}
+}
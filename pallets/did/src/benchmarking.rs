@@ -0,0 +1,298 @@
+//! Benchmarking setup for pallet-did
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_support::{traits::DefensiveTruncateFrom, BoundedVec};
+use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
+use sp_core::H256;
+use sp_runtime::traits::Bounded;
+use sp_std::vec;
+
+const SEED: u32 = 0;
+
+fn manager<T: Config>() -> T::AccountId {
+	let caller: T::AccountId = whitelisted_caller();
+	PalletManager::<T>::insert(&caller, ManagerRole::full());
+	let _ = T::Currency::set_balance(&caller, BalanceOf::<T>::max_value() / 2u32.into());
+	caller
+}
+
+fn provider() -> Provider {
+	Provider::defensive_truncate_from(b"provider".to_vec())
+}
+
+fn external_id() -> ExternalId {
+	ExternalId::defensive_truncate_from(vec![7u8; 128])
+}
+
+fn list<T: Config>(owner: &T::AccountId) -> ListName {
+	let list_name = ListName::defensive_truncate_from(b"list".to_vec());
+	Lists::<T>::insert(
+		&list_name,
+		ListInfo {
+			owner: owner.clone(),
+			description: ListDescription::defensive_truncate_from(vec![0u8; 256]),
+			kind: ListKind::Allowlist,
+			max_size: 0,
+			member_count: 0,
+		},
+	);
+	list_name
+}
+
+fn expiry<T: Config>() -> Option<BlockNumberFor<T>> {
+	Some(frame_system::Pallet::<T>::block_number() + 100u32.into())
+}
+
+benchmarks! {
+	add_user_address {
+		let caller = manager::<T>();
+		let user: T::AccountId = account("user", 0, SEED);
+		Pallet::<T>::set_user_address(
+			&caller,
+			user.clone(),
+			provider(),
+			ExternalIdValue::Plain(external_id()),
+			expiry::<T>(),
+		)?;
+	}: _(RawOrigin::Signed(caller), user.clone(), provider().to_vec(), external_id().to_vec(), Some(frame_system::Pallet::<T>::block_number() + 200u32.into()))
+	verify {
+		assert!(!Pallet::<T>::external_id(user, provider()).is_empty());
+	}
+
+	remove_user_address {
+		let caller = manager::<T>();
+		let user: T::AccountId = account("user", 0, SEED);
+		Pallet::<T>::set_user_address(
+			&caller,
+			user.clone(),
+			provider(),
+			ExternalIdValue::Plain(external_id()),
+			expiry::<T>(),
+		)?;
+	}: _(RawOrigin::Signed(caller), user.clone(), provider().to_vec())
+	verify {
+		assert!(!ExternalIdAddress::<T>::contains_key(user, provider()));
+	}
+
+	add_user_to_list {
+		let caller = manager::<T>();
+		let list_name = list::<T>(&caller);
+		let user: T::AccountId = account("user", 0, SEED);
+	}: _(RawOrigin::Signed(caller), list_name.to_vec(), user.clone(), expiry::<T>())
+	verify {
+		assert!(Pallet::<T>::user_list(list_name, user));
+	}
+
+	remove_user_from_list {
+		let caller = manager::<T>();
+		let list_name = list::<T>(&caller);
+		let user: T::AccountId = account("user", 0, SEED);
+		Pallet::<T>::do_add_user_to_list(&caller, &list_name, &user, expiry::<T>())?;
+	}: _(RawOrigin::Signed(caller), list_name.to_vec(), user.clone())
+	verify {
+		assert!(!Pallet::<T>::user_list(list_name, user));
+	}
+
+	add_did_manager {
+		let origin =
+			T::ManagerOrigin::try_successful_origin().map_err(|_| "no successful origin")?;
+		let new_manager: T::AccountId = account("manager", 0, SEED);
+	}: _<T::RuntimeOrigin>(origin, new_manager.clone(), ManagerRole::full())
+	verify {
+		assert!(Pallet::<T>::is_manager(&new_manager));
+	}
+
+	remove_did_manager {
+		let origin =
+			T::ManagerOrigin::try_successful_origin().map_err(|_| "no successful origin")?;
+		let old_manager: T::AccountId = account("manager", 0, SEED);
+		PalletManager::<T>::insert(&old_manager, ManagerRole::full());
+	}: _<T::RuntimeOrigin>(origin, old_manager.clone())
+	verify {
+		assert!(!Pallet::<T>::is_manager(&old_manager));
+	}
+
+	renew_user_address {
+		let caller = manager::<T>();
+		let user: T::AccountId = account("user", 0, SEED);
+		Pallet::<T>::set_user_address(
+			&caller,
+			user.clone(),
+			provider(),
+			ExternalIdValue::Plain(external_id()),
+			expiry::<T>(),
+		)?;
+		let expires_at = Some(frame_system::Pallet::<T>::block_number() + 200u32.into());
+	}: _(RawOrigin::Signed(caller), user.clone(), provider().to_vec(), expires_at)
+	verify {
+		assert_eq!(ExternalIdAddress::<T>::get(user, provider()).unwrap().expires_at, expires_at);
+	}
+
+	renew_user_in_list {
+		let caller = manager::<T>();
+		let list_name = list::<T>(&caller);
+		let user: T::AccountId = account("user", 0, SEED);
+		Pallet::<T>::do_add_user_to_list(&caller, &list_name, &user, expiry::<T>())?;
+		let expires_at = Some(frame_system::Pallet::<T>::block_number() + 200u32.into());
+	}: _(RawOrigin::Signed(caller), list_name.to_vec(), user.clone(), expires_at)
+	verify {
+		assert_eq!(UserList::<T>::get(list_name, user).unwrap().expires_at, expires_at);
+	}
+
+	add_user_commitment {
+		let caller = manager::<T>();
+		let user: T::AccountId = account("user", 0, SEED);
+		Pallet::<T>::set_user_address(
+			&caller,
+			user.clone(),
+			provider(),
+			ExternalIdValue::Plain(external_id()),
+			expiry::<T>(),
+		)?;
+		let commitment = ExternalIdValue::MerkleRoot(H256::repeat_byte(1));
+	}: _(RawOrigin::Signed(caller), user.clone(), provider().to_vec(), commitment.clone(), Some(frame_system::Pallet::<T>::block_number() + 200u32.into()))
+	verify {
+		assert_eq!(ExternalIdAddress::<T>::get(user, provider()).unwrap().value, commitment);
+	}
+
+	verify_user_address {
+		let caller = manager::<T>();
+		let user: T::AccountId = account("user", 0, SEED);
+		let salt = H256::repeat_byte(2);
+		let proof = vec![H256::repeat_byte(3); MAX_PROOF_DEPTH as usize];
+		let leaf = Pallet::<T>::commitment_of(&external_id(), salt);
+		let root = Pallet::<T>::merkle_root_of(leaf, &proof);
+		Pallet::<T>::set_user_address(
+			&caller,
+			user.clone(),
+			provider(),
+			ExternalIdValue::MerkleRoot(root),
+			None,
+		)?;
+	}: _(RawOrigin::Signed(caller), user, provider().to_vec(), external_id().to_vec(), salt, proof)
+
+	create_list {
+		let caller = manager::<T>();
+		let list_name = ListName::defensive_truncate_from(b"list".to_vec());
+	}: _(RawOrigin::Signed(caller), list_name.to_vec(), vec![0u8; 256], ListKind::Allowlist, 0)
+	verify {
+		assert!(Lists::<T>::contains_key(list_name));
+	}
+
+	update_list {
+		let caller = manager::<T>();
+		let list_name = list::<T>(&caller);
+	}: _(RawOrigin::Signed(caller), list_name.to_vec(), vec![1u8; 256], 10)
+	verify {
+		assert_eq!(Lists::<T>::get(list_name).unwrap().max_size, 10);
+	}
+
+	destroy_list {
+		let caller = manager::<T>();
+		let list_name = list::<T>(&caller);
+	}: _(RawOrigin::Signed(caller), list_name.to_vec())
+	verify {
+		assert!(!Lists::<T>::contains_key(list_name));
+	}
+
+	add_users_to_list {
+		let n in 1 .. T::MaxBatchSize::get();
+		let caller = manager::<T>();
+		let list_name = list::<T>(&caller);
+		let users: Vec<_> = (0..n).map(|i| (account("user", i, SEED), expiry::<T>())).collect();
+		let users = BoundedVec::<_, T::MaxBatchSize>::defensive_truncate_from(users);
+	}: _(RawOrigin::Signed(caller), list_name.to_vec(), users)
+	verify {
+		assert_eq!(Lists::<T>::get(list_name).unwrap().member_count, n);
+	}
+
+	remove_users_from_list {
+		let n in 1 .. T::MaxBatchSize::get();
+		let caller = manager::<T>();
+		let list_name = list::<T>(&caller);
+		let users: Vec<T::AccountId> = (0..n).map(|i| account("user", i, SEED)).collect();
+		for user in users.iter() {
+			Pallet::<T>::do_add_user_to_list(&caller, &list_name, user, expiry::<T>())?;
+		}
+		let users = BoundedVec::<_, T::MaxBatchSize>::defensive_truncate_from(users);
+	}: _(RawOrigin::Signed(caller), list_name.to_vec(), users)
+	verify {
+		assert_eq!(Lists::<T>::get(list_name).unwrap().member_count, 0);
+	}
+
+	add_user_addresses {
+		let n in 1 .. T::MaxBatchSize::get();
+		let caller = manager::<T>();
+		let entries: Vec<_> =
+			(0..n).map(|i| (account("user", i, SEED), external_id(), expiry::<T>())).collect();
+		let entries = BoundedVec::<_, T::MaxBatchSize>::defensive_truncate_from(entries);
+	}: _(RawOrigin::Signed(caller), provider().to_vec(), entries)
+
+	approve_migration {
+		let caller: T::AccountId = whitelisted_caller();
+		let to: T::AccountId = account("to", 0, SEED);
+	}: _(RawOrigin::Signed(caller.clone()), to.clone())
+	verify {
+		assert_eq!(PendingMigrations::<T>::get(caller), Some(to));
+	}
+
+	cancel_migration {
+		let caller: T::AccountId = whitelisted_caller();
+		PendingMigrations::<T>::insert(&caller, account::<T::AccountId>("to", 0, SEED));
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(!PendingMigrations::<T>::contains_key(caller));
+	}
+
+	accept_migration {
+		let e in 0 .. T::MaxBatchSize::get();
+		let m in 0 .. T::MaxBatchSize::get();
+		let payer = manager::<T>();
+		let from: T::AccountId = account("from", 0, SEED);
+		let to: T::AccountId = account("to", 0, SEED);
+		for i in 0..e {
+			Pallet::<T>::set_user_address(
+				&payer,
+				from.clone(),
+				Provider::defensive_truncate_from(i.to_le_bytes().to_vec()),
+				ExternalIdValue::Plain(external_id()),
+				expiry::<T>(),
+			)?;
+		}
+		for i in 0..m {
+			let list_name = ListName::defensive_truncate_from(i.to_le_bytes().to_vec());
+			Lists::<T>::insert(
+				&list_name,
+				ListInfo {
+					owner: payer.clone(),
+					description: ListDescription::default(),
+					kind: ListKind::Denylist,
+					max_size: 0,
+					member_count: 0,
+				},
+			);
+			Pallet::<T>::do_add_user_to_list(&payer, &list_name, &from, expiry::<T>())?;
+		}
+		PendingMigrations::<T>::insert(&from, &to);
+		let witness = MigrationWitness { external_ids: e, memberships: m };
+	}: _(RawOrigin::Signed(to.clone()), from.clone(), witness)
+	verify {
+		assert_eq!(ExternalIdAddress::<T>::iter_prefix(&to).count() as u32, e);
+		assert_eq!(Memberships::<T>::iter_prefix(&to).count() as u32, m);
+		assert!(Memberships::<T>::iter_prefix(&from).next().is_none());
+	}
+
+	pay_deposit {
+		let payer = manager::<T>();
+		let user: T::AccountId = whitelisted_caller();
+		let list_name = list::<T>(&payer);
+		Pallet::<T>::do_add_user_to_list(&payer, &list_name, &user, expiry::<T>())?;
+		let record = DidRecord::ListMembership { list_name, who: user.clone() };
+	}: _(RawOrigin::Signed(user.clone()), record.clone())
+	verify {
+		assert_eq!(Deposits::<T>::get(record).map(|(payer, _)| payer), Some(user));
+	}
+
+	impl_benchmark_test_suite!(Pallet, crate::mock::ExtBuilder::default().build(), crate::mock::Test);
+}
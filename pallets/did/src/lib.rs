@@ -1,12 +1,42 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+pub mod migrations;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+pub mod weights;
+
+use frame_support::traits::{
+	fungible::{Inspect, Mutate, MutateHold},
+	tokens::Precision,
+	Contains, EnsureOrigin, Get,
+};
+use frame_system::RawOrigin;
 pub use pallet::*;
-use sp_std::vec::Vec;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::{marker::PhantomData, vec::Vec};
+pub use weights::WeightInfo;
+
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
 	use frame_support::{pallet_prelude::*, traits::DefensiveTruncateFrom};
 	use frame_system::pallet_prelude::*;
+	use sp_core::H256;
+	use sp_runtime::traits::Saturating;
+
+	/// The in-code storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
@@ -16,39 +46,428 @@ pub mod pallet {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		/// The manager origin.
 		type ManagerOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+		/// The most entries a single batch call can carry.
+		#[pallet::constant]
+		type MaxBatchSize: Get<u32>;
+		/// The currency the storage deposits are held in.
+		type Currency: Mutate<Self::AccountId>
+			+ MutateHold<Self::AccountId, Reason = Self::RuntimeHoldReason>;
+		/// The overarching hold reason.
+		type RuntimeHoldReason: From<HoldReason>;
+		/// The deposit held for every external id or list membership.
+		#[pallet::constant]
+		type DepositPerItem: Get<BalanceOf<Self>>;
+		/// The deposit held for every byte an external id or list membership takes in storage.
+		#[pallet::constant]
+		type DepositPerByte: Get<BalanceOf<Self>>;
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// A reason for the pallet placing a hold on funds.
+	#[pallet::composite_enum]
+	pub enum HoldReason {
+		/// Funds held for the storage of a DID record.
+		#[codec(index = 0)]
+		StorageDeposit,
 	}
 
 	pub type ExternalId = BoundedVec<u8, ConstU32<128>>;
 	pub type Provider = BoundedVec<u8, ConstU32<32>>;
 	pub type ListName = BoundedVec<u8, ConstU32<32>>;
 
+	/// The deepest Merkle proof accepted when verifying an external id.
+	pub const MAX_PROOF_DEPTH: u32 = 32;
+
+	/// The most members returned by one page of [`Pallet::list_members_page`].
+	pub const MAX_LIST_PAGE: u32 = 1000;
+
+	/// The most expired records a full list sweeps before it turns a new member away.
+	pub const MAX_FULL_LIST_SWEEP: u32 = 16;
+
+	/// The most expired records of the new account an account migration removes.
+	pub const MAX_MIGRATION_SWEEP: u32 = 16;
+
+	/// The first byte hashed into a commitment or Merkle leaf.
+	pub const LEAF_PREFIX: u8 = 0x00;
+
+	/// The first byte hashed into an inner Merkle node, so no node can be opened as a leaf.
+	pub const NODE_PREFIX: u8 = 0x01;
+
+	/// How an external id is kept on-chain.
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		PartialEq,
+		Eq,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum ExternalIdValue {
+		/// The external id in plain text.
+		Plain(ExternalId),
+		/// `keccak256(0x00 ++ external_id ++ salt)`, see [`Pallet::commitment_of`].
+		Commitment(H256),
+		/// Root of a Merkle tree with one `keccak256(0x00 ++ attribute ++ salt)` leaf per
+		/// attribute. Inner nodes are `keccak256(0x01 ++ left ++ right)` with the pair in sorted
+		/// order, so proofs carry no path bits and no inner node opens as a leaf.
+		MerkleRoot(H256),
+	}
+
+	/// An external id linked to an account, valid until `expires_at` if set.
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		PartialEq,
+		Eq,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	pub struct ExternalIdRecord<BlockNumber> {
+		pub value: ExternalIdValue,
+		pub expires_at: Option<BlockNumber>,
+	}
+
+	/// A list membership, valid until `expires_at` if set.
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		PartialEq,
+		Eq,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	pub struct ListMembership<BlockNumber> {
+		pub expires_at: Option<BlockNumber>,
+	}
+
+	/// The most providers or lists a single manager role can name.
+	pub type MaxScopeItems = ConstU32<16>;
+
+	/// The part of the DID data a manager may write.
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		PartialEq,
+		Eq,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum Scope<Item> {
+		/// Every item.
+		All,
+		/// Only the named items.
+		Only(BoundedVec<Item, MaxScopeItems>),
+		/// No item.
+		None,
+	}
+
+	impl<Item: PartialEq> Scope<Item> {
+		pub fn allows(&self, item: &Item) -> bool {
+			match self {
+				Scope::All => true,
+				Scope::Only(items) => items.contains(item),
+				Scope::None => false,
+			}
+		}
+
+		/// Whether no item is allowed at all.
+		pub fn allows_none(&self) -> bool {
+			match self {
+				Scope::All => false,
+				Scope::Only(items) => items.is_empty(),
+				Scope::None => true,
+			}
+		}
+	}
+
+	/// What a manager is allowed to do.
+	///
+	/// A read-only manager, see [`ManagerRole::read_only`], has neither providers nor lists in
+	/// scope. Every call writing DID data fails for it with `ProviderNotInScope` or
+	/// `ListNotInScope`, but [`Pallet::is_manager`] still reports it, so front ends and
+	/// contracts can open admin views to it without giving it write access.
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		PartialEq,
+		Eq,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct ManagerRole {
+		/// Providers whose external ids the manager can add, renew and remove.
+		pub providers: Scope<Provider>,
+		/// Lists whose members the manager can add, renew and remove.
+		pub lists: Scope<ListName>,
+	}
+
+	impl ManagerRole {
+		/// A manager of every provider and every list.
+		pub fn full() -> Self {
+			ManagerRole {
+				providers: Scope::All,
+				lists: Scope::All,
+			}
+		}
+
+		/// A read-only manager, which can write nothing.
+		pub fn read_only() -> Self {
+			ManagerRole {
+				providers: Scope::None,
+				lists: Scope::None,
+			}
+		}
+
+		/// Whether the role covers no provider and no list.
+		pub fn is_read_only(&self) -> bool {
+			self.providers.allows_none() && self.lists.allows_none()
+		}
+	}
+
+	pub type ListDescription = BoundedVec<u8, ConstU32<256>>;
+
+	/// Users added to a list in one call, each with its own expiry.
+	pub type ListEntries<T> = BoundedVec<
+		(
+			<T as frame_system::Config>::AccountId,
+			Option<BlockNumberFor<T>>,
+		),
+		<T as Config>::MaxBatchSize,
+	>;
+
+	/// External ids linked in one call, each to its user with its own expiry.
+	pub type ExternalIdEntries<T> = BoundedVec<
+		(
+			<T as frame_system::Config>::AccountId,
+			ExternalId,
+			Option<BlockNumberFor<T>>,
+		),
+		<T as Config>::MaxBatchSize,
+	>;
+
+	/// An external id linked at genesis: the user, the provider, the id and its expiry.
+	pub type GenesisExternalId<T> = (
+		<T as frame_system::Config>::AccountId,
+		Provider,
+		ExternalIdValue,
+		Option<BlockNumberFor<T>>,
+	);
+
+	/// How membership of a list is read when it gates something.
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		PartialEq,
+		Eq,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	#[derive(Default)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub enum ListKind {
+		/// Only members are let through.
+		#[default]
+		Allowlist,
+		/// Everyone but the members is let through.
+		Denylist,
+	}
+
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		PartialEq,
+		Eq,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	pub struct ListInfo<AccountId> {
+		pub owner: AccountId,
+		pub description: ListDescription,
+		pub kind: ListKind,
+		/// The most members the list may hold, zero for no limit.
+		pub max_size: u32,
+		/// Members, including expired ones the sweeper has not removed yet. A full list
+		/// sweeps due records before it turns a new member away.
+		pub member_count: u32,
+	}
+
+	/// A list to create at genesis, together with its first members.
+	#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct GenesisList<AccountId> {
+		pub name: ListName,
+		pub owner: AccountId,
+		pub description: ListDescription,
+		pub kind: ListKind,
+		pub max_size: u32,
+		pub members: Vec<AccountId>,
+	}
+
+	/// The number of records an account holds, declared up front when moving them.
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		Copy,
+		PartialEq,
+		Eq,
+		Default,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	pub struct MigrationWitness {
+		pub external_ids: u32,
+		pub memberships: u32,
+	}
+
+	/// A record that can expire, used as the key of the expiry schedule.
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		PartialEq,
+		Eq,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	pub enum DidRecord<AccountId> {
+		ExternalId { who: AccountId, provider: Provider },
+		ListMembership { list_name: ListName, who: AccountId },
+	}
+
 	#[pallet::error]
 	pub enum Error<T> {
 		NotAllowedToRemove,
 		NotAllowedToMutate,
 		InvalidOrigin,
+		/// The expiry block is not in the future.
+		ExpiryInPast,
+		/// The record does not exist or has already expired.
+		RecordNotFound,
+		/// The presented external id does not match the stored commitment.
+		CommitmentMismatch,
+		/// The Merkle proof is deeper than `MAX_PROOF_DEPTH`.
+		ProofTooLong,
+		/// The manager role does not cover this provider.
+		ProviderNotInScope,
+		/// The manager role does not cover this list.
+		ListNotInScope,
+		ListAlreadyExists,
+		ListNotFound,
+		/// The list holds `max_size` members already.
+		ListFull,
+		/// Only lists without members can be destroyed.
+		ListNotEmpty,
+		MaxSizeBelowMemberCount,
+		/// An account cannot migrate to itself.
+		MigrationToSelf,
+		/// No migration to the caller was approved by that account.
+		MigrationNotApproved,
+		/// The new account already holds DID records.
+		AccountHasRecords,
+		/// The witness counts fewer records than the old account holds.
+		BadWitness,
 	}
 
 	#[pallet::storage]
 	#[pallet::getter(fn did_manager)]
-	pub type PalletManager<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, bool, ValueQuery>;
+	pub type PalletManager<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, ManagerRole, OptionQuery>;
 
 	#[pallet::storage]
-	#[pallet::getter(fn external_id)]
 	pub type ExternalIdAddress<T: Config> = StorageDoubleMap<
 		_,
 		Twox64Concat,
 		T::AccountId,
 		Twox64Concat,
 		Provider,
-		ExternalId,
-		ValueQuery,
+		ExternalIdRecord<BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// Reverse index of plain external ids to the accounts holding them.
+	#[pallet::storage]
+	pub type ExternalIdOwners<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		(Provider, ExternalId),
+		Twox64Concat,
+		T::AccountId,
+		(),
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	pub type UserList<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		ListName,
+		Twox64Concat,
+		T::AccountId,
+		ListMembership<BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// Reverse index of `UserList`, the lists each account is a member of.
+	#[pallet::storage]
+	pub type Memberships<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, ListName, (), OptionQuery>;
+
+	/// Account migrations approved by the old account, waiting for the new one to accept.
+	#[pallet::storage]
+	pub type PendingMigrations<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, T::AccountId, OptionQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn list)]
+	pub type Lists<T: Config> =
+		StorageMap<_, Twox64Concat, ListName, ListInfo<T::AccountId>, OptionQuery>;
+
+	/// Records scheduled to expire, keyed by their expiry block.
+	#[pallet::storage]
+	pub type Expiries<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		Twox64Concat,
+		DidRecord<T::AccountId>,
+		(),
+		OptionQuery,
 	>;
 
+	/// Who paid the deposit for a record, and how much is held.
 	#[pallet::storage]
-	#[pallet::getter(fn user_list)]
-	pub type UserList<T: Config> =
-		StorageDoubleMap<_, Twox64Concat, ListName, Twox64Concat, T::AccountId, bool, ValueQuery>;
+	pub type Deposits<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		DidRecord<T::AccountId>,
+		(T::AccountId, BalanceOf<T>),
+		OptionQuery,
+	>;
+
+	/// The next block of `Expiries` the idle sweeper has to look at.
+	#[pallet::storage]
+	pub type ExpiryCursor<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -56,30 +475,105 @@ pub mod pallet {
 		AddedUserAddress {
 			who: T::AccountId,
 			provider: Provider,
+			expires_at: Option<BlockNumberFor<T>>,
 		},
 		RemovedUserAddress {
 			who: T::AccountId,
 			provider: Provider,
 		},
+		RenewedUserAddress {
+			who: T::AccountId,
+			provider: Provider,
+			expires_at: Option<BlockNumberFor<T>>,
+		},
+		UserAddressExpired {
+			who: T::AccountId,
+			provider: Provider,
+		},
+		ExternalIdVerified {
+			who: T::AccountId,
+			provider: Provider,
+		},
 		AddedUserToList {
 			who: T::AccountId,
 			list_name: ListName,
+			expires_at: Option<BlockNumberFor<T>>,
 		},
 		RemovedUserFromList {
 			who: T::AccountId,
 			list_name: ListName,
 		},
+		RenewedUserInList {
+			who: T::AccountId,
+			list_name: ListName,
+			expires_at: Option<BlockNumberFor<T>>,
+		},
+		UserListMembershipExpired {
+			who: T::AccountId,
+			list_name: ListName,
+		},
+		BatchAddedUsersToList {
+			list_name: ListName,
+			count: u32,
+		},
+		BatchRemovedUsersFromList {
+			list_name: ListName,
+			count: u32,
+		},
+		BatchAddedUserAddresses {
+			provider: Provider,
+			count: u32,
+		},
+		ListCreated {
+			list_name: ListName,
+			owner: T::AccountId,
+			kind: ListKind,
+		},
+		ListUpdated {
+			list_name: ListName,
+		},
+		ListDestroyed {
+			list_name: ListName,
+		},
+		DepositHeld {
+			who: T::AccountId,
+			amount: BalanceOf<T>,
+		},
+		DepositReleased {
+			who: T::AccountId,
+			amount: BalanceOf<T>,
+		},
 		AddedManager {
 			manager: T::AccountId,
+			role: ManagerRole,
 		},
 		RemovedManager {
 			manager: T::AccountId,
 		},
+		MigrationApproved {
+			from: T::AccountId,
+			to: T::AccountId,
+		},
+		MigrationCancelled {
+			from: T::AccountId,
+			to: T::AccountId,
+		},
+		AccountMigrated {
+			from: T::AccountId,
+			to: T::AccountId,
+			external_ids: u32,
+			memberships: u32,
+		},
 	}
 
+	/// Genesis records are stored without a deposit.
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
-		pub managers: Vec<T::AccountId>,
+		pub managers: Vec<(T::AccountId, ManagerRole)>,
+		/// Accounts allowed to write the external ids of a provider, added to their roles.
+		pub providers: Vec<(Provider, Vec<T::AccountId>)>,
+		pub lists: Vec<GenesisList<T::AccountId>>,
+		pub external_ids: Vec<GenesisExternalId<T>>,
 	}
 
 	#[cfg(feature = "std")]
@@ -87,6 +581,9 @@ pub mod pallet {
 		fn default() -> Self {
 			Self {
 				managers: vec![],
+				providers: vec![],
+				lists: vec![],
+				external_ids: vec![],
 			}
 		}
 	}
@@ -94,48 +591,124 @@ pub mod pallet {
 	#[pallet::genesis_build]
 	impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
 		fn build(&self) {
-			for manager in &self.managers {
-				<PalletManager<T>>::insert(manager, true);
+			for (manager, role) in &self.managers {
+				<PalletManager<T>>::insert(manager, role);
+			}
+			for (provider, managers) in &self.providers {
+				for manager in managers {
+					<PalletManager<T>>::mutate(manager, |maybe_role| {
+						let role = maybe_role.get_or_insert_with(ManagerRole::read_only);
+						if let Scope::None = role.providers {
+							role.providers = Scope::Only(Default::default());
+						}
+						if let Scope::Only(providers) = &mut role.providers {
+							if !providers.contains(provider) {
+								providers
+									.try_push(provider.clone())
+									.expect("genesis manager covers too many providers");
+							}
+						}
+					});
+				}
+			}
+			for list in &self.lists {
+				assert!(
+					!<Lists<T>>::contains_key(&list.name),
+					"duplicate genesis list"
+				);
+				assert!(
+					list.max_size == 0 || list.members.len() as u32 <= list.max_size,
+					"genesis list has more members than its max size"
+				);
+				<Lists<T>>::insert(
+					&list.name,
+					ListInfo {
+						owner: list.owner.clone(),
+						description: list.description.clone(),
+						kind: list.kind.clone(),
+						max_size: list.max_size,
+						member_count: 0,
+					},
+				);
+				for member in &list.members {
+					if <UserList<T>>::contains_key(&list.name, member) {
+						continue;
+					}
+					<UserList<T>>::insert(&list.name, member, ListMembership { expires_at: None });
+					<Memberships<T>>::insert(member, &list.name, ());
+					<Lists<T>>::mutate(&list.name, |maybe_list| {
+						if let Some(info) = maybe_list {
+							info.member_count.saturating_inc();
+						}
+					});
+				}
+			}
+			for (who, provider, value, expires_at) in &self.external_ids {
+				if let Some(expiry) = expires_at {
+					let record = DidRecord::ExternalId {
+						who: who.clone(),
+						provider: provider.clone(),
+					};
+					<Expiries<T>>::insert(expiry, record, ());
+				}
+				if let ExternalIdValue::Plain(external_id) = value {
+					<ExternalIdOwners<T>>::insert((provider, external_id), who, ());
+				}
+				<ExternalIdAddress<T>>::insert(
+					who,
+					provider,
+					ExternalIdRecord {
+						value: value.clone(),
+						expires_at: *expires_at,
+					},
+				);
 			}
 		}
 	}
 
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::sweep_expired(n, remaining_weight)
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		#[pallet::call_index(0)]
-		#[pallet::weight((10_100, DispatchClass::Normal))]
+		#[pallet::weight((T::WeightInfo::add_user_address(), DispatchClass::Normal))]
 		pub fn add_user_address(
 			origin: OriginFor<T>,
 			user: T::AccountId,
 			provider: Vec<u8>,
 			external_id: Vec<u8>,
+			expires_at: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			let is_manager = <PalletManager<T>>::get(who);
-			ensure!(is_manager, Error::<T>::InvalidOrigin);
 			let provider_bounded: Provider = Provider::defensive_truncate_from(provider.clone());
+			Self::ensure_provider_manager(&who, &provider_bounded)?;
 			let external_id_bounded: ExternalId =
 				ExternalId::defensive_truncate_from(external_id.clone());
-			<ExternalIdAddress<T>>::insert(&user, &provider_bounded, external_id_bounded);
-			Self::deposit_event(Event::AddedUserAddress {
-				who: user,
-				provider: provider_bounded,
-			});
-			Ok(())
+			Self::do_add_user_address(
+				&who,
+				user,
+				provider_bounded,
+				ExternalIdValue::Plain(external_id_bounded),
+				expires_at,
+			)
 		}
 
 		#[pallet::call_index(1)]
-		#[pallet::weight((10_100, DispatchClass::Normal))]
+		#[pallet::weight((T::WeightInfo::remove_user_address(), DispatchClass::Normal))]
 		pub fn remove_user_address(
 			origin: OriginFor<T>,
 			user: T::AccountId,
 			provider: Vec<u8>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			let is_manager = <PalletManager<T>>::get(who);
-			ensure!(is_manager, Error::<T>::InvalidOrigin);
 			let provider_bounded: Provider = Provider::defensive_truncate_from(provider.clone());
-			<ExternalIdAddress<T>>::remove(&user, &provider_bounded);
+			Self::ensure_provider_manager(&who, &provider_bounded)?;
+			Self::do_remove_user_address(&user, &provider_bounded);
 			Self::deposit_event(Event::RemovedUserAddress {
 				who: user,
 				provider: provider_bounded,
@@ -144,36 +717,40 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(2)]
-		#[pallet::weight((10_100, DispatchClass::Normal))]
+		#[pallet::weight((
+			T::WeightInfo::add_user_to_list().saturating_add(Pallet::<T>::full_list_sweep_weight()),
+			DispatchClass::Normal
+		))]
 		pub fn add_user_to_list(
 			origin: OriginFor<T>,
 			list_name: Vec<u8>,
 			user: T::AccountId,
+			expires_at: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			let is_manager = <PalletManager<T>>::get(who);
-			ensure!(is_manager, Error::<T>::InvalidOrigin);
+			Self::ensure_expiry(expires_at)?;
 			let list_name_bounded: ListName = ListName::defensive_truncate_from(list_name.clone());
-			<UserList<T>>::insert(&list_name_bounded, &user, true);
+			Self::ensure_list_manager(&who, &list_name_bounded)?;
+			Self::do_add_user_to_list(&who, &list_name_bounded, &user, expires_at)?;
 			Self::deposit_event(Event::AddedUserToList {
 				who: user,
 				list_name: list_name_bounded,
+				expires_at,
 			});
 			Ok(())
 		}
 
 		#[pallet::call_index(3)]
-		#[pallet::weight((10_100, DispatchClass::Normal))]
+		#[pallet::weight((T::WeightInfo::remove_user_from_list(), DispatchClass::Normal))]
 		pub fn remove_user_from_list(
 			origin: OriginFor<T>,
 			list_name: Vec<u8>,
 			user: T::AccountId,
 		) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			let is_manager = <PalletManager<T>>::get(who);
-			ensure!(is_manager, Error::<T>::InvalidOrigin);
 			let list_name_bounded: ListName = ListName::defensive_truncate_from(list_name.clone());
-			<UserList<T>>::remove(&list_name_bounded, &user);
+			Self::ensure_list_manager(&who, &list_name_bounded)?;
+			Self::do_remove_user_from_list(&list_name_bounded, &user);
 			Self::deposit_event(Event::RemovedUserFromList {
 				who: user,
 				list_name: list_name_bounded,
@@ -182,21 +759,1133 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(4)]
-		#[pallet::weight((10_100, DispatchClass::Normal))]
-		pub fn add_did_manager(origin: OriginFor<T>, manager: T::AccountId) -> DispatchResult {
+		#[pallet::weight((T::WeightInfo::add_did_manager(), DispatchClass::Normal))]
+		pub fn add_did_manager(
+			origin: OriginFor<T>,
+			manager: T::AccountId,
+			role: ManagerRole,
+		) -> DispatchResult {
 			T::ManagerOrigin::ensure_origin(origin)?;
-			<PalletManager<T>>::insert(&manager, true);
-			Self::deposit_event(Event::AddedManager { manager });
+			<PalletManager<T>>::insert(&manager, &role);
+			Self::deposit_event(Event::AddedManager { manager, role });
 			Ok(())
 		}
 
 		#[pallet::call_index(5)]
-		#[pallet::weight((10_100, DispatchClass::Normal))]
+		#[pallet::weight((T::WeightInfo::remove_did_manager(), DispatchClass::Normal))]
 		pub fn remove_did_manager(origin: OriginFor<T>, manager: T::AccountId) -> DispatchResult {
 			T::ManagerOrigin::ensure_origin(origin)?;
 			<PalletManager<T>>::remove(&manager);
 			Self::deposit_event(Event::RemovedManager { manager });
 			Ok(())
 		}
+
+		/// Set a new expiry on an external id that has not expired yet.
+		///
+		/// `None` makes the link permanent.
+		#[pallet::call_index(6)]
+		#[pallet::weight((T::WeightInfo::renew_user_address(), DispatchClass::Normal))]
+		pub fn renew_user_address(
+			origin: OriginFor<T>,
+			user: T::AccountId,
+			provider: Vec<u8>,
+			expires_at: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_expiry(expires_at)?;
+			let provider_bounded: Provider = Provider::defensive_truncate_from(provider.clone());
+			Self::ensure_provider_manager(&who, &provider_bounded)?;
+			<ExternalIdAddress<T>>::try_mutate(&user, &provider_bounded, |maybe_record| {
+				let record = maybe_record
+					.as_mut()
+					.filter(|record| Self::is_active(record.expires_at))
+					.ok_or(Error::<T>::RecordNotFound)?;
+				Self::reschedule(
+					DidRecord::ExternalId {
+						who: user.clone(),
+						provider: provider_bounded.clone(),
+					},
+					record.expires_at,
+					expires_at,
+				);
+				record.expires_at = expires_at;
+				Ok::<_, DispatchError>(())
+			})?;
+			Self::deposit_event(Event::RenewedUserAddress {
+				who: user,
+				provider: provider_bounded,
+				expires_at,
+			});
+			Ok(())
+		}
+
+		/// Set a new expiry on a list membership that has not expired yet.
+		///
+		/// `None` makes the membership permanent.
+		#[pallet::call_index(7)]
+		#[pallet::weight((T::WeightInfo::renew_user_in_list(), DispatchClass::Normal))]
+		pub fn renew_user_in_list(
+			origin: OriginFor<T>,
+			list_name: Vec<u8>,
+			user: T::AccountId,
+			expires_at: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_expiry(expires_at)?;
+			let list_name_bounded: ListName = ListName::defensive_truncate_from(list_name.clone());
+			Self::ensure_list_manager(&who, &list_name_bounded)?;
+			<UserList<T>>::try_mutate(&list_name_bounded, &user, |maybe_membership| {
+				let membership = maybe_membership
+					.as_mut()
+					.filter(|membership| Self::is_active(membership.expires_at))
+					.ok_or(Error::<T>::RecordNotFound)?;
+				Self::reschedule(
+					DidRecord::ListMembership {
+						list_name: list_name_bounded.clone(),
+						who: user.clone(),
+					},
+					membership.expires_at,
+					expires_at,
+				);
+				membership.expires_at = expires_at;
+				Ok::<_, DispatchError>(())
+			})?;
+			Self::deposit_event(Event::RenewedUserInList {
+				who: user,
+				list_name: list_name_bounded,
+				expires_at,
+			});
+			Ok(())
+		}
+
+		/// Link a hidden external id to `user`.
+		///
+		/// Only `commitment` is stored, either a salted hash of one external id or the Merkle root
+		/// of several salted attributes. A plain `value` is accepted as well.
+		#[pallet::call_index(8)]
+		#[pallet::weight((T::WeightInfo::add_user_commitment(), DispatchClass::Normal))]
+		pub fn add_user_commitment(
+			origin: OriginFor<T>,
+			user: T::AccountId,
+			provider: Vec<u8>,
+			commitment: ExternalIdValue,
+			expires_at: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let provider_bounded: Provider = Provider::defensive_truncate_from(provider.clone());
+			Self::ensure_provider_manager(&who, &provider_bounded)?;
+			Self::do_add_user_address(&who, user, provider_bounded, commitment, expires_at)
+		}
+
+		/// Check that `external_id` and `salt` open the commitment `user` holds with `provider`.
+		///
+		/// `proof` is only used for Merkle roots, where `external_id` is one of the attributes.
+		#[pallet::call_index(9)]
+		#[pallet::weight((T::WeightInfo::verify_user_address(), DispatchClass::Normal))]
+		pub fn verify_user_address(
+			origin: OriginFor<T>,
+			user: T::AccountId,
+			provider: Vec<u8>,
+			external_id: Vec<u8>,
+			salt: H256,
+			proof: Vec<H256>,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(
+				proof.len() <= MAX_PROOF_DEPTH as usize,
+				Error::<T>::ProofTooLong
+			);
+			let provider_bounded: Provider = Provider::defensive_truncate_from(provider.clone());
+			ensure!(
+				Self::verify_external_id(
+					user.clone(),
+					provider_bounded.clone(),
+					&external_id,
+					salt,
+					&proof
+				),
+				Error::<T>::CommitmentMismatch
+			);
+			Self::deposit_event(Event::ExternalIdVerified {
+				who: user,
+				provider: provider_bounded,
+			});
+			Ok(())
+		}
+
+		/// Create an empty list owned by the caller.
+		///
+		/// The caller must be a manager whose role covers `list_name`. A `max_size` of zero
+		/// means the list is unbounded.
+		#[pallet::call_index(10)]
+		#[pallet::weight((T::WeightInfo::create_list(), DispatchClass::Normal))]
+		pub fn create_list(
+			origin: OriginFor<T>,
+			list_name: Vec<u8>,
+			description: Vec<u8>,
+			kind: ListKind,
+			max_size: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let list_name_bounded: ListName = ListName::defensive_truncate_from(list_name.clone());
+			let role = <PalletManager<T>>::get(&who).ok_or(Error::<T>::InvalidOrigin)?;
+			ensure!(
+				role.lists.allows(&list_name_bounded),
+				Error::<T>::ListNotInScope
+			);
+			ensure!(
+				!<Lists<T>>::contains_key(&list_name_bounded),
+				Error::<T>::ListAlreadyExists
+			);
+			let description_bounded: ListDescription =
+				ListDescription::defensive_truncate_from(description);
+			<Lists<T>>::insert(
+				&list_name_bounded,
+				ListInfo {
+					owner: who.clone(),
+					description: description_bounded,
+					kind: kind.clone(),
+					max_size,
+					member_count: 0,
+				},
+			);
+			Self::deposit_event(Event::ListCreated {
+				list_name: list_name_bounded,
+				owner: who,
+				kind,
+			});
+			Ok(())
+		}
+
+		/// Change the description and size limit of a list.
+		#[pallet::call_index(11)]
+		#[pallet::weight((T::WeightInfo::update_list(), DispatchClass::Normal))]
+		pub fn update_list(
+			origin: OriginFor<T>,
+			list_name: Vec<u8>,
+			description: Vec<u8>,
+			max_size: u32,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let list_name_bounded: ListName = ListName::defensive_truncate_from(list_name.clone());
+			Self::ensure_list_manager(&who, &list_name_bounded)?;
+			<Lists<T>>::try_mutate(&list_name_bounded, |maybe_list| {
+				let list = maybe_list.as_mut().ok_or(Error::<T>::ListNotFound)?;
+				ensure!(
+					max_size == 0 || max_size >= list.member_count,
+					Error::<T>::MaxSizeBelowMemberCount
+				);
+				list.description = ListDescription::defensive_truncate_from(description);
+				list.max_size = max_size;
+				Ok::<_, DispatchError>(())
+			})?;
+			Self::deposit_event(Event::ListUpdated {
+				list_name: list_name_bounded,
+			});
+			Ok(())
+		}
+
+		/// Delete a list that has no members left.
+		#[pallet::call_index(12)]
+		#[pallet::weight((T::WeightInfo::destroy_list(), DispatchClass::Normal))]
+		pub fn destroy_list(origin: OriginFor<T>, list_name: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let list_name_bounded: ListName = ListName::defensive_truncate_from(list_name.clone());
+			Self::ensure_list_manager(&who, &list_name_bounded)?;
+			let list = <Lists<T>>::get(&list_name_bounded).ok_or(Error::<T>::ListNotFound)?;
+			ensure!(list.member_count == 0, Error::<T>::ListNotEmpty);
+			<Lists<T>>::remove(&list_name_bounded);
+			Self::deposit_event(Event::ListDestroyed {
+				list_name: list_name_bounded,
+			});
+			Ok(())
+		}
+
+		/// Add many users to one list, each with its own expiry.
+		///
+		/// Either every user is added or none is.
+		#[pallet::call_index(13)]
+		#[pallet::weight((
+			T::WeightInfo::add_users_to_list(users.len() as u32).saturating_add(
+				Pallet::<T>::full_list_sweep_weight().saturating_mul(users.len() as u64)
+			),
+			DispatchClass::Normal
+		))]
+		pub fn add_users_to_list(
+			origin: OriginFor<T>,
+			list_name: Vec<u8>,
+			users: ListEntries<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let list_name_bounded: ListName = ListName::defensive_truncate_from(list_name.clone());
+			Self::ensure_list_manager(&who, &list_name_bounded)?;
+			for (user, expires_at) in users.iter() {
+				Self::ensure_expiry(*expires_at)?;
+				Self::do_add_user_to_list(&who, &list_name_bounded, user, *expires_at)?;
+			}
+			Self::deposit_event(Event::BatchAddedUsersToList {
+				list_name: list_name_bounded,
+				count: users.len() as u32,
+			});
+			Ok(())
+		}
+
+		/// Remove many users from one list.
+		#[pallet::call_index(14)]
+		#[pallet::weight((
+			T::WeightInfo::remove_users_from_list(users.len() as u32),
+			DispatchClass::Normal
+		))]
+		pub fn remove_users_from_list(
+			origin: OriginFor<T>,
+			list_name: Vec<u8>,
+			users: BoundedVec<T::AccountId, T::MaxBatchSize>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let list_name_bounded: ListName = ListName::defensive_truncate_from(list_name.clone());
+			Self::ensure_list_manager(&who, &list_name_bounded)?;
+			let count = users
+				.iter()
+				.filter(|user| Self::do_remove_user_from_list(&list_name_bounded, user))
+				.count() as u32;
+			Self::deposit_event(Event::BatchRemovedUsersFromList {
+				list_name: list_name_bounded,
+				count,
+			});
+			Ok(())
+		}
+
+		/// Link external ids with one provider to many users, each with its own expiry.
+		///
+		/// Either every link is set or none is.
+		#[pallet::call_index(15)]
+		#[pallet::weight((
+			T::WeightInfo::add_user_addresses(entries.len() as u32),
+			DispatchClass::Normal
+		))]
+		pub fn add_user_addresses(
+			origin: OriginFor<T>,
+			provider: Vec<u8>,
+			entries: ExternalIdEntries<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let provider_bounded: Provider = Provider::defensive_truncate_from(provider.clone());
+			Self::ensure_provider_manager(&who, &provider_bounded)?;
+			let count = entries.len() as u32;
+			for (user, external_id, expires_at) in entries {
+				Self::set_user_address(
+					&who,
+					user,
+					provider_bounded.clone(),
+					ExternalIdValue::Plain(external_id),
+					expires_at,
+				)?;
+			}
+			Self::deposit_event(Event::BatchAddedUserAddresses {
+				provider: provider_bounded,
+				count,
+			});
+			Ok(())
+		}
+
+		/// Approve moving every DID record of the caller to `to`, which has to accept it.
+		///
+		/// Replaces a migration the caller approved before.
+		#[pallet::call_index(16)]
+		#[pallet::weight((T::WeightInfo::approve_migration(), DispatchClass::Normal))]
+		pub fn approve_migration(origin: OriginFor<T>, to: T::AccountId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(who != to, Error::<T>::MigrationToSelf);
+			<PendingMigrations<T>>::insert(&who, &to);
+			Self::deposit_event(Event::MigrationApproved { from: who, to });
+			Ok(())
+		}
+
+		#[pallet::call_index(17)]
+		#[pallet::weight((T::WeightInfo::cancel_migration(), DispatchClass::Normal))]
+		pub fn cancel_migration(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let to = <PendingMigrations<T>>::take(&who).ok_or(Error::<T>::MigrationNotApproved)?;
+			Self::deposit_event(Event::MigrationCancelled { from: who, to });
+			Ok(())
+		}
+
+		/// Take over the external id links and list memberships of `from`, which approved the
+		/// caller with [`Pallet::approve_migration`].
+		///
+		/// The caller must not hold any unexpired DID records yet, so links stay unique and a
+		/// membership of a denylist can neither be dropped nor merged away. Up to
+		/// [`MAX_MIGRATION_SWEEP`] expired records of the caller are removed first. Deposits stay
+		/// with whoever paid them. `witness` must count at least the records `from` holds.
+		///
+		/// List ownership and manager roles are not moved: the lists have no owner index to
+		/// find them by, and roles are only granted by `ManagerOrigin`, which has to grant them
+		/// to the new account again.
+		#[pallet::call_index(18)]
+		#[pallet::weight((
+			T::WeightInfo::accept_migration(witness.external_ids, witness.memberships)
+				.saturating_add(Pallet::<T>::expired_records_weight(MAX_MIGRATION_SWEEP)),
+			DispatchClass::Normal
+		))]
+		pub fn accept_migration(
+			origin: OriginFor<T>,
+			from: T::AccountId,
+			witness: MigrationWitness,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				<PendingMigrations<T>>::get(&from).as_ref() == Some(&who),
+				Error::<T>::MigrationNotApproved
+			);
+			// Count no further than the witness allows.
+			let external_ids = <ExternalIdAddress<T>>::iter_prefix(&from)
+				.take(witness.external_ids.saturating_add(1) as usize)
+				.count() as u32;
+			let memberships = <Memberships<T>>::iter_prefix(&from)
+				.take(witness.memberships.saturating_add(1) as usize)
+				.count() as u32;
+			ensure!(
+				external_ids <= witness.external_ids && memberships <= witness.memberships,
+				Error::<T>::BadWitness
+			);
+			let expired = Self::remove_expired_records(&who, MAX_MIGRATION_SWEEP)?;
+			<PendingMigrations<T>>::remove(&from);
+			Self::do_migrate(&from, &who);
+			Self::deposit_event(Event::AccountMigrated {
+				from,
+				to: who,
+				external_ids,
+				memberships,
+			});
+			Ok(Some(
+				T::WeightInfo::accept_migration(external_ids, memberships)
+					.saturating_add(Self::expired_records_weight(expired)),
+			)
+			.into())
+		}
+
+		/// Pay the deposit of an unexpired link or membership of the caller, releasing the
+		/// deposit of whoever paid it until now.
+		///
+		/// Lets managers hand the storage cost of a record to its user.
+		#[pallet::call_index(19)]
+		#[pallet::weight((T::WeightInfo::pay_deposit(), DispatchClass::Normal))]
+		pub fn pay_deposit(
+			origin: OriginFor<T>,
+			record: DidRecord<T::AccountId>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let value_size = match &record {
+				DidRecord::ExternalId {
+					who: user,
+					provider,
+				} => {
+					ensure!(user == &who, Error::<T>::NotAllowedToMutate);
+					<ExternalIdAddress<T>>::get(user, provider)
+						.filter(|record| Self::is_active(record.expires_at))
+						.map(|record| record.encoded_size())
+				}
+				DidRecord::ListMembership {
+					list_name,
+					who: user,
+				} => {
+					ensure!(user == &who, Error::<T>::NotAllowedToMutate);
+					<UserList<T>>::get(list_name, user)
+						.filter(|membership| Self::is_active(membership.expires_at))
+						.map(|membership| membership.encoded_size())
+				}
+			}
+			.ok_or(Error::<T>::RecordNotFound)?;
+			Self::release_deposit(&record);
+			Self::hold_deposit(&who, &record, value_size)
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The external id `who` holds with `provider`, empty if there is none or it expired.
+		///
+		/// Commitments are never revealed, so this is empty for them as well.
+		pub fn external_id(who: T::AccountId, provider: Provider) -> ExternalId {
+			<ExternalIdAddress<T>>::get(who, provider)
+				.filter(|record| Self::is_active(record.expires_at))
+				.and_then(|record| match record.value {
+					ExternalIdValue::Plain(external_id) => Some(external_id),
+					_ => None,
+				})
+				.unwrap_or_default()
+		}
+
+		/// Whether `who` holds an unexpired record with `provider`, whatever its kind.
+		pub fn has_external_id(who: &T::AccountId, provider: &Provider) -> bool {
+			<ExternalIdAddress<T>>::get(who, provider)
+				.map_or(false, |record| Self::is_active(record.expires_at))
+		}
+
+		/// Whether `external_id` and `salt` open the unexpired record `who` holds with `provider`.
+		pub fn verify_external_id(
+			who: T::AccountId,
+			provider: Provider,
+			external_id: &[u8],
+			salt: H256,
+			proof: &[H256],
+		) -> bool {
+			let Some(record) = <ExternalIdAddress<T>>::get(who, provider)
+				.filter(|record| Self::is_active(record.expires_at))
+			else {
+				return false
+			};
+			match record.value {
+				ExternalIdValue::Plain(stored) => stored.as_slice() == external_id,
+				ExternalIdValue::Commitment(commitment) => {
+					Self::commitment_of(external_id, salt) == commitment
+				}
+				ExternalIdValue::MerkleRoot(root) => {
+					let leaf = Self::commitment_of(external_id, salt);
+					Self::merkle_root_of(leaf, proof) == root
+				}
+			}
+		}
+
+		/// `keccak256(0x00 ++ external_id ++ salt)`, the commitment to a single external id and
+		/// the leaf of an attribute in a Merkle tree.
+		pub fn commitment_of(external_id: &[u8], salt: H256) -> H256 {
+			let mut preimage = sp_std::vec![LEAF_PREFIX];
+			preimage.extend_from_slice(external_id);
+			preimage.extend_from_slice(salt.as_bytes());
+			H256(sp_io::hashing::keccak_256(&preimage))
+		}
+
+		/// Fold `proof` into `leaf`, hashing each pair in sorted order behind [`NODE_PREFIX`].
+		pub fn merkle_root_of(leaf: H256, proof: &[H256]) -> H256 {
+			proof.iter().fold(leaf, |node, sibling| {
+				let (left, right) = if node <= *sibling {
+					(node, *sibling)
+				} else {
+					(*sibling, node)
+				};
+				let mut preimage = [0u8; 65];
+				preimage[0] = NODE_PREFIX;
+				preimage[1..33].copy_from_slice(left.as_bytes());
+				preimage[33..].copy_from_slice(right.as_bytes());
+				H256(sp_io::hashing::keccak_256(&preimage))
+			})
+		}
+
+		fn do_add_user_address(
+			payer: &T::AccountId,
+			user: T::AccountId,
+			provider_bounded: Provider,
+			value: ExternalIdValue,
+			expires_at: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			Self::set_user_address(
+				payer,
+				user.clone(),
+				provider_bounded.clone(),
+				value,
+				expires_at,
+			)?;
+			Self::deposit_event(Event::AddedUserAddress {
+				who: user,
+				provider: provider_bounded,
+				expires_at,
+			});
+			Ok(())
+		}
+
+		/// Store a link without emitting an event, holding its deposit from `payer`.
+		pub(crate) fn set_user_address(
+			payer: &T::AccountId,
+			user: T::AccountId,
+			provider: Provider,
+			value: ExternalIdValue,
+			expires_at: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			Self::ensure_expiry(expires_at)?;
+			let record = DidRecord::ExternalId {
+				who: user.clone(),
+				provider: provider.clone(),
+			};
+			let previous = <ExternalIdAddress<T>>::get(&user, &provider);
+			let stored = ExternalIdRecord { value, expires_at };
+			Self::release_deposit(&record);
+			Self::hold_deposit(payer, &record, stored.encoded_size())?;
+			Self::reschedule(
+				record,
+				previous.as_ref().and_then(|record| record.expires_at),
+				expires_at,
+			);
+			if let Some(previous) = previous {
+				Self::unindex(&user, &provider, previous.value);
+			}
+			if let ExternalIdValue::Plain(external_id) = &stored.value {
+				<ExternalIdOwners<T>>::insert((provider.clone(), external_id.clone()), &user, ());
+			}
+			<ExternalIdAddress<T>>::insert(&user, &provider, stored);
+			Ok(())
+		}
+
+		/// Returns whether `user` had a link with `provider`.
+		pub(crate) fn do_remove_user_address(user: &T::AccountId, provider: &Provider) -> bool {
+			let Some(previous) = <ExternalIdAddress<T>>::take(user, provider) else { return false };
+			let record = DidRecord::ExternalId {
+				who: user.clone(),
+				provider: provider.clone(),
+			};
+			Self::release_deposit(&record);
+			Self::reschedule(record, previous.expires_at, None);
+			Self::unindex(user, provider, previous.value);
+			true
+		}
+
+		fn unindex(user: &T::AccountId, provider: &Provider, value: ExternalIdValue) {
+			if let ExternalIdValue::Plain(external_id) = value {
+				<ExternalIdOwners<T>>::remove((provider.clone(), external_id), user);
+			}
+		}
+
+		/// The unexpired external ids `who` holds, by provider.
+		pub fn external_ids_of(
+			who: &T::AccountId,
+		) -> Vec<(Provider, ExternalIdRecord<BlockNumberFor<T>>)> {
+			<ExternalIdAddress<T>>::iter_prefix(who)
+				.filter(|(_, record)| Self::is_active(record.expires_at))
+				.collect()
+		}
+
+		/// The accounts holding the plain `external_id` with `provider`, expired links left out.
+		pub fn accounts_of(provider: Provider, external_id: ExternalId) -> Vec<T::AccountId> {
+			<ExternalIdOwners<T>>::iter_key_prefix((provider.clone(), external_id.clone()))
+				.filter(|who| Self::external_id(who.clone(), provider.clone()) == external_id)
+				.collect()
+		}
+
+		/// The deposit for `record` taking `value_size` more bytes next to its key.
+		pub fn deposit_for(record: &DidRecord<T::AccountId>, value_size: usize) -> BalanceOf<T> {
+			let bytes = record.encoded_size().saturating_add(value_size) as u32;
+			T::DepositPerByte::get()
+				.saturating_mul(bytes.into())
+				.saturating_add(T::DepositPerItem::get())
+		}
+
+		fn hold_deposit(
+			payer: &T::AccountId,
+			record: &DidRecord<T::AccountId>,
+			value_size: usize,
+		) -> DispatchResult {
+			let amount = Self::deposit_for(record, value_size);
+			T::Currency::hold(&HoldReason::StorageDeposit.into(), payer, amount)?;
+			<Deposits<T>>::insert(record, (payer.clone(), amount));
+			Self::deposit_event(Event::DepositHeld {
+				who: payer.clone(),
+				amount,
+			});
+			Ok(())
+		}
+
+		fn release_deposit(record: &DidRecord<T::AccountId>) {
+			let Some((payer, amount)) = <Deposits<T>>::take(record) else { return };
+			let released = T::Currency::release(
+				&HoldReason::StorageDeposit.into(),
+				&payer,
+				amount,
+				Precision::BestEffort,
+			)
+			.unwrap_or_default();
+			Self::deposit_event(Event::DepositReleased {
+				who: payer,
+				amount: released,
+			});
+		}
+
+		/// Whether `who` is a member of `list_name` that has not expired.
+		pub fn user_list(list_name: ListName, who: T::AccountId) -> bool {
+			<UserList<T>>::get(list_name, who)
+				.map_or(false, |membership| Self::is_active(membership.expires_at))
+		}
+
+		/// Whether `who` holds any manager role, read-only included.
+		pub fn is_manager(who: &T::AccountId) -> bool {
+			<PalletManager<T>>::contains_key(who)
+		}
+
+		fn ensure_provider_manager(who: &T::AccountId, provider: &Provider) -> DispatchResult {
+			let role = <PalletManager<T>>::get(who).ok_or(Error::<T>::InvalidOrigin)?;
+			ensure!(
+				role.providers.allows(provider),
+				Error::<T>::ProviderNotInScope
+			);
+			Ok(())
+		}
+
+		/// Managers whose role covers a list may change it. Owning the list does not widen the
+		/// role, an owner whose scope no longer covers it loses access too.
+		fn ensure_list_manager(who: &T::AccountId, list_name: &ListName) -> DispatchResult {
+			let role = <PalletManager<T>>::get(who).ok_or(Error::<T>::InvalidOrigin)?;
+			ensure!(role.lists.allows(list_name), Error::<T>::ListNotInScope);
+			Ok(())
+		}
+
+		/// Whether `list_name` lets `who` through: members of an allowlist and everyone
+		/// missing from a denylist. Unknown lists let nobody through.
+		pub fn is_allowed(list_name: &ListName, who: &T::AccountId) -> bool {
+			match <Lists<T>>::get(list_name) {
+				Some(list) => match list.kind {
+					ListKind::Allowlist => Self::user_list(list_name.clone(), who.clone()),
+					ListKind::Denylist => !Self::user_list(list_name.clone(), who.clone()),
+				},
+				None => false,
+			}
+		}
+
+		/// The members of `list_name`, expired ones left out.
+		pub fn list_members(list_name: &ListName) -> Vec<T::AccountId> {
+			<UserList<T>>::iter_prefix(list_name)
+				.filter(|(_, membership)| Self::is_active(membership.expires_at))
+				.map(|(who, _)| who)
+				.collect()
+		}
+
+		/// Up to `limit` members of `list_name` stored after `start_after`, in storage order.
+		///
+		/// `limit` is capped at [`MAX_LIST_PAGE`]. Pass the last account of a page as
+		/// `start_after` to get the next one.
+		pub fn list_members_page(
+			list_name: &ListName,
+			start_after: Option<T::AccountId>,
+			limit: u32,
+		) -> Vec<(T::AccountId, ListMembership<BlockNumberFor<T>>)> {
+			let members = match start_after {
+				Some(who) => <UserList<T>>::iter_prefix_from(
+					list_name,
+					<UserList<T>>::hashed_key_for(list_name, who),
+				),
+				None => <UserList<T>>::iter_prefix(list_name),
+			};
+			members
+				.filter(|(_, membership)| Self::is_active(membership.expires_at))
+				.take(limit.min(MAX_LIST_PAGE) as usize)
+				.collect()
+		}
+
+		/// The unexpired membership of `who` in `list_name`.
+		pub fn membership(
+			list_name: &ListName,
+			who: &T::AccountId,
+		) -> Option<ListMembership<BlockNumberFor<T>>> {
+			<UserList<T>>::get(list_name, who)
+				.filter(|membership| Self::is_active(membership.expires_at))
+		}
+
+		pub(crate) fn do_add_user_to_list(
+			payer: &T::AccountId,
+			list_name: &ListName,
+			user: &T::AccountId,
+			expires_at: Option<BlockNumberFor<T>>,
+		) -> DispatchResult {
+			let previous = <UserList<T>>::get(list_name, user);
+			if previous.is_none() {
+				let list = <Lists<T>>::get(list_name).ok_or(Error::<T>::ListNotFound)?;
+				if list.max_size != 0 && list.member_count >= list.max_size {
+					// Expired members keep their place until they are swept.
+					Self::sweep_expired(
+						frame_system::Pallet::<T>::block_number(),
+						Self::full_list_sweep_weight(),
+					);
+				}
+				<Lists<T>>::try_mutate(list_name, |maybe_list| {
+					let list = maybe_list.as_mut().ok_or(Error::<T>::ListNotFound)?;
+					ensure!(
+						list.max_size == 0 || list.member_count < list.max_size,
+						Error::<T>::ListFull
+					);
+					list.member_count.saturating_inc();
+					Ok::<_, DispatchError>(())
+				})?;
+			}
+			let record = DidRecord::ListMembership {
+				list_name: list_name.clone(),
+				who: user.clone(),
+			};
+			let membership = ListMembership { expires_at };
+			Self::release_deposit(&record);
+			Self::hold_deposit(payer, &record, membership.encoded_size())?;
+			Self::reschedule(
+				record,
+				previous.and_then(|membership| membership.expires_at),
+				expires_at,
+			);
+			<UserList<T>>::insert(list_name, user, membership);
+			<Memberships<T>>::insert(user, list_name, ());
+			Ok(())
+		}
+
+		/// Returns whether `user` was a member.
+		pub(crate) fn do_remove_user_from_list(list_name: &ListName, user: &T::AccountId) -> bool {
+			let Some(previous) = <UserList<T>>::take(list_name, user) else { return false };
+			<Memberships<T>>::remove(user, list_name);
+			let record = DidRecord::ListMembership {
+				list_name: list_name.clone(),
+				who: user.clone(),
+			};
+			Self::release_deposit(&record);
+			Self::reschedule(record, previous.expires_at, None);
+			<Lists<T>>::mutate(list_name, |maybe_list| {
+				if let Some(list) = maybe_list {
+					list.member_count.saturating_dec();
+				}
+			});
+			true
+		}
+
+		/// Re-key every link and membership of `from` to `to`, with their schedule entries,
+		/// deposits and index entries.
+		pub(crate) fn do_migrate(from: &T::AccountId, to: &T::AccountId) {
+			let links: Vec<_> = <ExternalIdAddress<T>>::drain_prefix(from).collect();
+			for (provider, link) in links {
+				let old = DidRecord::ExternalId {
+					who: from.clone(),
+					provider: provider.clone(),
+				};
+				let new = DidRecord::ExternalId {
+					who: to.clone(),
+					provider: provider.clone(),
+				};
+				Self::move_record(&old, &new, link.expires_at);
+				if let ExternalIdValue::Plain(external_id) = &link.value {
+					let key = (provider.clone(), external_id.clone());
+					<ExternalIdOwners<T>>::remove(&key, from);
+					<ExternalIdOwners<T>>::insert(&key, to, ());
+				}
+				<ExternalIdAddress<T>>::insert(to, provider, link);
+			}
+			let lists: Vec<_> = <Memberships<T>>::drain_prefix(from)
+				.map(|(list, _)| list)
+				.collect();
+			for list_name in lists {
+				let Some(membership) = <UserList<T>>::take(&list_name, from) else { continue };
+				let old = DidRecord::ListMembership {
+					list_name: list_name.clone(),
+					who: from.clone(),
+				};
+				let new = DidRecord::ListMembership {
+					list_name: list_name.clone(),
+					who: to.clone(),
+				};
+				Self::move_record(&old, &new, membership.expires_at);
+				<Memberships<T>>::insert(to, &list_name, ());
+				<UserList<T>>::insert(&list_name, to, membership);
+			}
+		}
+
+		fn move_record(
+			old: &DidRecord<T::AccountId>,
+			new: &DidRecord<T::AccountId>,
+			expires_at: Option<BlockNumberFor<T>>,
+		) {
+			if let Some(expiry) = expires_at {
+				<Expiries<T>>::remove(expiry, old);
+				<Expiries<T>>::insert(expiry, new, ());
+			}
+			if let Some(deposit) = <Deposits<T>>::take(old) {
+				<Deposits<T>>::insert(new, deposit);
+			}
+		}
+
+		fn is_active(expires_at: Option<BlockNumberFor<T>>) -> bool {
+			expires_at.map_or(true, |expiry| {
+				frame_system::Pallet::<T>::block_number() < expiry
+			})
+		}
+
+		fn ensure_expiry(expires_at: Option<BlockNumberFor<T>>) -> DispatchResult {
+			if let Some(expiry) = expires_at {
+				let current_block = frame_system::Pallet::<T>::block_number();
+				ensure!(expiry > current_block, Error::<T>::ExpiryInPast);
+			}
+			Ok(())
+		}
+
+		/// Move `record` in the expiry schedule from `previous` to `next`.
+		fn reschedule(
+			record: DidRecord<T::AccountId>,
+			previous: Option<BlockNumberFor<T>>,
+			next: Option<BlockNumberFor<T>>,
+		) {
+			if let Some(previous) = previous {
+				<Expiries<T>>::remove(previous, &record);
+			}
+			if let Some(next) = next {
+				<Expiries<T>>::insert(next, &record, ());
+			}
+		}
+
+		/// The most a full list spends sweeping before it turns a new member away, enough for
+		/// [`MAX_FULL_LIST_SWEEP`] records.
+		pub fn full_list_sweep_weight() -> Weight {
+			let db_weight = T::DbWeight::get();
+			let records = db_weight
+				.reads_writes(4, 6)
+				.saturating_mul(MAX_FULL_LIST_SWEEP.into());
+			db_weight
+				.reads_writes(1, 1)
+				.saturating_add(db_weight.reads(1))
+				.saturating_add(records)
+		}
+
+		/// The weight of finding and removing `count` expired records of one account.
+		pub fn expired_records_weight(count: u32) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let records = db_weight.reads_writes(4, 6).saturating_mul(count.into());
+			db_weight.reads(2).saturating_add(records)
+		}
+
+		/// Remove the expired links and memberships of `who`, failing if it holds an unexpired
+		/// one or more than `limit` expired ones. Returns how many were removed.
+		fn remove_expired_records(who: &T::AccountId, limit: u32) -> Result<u32, DispatchError> {
+			let links: Vec<_> = <ExternalIdAddress<T>>::iter_prefix(who)
+				.take(limit as usize + 1)
+				.collect();
+			let memberships: Vec<_> = <Memberships<T>>::iter_key_prefix(who)
+				.take(limit as usize + 1)
+				.filter_map(|list_name| Some((<UserList<T>>::get(&list_name, who)?, list_name)))
+				.collect();
+			let expired = |expires_at| !Self::is_active(expires_at);
+			ensure!(
+				links.len() + memberships.len() <= limit as usize
+					&& links.iter().all(|(_, link)| expired(link.expires_at))
+					&& memberships
+						.iter()
+						.all(|(membership, _)| expired(membership.expires_at)),
+				Error::<T>::AccountHasRecords
+			);
+			let count = (links.len() + memberships.len()) as u32;
+			for (provider, _) in links {
+				Self::do_remove_user_address(who, &provider);
+				Self::deposit_event(Event::UserAddressExpired {
+					who: who.clone(),
+					provider,
+				});
+			}
+			for (_, list_name) in memberships {
+				Self::do_remove_user_from_list(&list_name, who);
+				let who = who.clone();
+				Self::deposit_event(Event::UserListMembershipExpired { who, list_name });
+			}
+			Ok(count)
+		}
+
+		/// Delete the records that expired up to block `n`, within `limit`.
+		///
+		/// Stops in the middle of a block when the weight runs out and carries on from there in
+		/// the next idle period.
+		pub(crate) fn sweep_expired(n: BlockNumberFor<T>, limit: Weight) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let per_block = db_weight.reads(1);
+			// Read the record and its list, then delete the record, its schedule entry, its index
+			// entry and its deposit, and update the list.
+			let per_record = db_weight.reads_writes(4, 6);
+			let mut used = db_weight.reads_writes(1, 1);
+			if used.any_gt(limit) {
+				return Weight::zero();
+			}
+			let mut cursor = <ExpiryCursor<T>>::get().unwrap_or(n);
+			'blocks: while cursor <= n {
+				if used.saturating_add(per_block).any_gt(limit) {
+					break;
+				}
+				used.saturating_accrue(per_block);
+				for (record, _) in <Expiries<T>>::iter_prefix(cursor) {
+					if used.saturating_add(per_record).any_gt(limit) {
+						break 'blocks;
+					}
+					used.saturating_accrue(per_record);
+					<Expiries<T>>::remove(cursor, &record);
+					Self::expire(record, cursor);
+				}
+				cursor.saturating_inc();
+			}
+			<ExpiryCursor<T>>::put(cursor);
+			used
+		}
+
+		fn expire(record: DidRecord<T::AccountId>, block: BlockNumberFor<T>) {
+			match record {
+				DidRecord::ExternalId { who, provider } => {
+					let expired = <ExternalIdAddress<T>>::get(&who, &provider)
+						.map_or(false, |record| record.expires_at == Some(block));
+					if expired {
+						Self::do_remove_user_address(&who, &provider);
+						Self::deposit_event(Event::UserAddressExpired { who, provider });
+					}
+				}
+				DidRecord::ListMembership { list_name, who } => {
+					let expired = <UserList<T>>::get(&list_name, &who)
+						.map_or(false, |membership| membership.expires_at == Some(block));
+					if expired {
+						Self::do_remove_user_from_list(&list_name, &who);
+						Self::deposit_event(Event::UserListMembershipExpired { who, list_name });
+					}
+				}
+			}
+		}
+	}
+}
+
+/// Let through signed origins that `List` allows, see [`Pallet::is_allowed`].
+pub struct EnsureInList<T, List>(PhantomData<(T, List)>);
+
+impl<T: Config, List: Get<ListName>> EnsureOrigin<T::RuntimeOrigin> for EnsureInList<T, List> {
+	type Success = T::AccountId;
+
+	fn try_origin(o: T::RuntimeOrigin) -> Result<Self::Success, T::RuntimeOrigin> {
+		o.into().and_then(|o| match o {
+			RawOrigin::Signed(who) if Pallet::<T>::is_allowed(&List::get(), &who) => Ok(who),
+			r => Err(T::RuntimeOrigin::from(r)),
+		})
+	}
+
+	/// Lets `account("in_list", 0, 0)` through, creating `List` as an allowlist if it is missing.
+	#[cfg(feature = "runtime-benchmarks")]
+	fn try_successful_origin() -> Result<T::RuntimeOrigin, ()> {
+		use sp_runtime::traits::Saturating;
+		let who: T::AccountId = frame_benchmarking::account("in_list", 0, 0);
+		let list_name = List::get();
+		let list = <Lists<T>>::get(&list_name).unwrap_or_else(|| {
+			let list = ListInfo {
+				owner: who.clone(),
+				description: Default::default(),
+				kind: ListKind::Allowlist,
+				max_size: 0,
+				member_count: 0,
+			};
+			<Lists<T>>::insert(&list_name, &list);
+			list
+		});
+		match list.kind {
+			ListKind::Allowlist if !Pallet::<T>::is_allowed(&list_name, &who) => {
+				if <UserList<T>>::take(&list_name, &who).is_none() {
+					<Lists<T>>::mutate(&list_name, |maybe_list| {
+						if let Some(list) = maybe_list {
+							list.member_count.saturating_inc();
+						}
+					});
+				}
+				<UserList<T>>::insert(&list_name, &who, ListMembership { expires_at: None });
+				<Memberships<T>>::insert(&who, &list_name, ());
+			}
+			ListKind::Allowlist => (),
+			ListKind::Denylist => {
+				Pallet::<T>::do_remove_user_from_list(&list_name, &who);
+			}
+		}
+		Ok(RawOrigin::Signed(who).into())
+	}
+}
+
+/// The accounts `List` allows, see [`Pallet::is_allowed`].
+pub struct InList<T, List>(PhantomData<(T, List)>);
+
+impl<T: Config, List: Get<ListName>> Contains<T::AccountId> for InList<T, List> {
+	fn contains(who: &T::AccountId) -> bool {
+		Pallet::<T>::is_allowed(&List::get(), who)
+	}
+}
+
+/// The identity tier of an account. Every account reaches tier `0`.
+pub type Tier = u8;
+
+/// Identity checks for pallets that gate calls on who the caller is.
+pub trait VerifyIdentity<AccountId> {
+	/// The highest tier `who` reaches.
+	fn tier(who: &AccountId) -> Tier;
+
+	/// The highest tier an account can reach.
+	fn max_tier() -> Tier;
+
+	/// Whether `who` holds an unexpired external id with `provider`.
+	fn has_provider(who: &AccountId, provider: &Provider) -> bool;
+
+	/// Whether `list_name` lets `who` through.
+	fn in_list(list_name: &ListName, who: &AccountId) -> bool;
+
+	/// Whether `who` is an unexpired member of `list_name`, whatever its kind.
+	fn is_member(list_name: &ListName, who: &AccountId) -> bool;
+
+	/// Whether `who` reaches `tier`.
+	fn meets_tier(who: &AccountId, tier: Tier) -> bool {
+		Self::tier(who) >= tier
+	}
+}
+
+/// Nobody is verified: every account stays at tier `0`.
+impl<AccountId> VerifyIdentity<AccountId> for () {
+	fn tier(_: &AccountId) -> Tier {
+		0
+	}
+
+	fn max_tier() -> Tier {
+		0
+	}
+
+	fn has_provider(_: &AccountId, _: &Provider) -> bool {
+		false
+	}
+
+	fn in_list(_: &ListName, _: &AccountId) -> bool {
+		false
+	}
+
+	fn is_member(_: &ListName, _: &AccountId) -> bool {
+		false
+	}
+}
+
+/// What an account needs, on top of the tier below, to reach a tier.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct TierRequirement {
+	/// Providers the account must hold an unexpired external id with.
+	pub providers: Vec<Provider>,
+	/// Lists that must let the account through, see [`Pallet::is_allowed`].
+	pub lists: Vec<ListName>,
+}
+
+/// Tiers backed by this pallet. `Tiers` holds the requirements of tier `1` onwards, so an
+/// account reaches tier `n` when it meets the first `n` of them.
+pub struct DidTiers<T, Tiers>(PhantomData<(T, Tiers)>);
+
+impl<T: Config, Tiers: Get<Vec<TierRequirement>>> VerifyIdentity<T::AccountId>
+	for DidTiers<T, Tiers>
+{
+	fn tier(who: &T::AccountId) -> Tier {
+		let reached = Tiers::get()
+			.iter()
+			.take_while(|tier| {
+				tier.providers
+					.iter()
+					.all(|provider| Self::has_provider(who, provider))
+					&& tier
+						.lists
+						.iter()
+						.all(|list_name| Self::in_list(list_name, who))
+			})
+			.count();
+		Tier::try_from(reached).unwrap_or(Tier::MAX)
+	}
+
+	fn max_tier() -> Tier {
+		Tier::try_from(Tiers::get().len()).unwrap_or(Tier::MAX)
+	}
+
+	fn has_provider(who: &T::AccountId, provider: &Provider) -> bool {
+		Pallet::<T>::has_external_id(who, provider)
+	}
+
+	fn in_list(list_name: &ListName, who: &T::AccountId) -> bool {
+		Pallet::<T>::is_allowed(list_name, who)
+	}
+
+	fn is_member(list_name: &ListName, who: &T::AccountId) -> bool {
+		Pallet::<T>::user_list(list_name.clone(), who.clone())
 	}
 }
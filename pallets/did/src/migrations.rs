@@ -0,0 +1,92 @@
+//! Storage migrations for pallet-did.
+
+use super::*;
+use frame_support::{
+	pallet_prelude::*,
+	traits::{GetStorageVersion, OnRuntimeUpgrade},
+};
+use sp_std::collections::btree_map::BTreeMap;
+
+const LOG_TARGET: &str = "runtime::did";
+
+pub mod v1 {
+	use super::*;
+
+	/// Move the storage of version 0 to version 1.
+	///
+	/// Version 0 kept managers as flags, external ids as plain values and list memberships as
+	/// flags, all without expiry. Managers become full managers, external ids plain records
+	/// and memberships records, none of them expiring, and the reverse indexes are filled in.
+	/// Lists had no record of their own, so each list with members is created as an allowlist
+	/// owned by `ListOwner`. Records of version 0 held no deposit.
+	pub struct MigrateToV1<T, ListOwner>(PhantomData<(T, ListOwner)>);
+
+	impl<T: Config, ListOwner: Get<T::AccountId>> OnRuntimeUpgrade for MigrateToV1<T, ListOwner> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				log::info!(target: LOG_TARGET, "Skipping migration to v1, already applied");
+				return T::DbWeight::get().reads(1);
+			}
+			let (mut reads, mut writes) = (1u64, 1u64);
+
+			<PalletManager<T>>::translate::<bool, _>(|_, manager| {
+				reads += 1;
+				writes += 1;
+				manager.then(ManagerRole::full)
+			});
+
+			<ExternalIdAddress<T>>::translate::<ExternalId, _>(|who, provider, external_id| {
+				reads += 1;
+				writes += 2;
+				<ExternalIdOwners<T>>::insert((&provider, &external_id), who, ());
+				Some(ExternalIdRecord {
+					value: ExternalIdValue::Plain(external_id),
+					expires_at: None,
+				})
+			});
+
+			let mut members = BTreeMap::<ListName, u32>::new();
+			<UserList<T>>::translate::<bool, _>(|list_name, who, member| {
+				reads += 1;
+				writes += 1;
+				if !member {
+					return None;
+				}
+				writes += 1;
+				<Memberships<T>>::insert(who, &list_name, ());
+				*members.entry(list_name).or_default() += 1;
+				Some(ListMembership { expires_at: None })
+			});
+			for (list_name, member_count) in members {
+				reads += 1;
+				if <Lists<T>>::contains_key(&list_name) {
+					continue;
+				}
+				writes += 1;
+				<Lists<T>>::insert(
+					list_name,
+					ListInfo {
+						owner: ListOwner::get(),
+						description: Default::default(),
+						kind: ListKind::Allowlist,
+						max_size: 0,
+						member_count,
+					},
+				);
+			}
+
+			StorageVersion::new(1).put::<Pallet<T>>();
+			log::info!(target: LOG_TARGET, "Migrated to v1");
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+			ensure!(
+				Pallet::<T>::on_chain_storage_version() == 1,
+				"pallet-did is not at storage version 1"
+			);
+			Ok(())
+		}
+	}
+}
@@ -0,0 +1,191 @@
+//! Test utilities
+use super::*;
+use crate as pallet_did;
+
+use frame_support::{
+	construct_runtime, parameter_types,
+	traits::{ConstU32, ConstU64, DefensiveTruncateFrom, Everything},
+	weights::constants::RocksDbWeight,
+};
+use frame_system::EnsureRootWithSuccess;
+use sp_core::H256;
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup},
+	BuildStorage,
+};
+
+pub type AccountId = u64;
+pub type Balance = u64;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+construct_runtime!(
+	pub enum Test {
+		System: frame_system,
+		Balances: pallet_balances,
+		Did: pallet_did,
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type DbWeight = RocksDbWeight;
+	type RuntimeOrigin = RuntimeOrigin;
+	type Nonce = u64;
+	type RuntimeCall = RuntimeCall;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Block = Block;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type MaxLocks = ();
+	type Balance = Balance;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type FreezeIdentifier = ();
+	type MaxHolds = ConstU32<1>;
+	type MaxFreezes = ();
+}
+
+parameter_types! {
+	pub const RootAccount: AccountId = ROOT;
+}
+
+impl pallet_did::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type ManagerOrigin = EnsureRootWithSuccess<AccountId, RootAccount>;
+	type MaxBatchSize = ConstU32<4>;
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type DepositPerItem = ConstU64<10>;
+	type DepositPerByte = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+pub const ROOT: AccountId = 0;
+/// Manager of every provider and every list.
+pub const ADMIN: AccountId = 1;
+/// Manager of the `kyc` provider and the `vip` list only.
+pub const SCOPED: AccountId = 2;
+pub const ALICE: AccountId = 10;
+pub const BOB: AccountId = 11;
+pub const CHARLIE: AccountId = 12;
+/// Holds no balance.
+pub const POOR: AccountId = 20;
+
+pub(crate) struct ExtBuilder {
+	// endowed accounts with balances
+	balances: Vec<(AccountId, Balance)>,
+	// DID managers with their roles
+	managers: Vec<(AccountId, ManagerRole)>,
+	// DID lists created at genesis
+	lists: Vec<GenesisList<AccountId>>,
+}
+
+impl Default for ExtBuilder {
+	fn default() -> ExtBuilder {
+		ExtBuilder {
+			balances: vec![
+				(ADMIN, 10_000),
+				(SCOPED, 10_000),
+				(ALICE, 10_000),
+				(BOB, 10_000),
+				(CHARLIE, 10_000),
+			],
+			managers: vec![
+				(ADMIN, ManagerRole::full()),
+				(
+					SCOPED,
+					ManagerRole {
+						providers: Scope::Only(vec![provider("kyc")].try_into().unwrap()),
+						lists: Scope::Only(vec![list_name("vip")].try_into().unwrap()),
+					},
+				),
+			],
+			lists: vec![],
+		}
+	}
+}
+
+impl ExtBuilder {
+	pub(crate) fn with_lists(mut self, lists: Vec<GenesisList<AccountId>>) -> Self {
+		self.lists = lists;
+		self
+	}
+
+	pub(crate) fn build(self) -> sp_io::TestExternalities {
+		let mut t = frame_system::GenesisConfig::<Test>::default()
+			.build_storage()
+			.expect("Frame system builds valid default genesis config");
+
+		pallet_balances::GenesisConfig::<Test> {
+			balances: self.balances,
+		}
+		.assimilate_storage(&mut t)
+		.expect("Pallet balances storage can be assimilated");
+
+		pallet_did::GenesisConfig::<Test> {
+			managers: self.managers,
+			lists: self.lists,
+			..Default::default()
+		}
+		.assimilate_storage(&mut t)
+		.expect("Pallet did storage can be assimilated");
+
+		let mut ext = sp_io::TestExternalities::new(t);
+		ext.execute_with(|| System::set_block_number(1));
+		ext
+	}
+}
+
+pub fn provider(name: &str) -> Provider {
+	Provider::defensive_truncate_from(name.as_bytes().to_vec())
+}
+
+pub fn list_name(name: &str) -> ListName {
+	ListName::defensive_truncate_from(name.as_bytes().to_vec())
+}
+
+/// A genesis list of `kind` owned by `owner`, holding `members` and at most `max_size` of them.
+pub fn genesis_list(
+	name: &str,
+	owner: AccountId,
+	kind: ListKind,
+	max_size: u32,
+	members: Vec<AccountId>,
+) -> GenesisList<AccountId> {
+	GenesisList {
+		name: list_name(name),
+		owner,
+		description: Default::default(),
+		kind,
+		max_size,
+		members,
+	}
+}
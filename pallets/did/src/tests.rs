@@ -0,0 +1,1553 @@
+use crate::{mock::*, *};
+use frame_support::{
+	assert_noop, assert_ok, pallet_prelude::Encode, parameter_types, traits::Hooks, weights::Weight,
+};
+use sp_core::H256;
+use sp_runtime::{BuildStorage, DispatchError, DispatchResult, TokenError};
+
+fn add_link(
+	user: AccountId,
+	name: &str,
+	external_id: &str,
+	expires_at: Option<u64>,
+) -> DispatchResult {
+	Did::add_user_address(
+		RuntimeOrigin::signed(ADMIN),
+		user,
+		name.as_bytes().to_vec(),
+		external_id.as_bytes().to_vec(),
+		expires_at,
+	)
+}
+
+fn add_member(name: &str, user: AccountId, expires_at: Option<u64>) -> DispatchResult {
+	Did::add_user_to_list(
+		RuntimeOrigin::signed(ADMIN),
+		name.as_bytes().to_vec(),
+		user,
+		expires_at,
+	)
+}
+
+fn create_list(owner: AccountId, name: &str, kind: ListKind, max_size: u32) -> DispatchResult {
+	Did::create_list(
+		RuntimeOrigin::signed(owner),
+		name.as_bytes().to_vec(),
+		b"description".to_vec(),
+		kind,
+		max_size,
+	)
+}
+
+fn link(who: AccountId, name: &str) -> Option<ExternalIdRecord<u64>> {
+	ExternalIdAddress::<Test>::get(who, provider(name))
+}
+
+fn link_record(who: AccountId, name: &str) -> DidRecord<AccountId> {
+	DidRecord::ExternalId {
+		who,
+		provider: provider(name),
+	}
+}
+
+fn membership_record(name: &str, who: AccountId) -> DidRecord<AccountId> {
+	DidRecord::ListMembership {
+		list_name: list_name(name),
+		who,
+	}
+}
+
+fn held(who: AccountId) -> Balance {
+	Balances::reserved_balance(who)
+}
+
+/// Advance to block `n`, letting the idle sweeper run in every block on the way.
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		let next = System::block_number() + 1;
+		System::set_block_number(next);
+		Did::on_idle(next, Weight::MAX);
+	}
+}
+
+/// The Merkle root of `leaves`, built bottom up with the odd node of a level carried up as is.
+fn merkle_root(leaves: &[H256]) -> H256 {
+	let mut level = leaves.to_vec();
+	while level.len() > 1 {
+		level = level
+			.chunks(2)
+			.map(|pair| match pair {
+				[left, right] => Did::merkle_root_of(*left, &[*right]),
+				[single] => *single,
+				_ => unreachable!(),
+			})
+			.collect();
+	}
+	level[0]
+}
+
+#[test]
+fn managers_link_external_ids() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(add_link(ALICE, "kyc", "alice", None));
+
+		assert_eq!(
+			Did::external_id(ALICE, provider("kyc")).to_vec(),
+			b"alice".to_vec()
+		);
+		assert!(Did::has_external_id(&ALICE, &provider("kyc")));
+		assert_eq!(
+			Did::accounts_of(provider("kyc"), b"alice".to_vec().try_into().unwrap()),
+			vec![ALICE]
+		);
+		System::assert_last_event(RuntimeEvent::Did(Event::AddedUserAddress {
+			who: ALICE,
+			provider: provider("kyc"),
+			expires_at: None,
+		}));
+
+		assert_ok!(Did::remove_user_address(
+			RuntimeOrigin::signed(ADMIN),
+			ALICE,
+			b"kyc".to_vec()
+		));
+		assert!(link(ALICE, "kyc").is_none());
+		assert!(
+			Did::accounts_of(provider("kyc"), b"alice".to_vec().try_into().unwrap()).is_empty()
+		);
+		System::assert_last_event(RuntimeEvent::Did(Event::RemovedUserAddress {
+			who: ALICE,
+			provider: provider("kyc"),
+		}));
+	});
+}
+
+#[test]
+fn only_managers_write_external_ids() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			Did::add_user_address(
+				RuntimeOrigin::signed(ALICE),
+				ALICE,
+				b"kyc".to_vec(),
+				b"alice".to_vec(),
+				None
+			),
+			Error::<Test>::InvalidOrigin
+		);
+		assert_noop!(
+			Did::remove_user_address(RuntimeOrigin::signed(ALICE), ALICE, b"kyc".to_vec()),
+			Error::<Test>::InvalidOrigin
+		);
+	});
+}
+
+#[test]
+fn links_expire_lazily_before_they_are_swept() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(add_link(ALICE, "kyc", "alice", Some(5)));
+		assert_eq!(
+			Expiries::<Test>::get(5, link_record(ALICE, "kyc")),
+			Some(())
+		);
+
+		System::set_block_number(4);
+		assert!(Did::has_external_id(&ALICE, &provider("kyc")));
+
+		System::set_block_number(5);
+		assert!(!Did::has_external_id(&ALICE, &provider("kyc")));
+		assert!(Did::external_id(ALICE, provider("kyc")).is_empty());
+		assert!(Did::external_ids_of(&ALICE).is_empty());
+		assert!(link(ALICE, "kyc").is_some());
+	});
+}
+
+#[test]
+fn expiries_must_be_in_the_future() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			add_link(ALICE, "kyc", "alice", Some(1)),
+			Error::<Test>::ExpiryInPast
+		);
+		assert_ok!(create_list(ADMIN, "vip", ListKind::Allowlist, 0));
+		assert_noop!(
+			add_member("vip", ALICE, Some(1)),
+			Error::<Test>::ExpiryInPast
+		);
+	});
+}
+
+#[test]
+fn the_sweeper_removes_expired_records_and_releases_their_deposits() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "vip", ListKind::Allowlist, 0));
+		assert_ok!(add_link(ALICE, "kyc", "alice", Some(3)));
+		assert_ok!(add_member("vip", BOB, Some(3)));
+		assert_ok!(add_link(CHARLIE, "kyc", "charlie", None));
+		let held_for_charlie = Did::deposit_for(
+			&link_record(CHARLIE, "kyc"),
+			link(CHARLIE, "kyc").unwrap().encoded_size(),
+		);
+
+		run_to_block(3);
+
+		assert!(link(ALICE, "kyc").is_none());
+		assert!(UserList::<Test>::get(list_name("vip"), BOB).is_none());
+		assert!(Memberships::<Test>::get(BOB, list_name("vip")).is_none());
+		assert_eq!(Did::list(list_name("vip")).unwrap().member_count, 0);
+		assert!(Expiries::<Test>::iter_prefix(3).next().is_none());
+		assert!(Deposits::<Test>::get(link_record(ALICE, "kyc")).is_none());
+		assert!(Deposits::<Test>::get(membership_record("vip", BOB)).is_none());
+		assert_eq!(held(ADMIN), held_for_charlie);
+		assert!(link(CHARLIE, "kyc").is_some());
+		assert_eq!(ExpiryCursor::<Test>::get(), Some(4));
+		System::assert_has_event(RuntimeEvent::Did(Event::UserAddressExpired {
+			who: ALICE,
+			provider: provider("kyc"),
+		}));
+		System::assert_has_event(RuntimeEvent::Did(Event::UserListMembershipExpired {
+			who: BOB,
+			list_name: list_name("vip"),
+		}));
+	});
+}
+
+#[test]
+fn the_sweeper_carries_on_where_the_weight_ran_out() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(add_link(ALICE, "kyc", "alice", Some(3)));
+		assert_ok!(add_link(BOB, "kyc", "bob", Some(3)));
+		run_to_block(2);
+		assert_eq!(ExpiryCursor::<Test>::get(), Some(3));
+
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+		let one_record = db_weight
+			.reads_writes(1, 1)
+			.saturating_add(db_weight.reads(1))
+			.saturating_add(db_weight.reads_writes(4, 6));
+		System::set_block_number(3);
+		assert_eq!(Did::on_idle(3, Weight::zero()), Weight::zero());
+		assert_eq!(Did::on_idle(3, one_record), one_record);
+
+		assert_eq!(Expiries::<Test>::iter_prefix(3).count(), 1);
+		assert_eq!(
+			link(ALICE, "kyc").is_some() as u32 + link(BOB, "kyc").is_some() as u32,
+			1
+		);
+		assert_eq!(ExpiryCursor::<Test>::get(), Some(3));
+
+		System::set_block_number(4);
+		Did::on_idle(4, Weight::MAX);
+		assert!(link(ALICE, "kyc").is_none());
+		assert!(link(BOB, "kyc").is_none());
+		assert_eq!(ExpiryCursor::<Test>::get(), Some(5));
+	});
+}
+
+#[test]
+fn relinking_moves_the_scheduled_expiry() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(add_link(ALICE, "kyc", "alice", Some(3)));
+		assert_ok!(add_link(ALICE, "kyc", "alice-2", Some(8)));
+
+		assert!(Expiries::<Test>::get(3, link_record(ALICE, "kyc")).is_none());
+		assert_eq!(
+			Expiries::<Test>::get(8, link_record(ALICE, "kyc")),
+			Some(())
+		);
+		assert!(
+			Did::accounts_of(provider("kyc"), b"alice".to_vec().try_into().unwrap()).is_empty()
+		);
+
+		run_to_block(3);
+		assert_eq!(
+			Did::external_id(ALICE, provider("kyc")).to_vec(),
+			b"alice-2".to_vec()
+		);
+	});
+}
+
+#[test]
+fn renew_user_address_reschedules_unexpired_links() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(add_link(ALICE, "kyc", "alice", Some(3)));
+		assert_ok!(Did::renew_user_address(
+			RuntimeOrigin::signed(ADMIN),
+			ALICE,
+			b"kyc".to_vec(),
+			Some(6)
+		));
+		assert!(Expiries::<Test>::get(3, link_record(ALICE, "kyc")).is_none());
+		assert_eq!(
+			Expiries::<Test>::get(6, link_record(ALICE, "kyc")),
+			Some(())
+		);
+		System::assert_last_event(RuntimeEvent::Did(Event::RenewedUserAddress {
+			who: ALICE,
+			provider: provider("kyc"),
+			expires_at: Some(6),
+		}));
+
+		assert_ok!(Did::renew_user_address(
+			RuntimeOrigin::signed(ADMIN),
+			ALICE,
+			b"kyc".to_vec(),
+			None
+		));
+		assert!(Expiries::<Test>::iter().next().is_none());
+		run_to_block(10);
+		assert!(Did::has_external_id(&ALICE, &provider("kyc")));
+	});
+}
+
+#[test]
+fn renew_user_address_rejects_missing_expired_and_past() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			Did::renew_user_address(RuntimeOrigin::signed(ADMIN), ALICE, b"kyc".to_vec(), None),
+			Error::<Test>::RecordNotFound
+		);
+		assert_ok!(add_link(ALICE, "kyc", "alice", Some(3)));
+		assert_noop!(
+			Did::renew_user_address(
+				RuntimeOrigin::signed(ADMIN),
+				ALICE,
+				b"kyc".to_vec(),
+				Some(1)
+			),
+			Error::<Test>::ExpiryInPast
+		);
+		assert_noop!(
+			Did::renew_user_address(RuntimeOrigin::signed(BOB), ALICE, b"kyc".to_vec(), None),
+			Error::<Test>::InvalidOrigin
+		);
+
+		System::set_block_number(3);
+		assert_noop!(
+			Did::renew_user_address(
+				RuntimeOrigin::signed(ADMIN),
+				ALICE,
+				b"kyc".to_vec(),
+				Some(9)
+			),
+			Error::<Test>::RecordNotFound
+		);
+	});
+}
+
+#[test]
+fn renew_user_in_list_reschedules_unexpired_memberships() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "vip", ListKind::Allowlist, 0));
+		assert_noop!(
+			Did::renew_user_in_list(RuntimeOrigin::signed(ADMIN), b"vip".to_vec(), ALICE, None),
+			Error::<Test>::RecordNotFound
+		);
+		assert_ok!(add_member("vip", ALICE, Some(3)));
+		assert_ok!(Did::renew_user_in_list(
+			RuntimeOrigin::signed(ADMIN),
+			b"vip".to_vec(),
+			ALICE,
+			Some(7)
+		));
+		assert!(Expiries::<Test>::get(3, membership_record("vip", ALICE)).is_none());
+		assert_eq!(
+			Expiries::<Test>::get(7, membership_record("vip", ALICE)),
+			Some(())
+		);
+		System::assert_last_event(RuntimeEvent::Did(Event::RenewedUserInList {
+			who: ALICE,
+			list_name: list_name("vip"),
+			expires_at: Some(7),
+		}));
+
+		System::set_block_number(7);
+		assert!(!Did::user_list(list_name("vip"), ALICE));
+		assert_noop!(
+			Did::renew_user_in_list(
+				RuntimeOrigin::signed(ADMIN),
+				b"vip".to_vec(),
+				ALICE,
+				Some(9)
+			),
+			Error::<Test>::RecordNotFound
+		);
+	});
+}
+
+#[test]
+fn commitments_are_opened_with_the_external_id_and_salt() {
+	ExtBuilder::default().build().execute_with(|| {
+		let salt = H256::repeat_byte(7);
+		let commitment = Did::commitment_of(b"alice", salt);
+		assert_ok!(Did::add_user_commitment(
+			RuntimeOrigin::signed(ADMIN),
+			ALICE,
+			b"kyc".to_vec(),
+			ExternalIdValue::Commitment(commitment),
+			None
+		));
+
+		assert!(Did::has_external_id(&ALICE, &provider("kyc")));
+		assert!(Did::external_id(ALICE, provider("kyc")).is_empty());
+		assert!(Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			b"alice",
+			salt,
+			&[]
+		));
+		assert!(!Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			b"alice",
+			H256::zero(),
+			&[]
+		));
+		assert!(!Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			b"bob",
+			salt,
+			&[]
+		));
+
+		assert_ok!(Did::verify_user_address(
+			RuntimeOrigin::signed(BOB),
+			ALICE,
+			b"kyc".to_vec(),
+			b"alice".to_vec(),
+			salt,
+			vec![]
+		));
+		System::assert_last_event(RuntimeEvent::Did(Event::ExternalIdVerified {
+			who: ALICE,
+			provider: provider("kyc"),
+		}));
+		assert_noop!(
+			Did::verify_user_address(
+				RuntimeOrigin::signed(BOB),
+				ALICE,
+				b"kyc".to_vec(),
+				b"bob".to_vec(),
+				salt,
+				vec![]
+			),
+			Error::<Test>::CommitmentMismatch
+		);
+	});
+}
+
+#[test]
+fn plain_external_ids_verify_without_a_salt() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(add_link(ALICE, "kyc", "alice", Some(4)));
+		assert!(Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			b"alice",
+			H256::zero(),
+			&[]
+		));
+		assert!(!Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			b"bob",
+			H256::zero(),
+			&[]
+		));
+
+		System::set_block_number(4);
+		assert!(!Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			b"alice",
+			H256::zero(),
+			&[]
+		));
+	});
+}
+
+#[test]
+fn merkle_roots_are_opened_with_one_attribute_and_its_proof() {
+	ExtBuilder::default().build().execute_with(|| {
+		let salt = H256::repeat_byte(3);
+		let attributes: [&[u8]; 3] = [b"name:alice", b"country:fr", b"age:30"];
+		let leaves: Vec<H256> = attributes
+			.iter()
+			.map(|attribute| Did::commitment_of(attribute, salt))
+			.collect();
+		let root = merkle_root(&leaves);
+		assert_ok!(Did::add_user_commitment(
+			RuntimeOrigin::signed(ADMIN),
+			ALICE,
+			b"kyc".to_vec(),
+			ExternalIdValue::MerkleRoot(root),
+			None
+		));
+
+		let node = Did::merkle_root_of(leaves[0], &[leaves[1]]);
+		assert!(Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			attributes[0],
+			salt,
+			&[leaves[1], leaves[2]]
+		));
+		assert!(Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			attributes[1],
+			salt,
+			&[leaves[0], leaves[2]]
+		));
+		assert!(Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			attributes[2],
+			salt,
+			&[node]
+		));
+		assert!(!Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			attributes[2],
+			salt,
+			&[]
+		));
+		assert!(!Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			b"age:31",
+			salt,
+			&[node]
+		));
+
+		assert_noop!(
+			Did::verify_user_address(
+				RuntimeOrigin::signed(BOB),
+				ALICE,
+				b"kyc".to_vec(),
+				attributes[2].to_vec(),
+				salt,
+				vec![H256::zero(); MAX_PROOF_DEPTH as usize + 1]
+			),
+			Error::<Test>::ProofTooLong
+		);
+	});
+}
+
+#[test]
+fn inner_merkle_nodes_do_not_open_as_attributes() {
+	ExtBuilder::default().build().execute_with(|| {
+		let salt = H256::repeat_byte(3);
+		let attributes: [&[u8]; 3] = [b"name:alice", b"country:fr", b"age:30"];
+		let leaves: Vec<H256> = attributes
+			.iter()
+			.map(|attribute| Did::commitment_of(attribute, salt))
+			.collect();
+		let root = merkle_root(&leaves);
+		assert_ok!(Did::add_user_commitment(
+			RuntimeOrigin::signed(ADMIN),
+			ALICE,
+			b"kyc".to_vec(),
+			ExternalIdValue::MerkleRoot(root),
+			None
+		));
+		assert!(Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			attributes[0],
+			salt,
+			&[leaves[1], leaves[2]]
+		));
+
+		// The node over the first two leaves, presented as the attribute `left` salted with
+		// `right`, only opens the root if leaves and nodes hash alike.
+		let (left, right) = if leaves[0] <= leaves[1] {
+			(leaves[0], leaves[1])
+		} else {
+			(leaves[1], leaves[0])
+		};
+		assert!(!Did::verify_external_id(
+			ALICE,
+			provider("kyc"),
+			left.as_bytes(),
+			right,
+			&[leaves[2]]
+		));
+		assert_ne!(
+			Did::commitment_of(left.as_bytes(), right),
+			Did::merkle_root_of(left, &[right])
+		);
+		assert_noop!(
+			Did::verify_user_address(
+				RuntimeOrigin::signed(BOB),
+				ALICE,
+				b"kyc".to_vec(),
+				left.as_bytes().to_vec(),
+				right,
+				vec![leaves[2]]
+			),
+			Error::<Test>::CommitmentMismatch
+		);
+	});
+}
+
+#[test]
+fn scoped_managers_only_write_their_providers_and_lists() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Did::add_user_address(
+			RuntimeOrigin::signed(SCOPED),
+			ALICE,
+			b"kyc".to_vec(),
+			b"alice".to_vec(),
+			None
+		));
+		assert_noop!(
+			Did::add_user_address(
+				RuntimeOrigin::signed(SCOPED),
+				ALICE,
+				b"email".to_vec(),
+				b"alice@example.com".to_vec(),
+				None
+			),
+			Error::<Test>::ProviderNotInScope
+		);
+		assert_noop!(
+			Did::add_user_commitment(
+				RuntimeOrigin::signed(SCOPED),
+				ALICE,
+				b"email".to_vec(),
+				ExternalIdValue::Commitment(H256::zero()),
+				None
+			),
+			Error::<Test>::ProviderNotInScope
+		);
+
+		assert_ok!(create_list(SCOPED, "vip", ListKind::Allowlist, 0));
+		assert_noop!(
+			create_list(SCOPED, "staff", ListKind::Allowlist, 0),
+			Error::<Test>::ListNotInScope
+		);
+		assert_ok!(create_list(ADMIN, "staff", ListKind::Allowlist, 0));
+		assert_noop!(
+			Did::add_user_to_list(
+				RuntimeOrigin::signed(SCOPED),
+				b"staff".to_vec(),
+				ALICE,
+				None
+			),
+			Error::<Test>::ListNotInScope
+		);
+		assert_noop!(
+			create_list(ALICE, "mine", ListKind::Allowlist, 0),
+			Error::<Test>::InvalidOrigin
+		);
+	});
+}
+
+#[test]
+fn the_manager_origin_adds_and_removes_managers() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			Did::add_did_manager(RuntimeOrigin::signed(ADMIN), ALICE, ManagerRole::full()),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Did::add_did_manager(
+			RuntimeOrigin::root(),
+			ALICE,
+			ManagerRole::full()
+		));
+		assert!(Did::is_manager(&ALICE));
+		System::assert_last_event(RuntimeEvent::Did(Event::AddedManager {
+			manager: ALICE,
+			role: ManagerRole::full(),
+		}));
+		assert_ok!(Did::add_user_address(
+			RuntimeOrigin::signed(ALICE),
+			BOB,
+			b"kyc".to_vec(),
+			b"bob".to_vec(),
+			None
+		));
+
+		assert_noop!(
+			Did::remove_did_manager(RuntimeOrigin::signed(ADMIN), ALICE),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Did::remove_did_manager(RuntimeOrigin::root(), ALICE));
+		assert!(Did::did_manager(ALICE).is_none());
+		System::assert_last_event(RuntimeEvent::Did(Event::RemovedManager { manager: ALICE }));
+	});
+}
+
+#[test]
+fn read_only_managers_write_nothing() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Did::add_did_manager(
+			RuntimeOrigin::root(),
+			ALICE,
+			ManagerRole::read_only()
+		));
+		assert!(Did::is_manager(&ALICE));
+		assert!(Did::did_manager(ALICE).unwrap().is_read_only());
+		assert!(ManagerRole {
+			providers: Scope::Only(Default::default()),
+			lists: Scope::None
+		}
+		.is_read_only());
+		assert!(!Did::did_manager(SCOPED).unwrap().is_read_only());
+		assert!(!ManagerRole::full().is_read_only());
+		assert_noop!(
+			Did::add_user_address(
+				RuntimeOrigin::signed(ALICE),
+				BOB,
+				b"kyc".to_vec(),
+				b"bob".to_vec(),
+				None
+			),
+			Error::<Test>::ProviderNotInScope
+		);
+		assert_noop!(
+			create_list(ALICE, "vip", ListKind::Allowlist, 0),
+			Error::<Test>::ListNotInScope
+		);
+	});
+}
+
+#[test]
+fn lists_are_created_updated_and_destroyed() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(SCOPED, "vip", ListKind::Denylist, 2));
+		assert_eq!(
+			Did::list(list_name("vip")),
+			Some(ListInfo {
+				owner: SCOPED,
+				description: b"description".to_vec().try_into().unwrap(),
+				kind: ListKind::Denylist,
+				max_size: 2,
+				member_count: 0,
+			})
+		);
+		System::assert_last_event(RuntimeEvent::Did(Event::ListCreated {
+			list_name: list_name("vip"),
+			owner: SCOPED,
+			kind: ListKind::Denylist,
+		}));
+		assert_noop!(
+			create_list(ADMIN, "vip", ListKind::Allowlist, 0),
+			Error::<Test>::ListAlreadyExists
+		);
+
+		assert_ok!(add_member("vip", ALICE, None));
+		assert_ok!(add_member("vip", BOB, None));
+		assert_noop!(
+			Did::update_list(RuntimeOrigin::signed(SCOPED), b"vip".to_vec(), vec![], 1),
+			Error::<Test>::MaxSizeBelowMemberCount
+		);
+		assert_ok!(Did::update_list(
+			RuntimeOrigin::signed(SCOPED),
+			b"vip".to_vec(),
+			b"new".to_vec(),
+			0
+		));
+		let list = Did::list(list_name("vip")).unwrap();
+		assert_eq!(
+			(list.description.to_vec(), list.max_size),
+			(b"new".to_vec(), 0)
+		);
+		System::assert_last_event(RuntimeEvent::Did(Event::ListUpdated {
+			list_name: list_name("vip"),
+		}));
+
+		assert_noop!(
+			Did::destroy_list(RuntimeOrigin::signed(SCOPED), b"vip".to_vec()),
+			Error::<Test>::ListNotEmpty
+		);
+		assert_ok!(Did::remove_users_from_list(
+			RuntimeOrigin::signed(SCOPED),
+			b"vip".to_vec(),
+			vec![ALICE, BOB].try_into().unwrap()
+		));
+		assert_ok!(Did::destroy_list(
+			RuntimeOrigin::signed(SCOPED),
+			b"vip".to_vec()
+		));
+		assert!(Did::list(list_name("vip")).is_none());
+		System::assert_last_event(RuntimeEvent::Did(Event::ListDestroyed {
+			list_name: list_name("vip"),
+		}));
+		assert_noop!(
+			Did::destroy_list(RuntimeOrigin::signed(ADMIN), b"vip".to_vec()),
+			Error::<Test>::ListNotFound
+		);
+		assert_noop!(
+			Did::update_list(RuntimeOrigin::signed(ADMIN), b"vip".to_vec(), vec![], 0),
+			Error::<Test>::ListNotFound
+		);
+	});
+}
+
+#[test]
+fn members_are_added_to_existing_lists_up_to_their_size() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(add_member("vip", ALICE, None), Error::<Test>::ListNotFound);
+		assert_ok!(create_list(ADMIN, "vip", ListKind::Allowlist, 2));
+		assert_ok!(add_member("vip", ALICE, None));
+		assert_ok!(add_member("vip", BOB, Some(9)));
+		System::assert_last_event(RuntimeEvent::Did(Event::AddedUserToList {
+			who: BOB,
+			list_name: list_name("vip"),
+			expires_at: Some(9),
+		}));
+		assert_noop!(add_member("vip", CHARLIE, None), Error::<Test>::ListFull);
+		// Updating a member does not take another place.
+		assert_ok!(add_member("vip", BOB, None));
+		assert_eq!(Did::list(list_name("vip")).unwrap().member_count, 2);
+		assert!(Expiries::<Test>::iter().next().is_none());
+
+		assert_eq!(Did::list_members(&list_name("vip")).len(), 2);
+		assert_eq!(
+			Did::membership(&list_name("vip"), &BOB),
+			Some(ListMembership { expires_at: None })
+		);
+		assert_eq!(Memberships::<Test>::iter_prefix(ALICE).count(), 1);
+
+		assert_ok!(Did::remove_user_from_list(
+			RuntimeOrigin::signed(ADMIN),
+			b"vip".to_vec(),
+			ALICE
+		));
+		assert!(!Did::user_list(list_name("vip"), ALICE));
+		assert!(Memberships::<Test>::iter_prefix(ALICE).next().is_none());
+		System::assert_last_event(RuntimeEvent::Did(Event::RemovedUserFromList {
+			who: ALICE,
+			list_name: list_name("vip"),
+		}));
+		assert_ok!(add_member("vip", CHARLIE, None));
+	});
+}
+
+#[test]
+fn list_owners_need_the_list_in_scope() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(SCOPED, "vip", ListKind::Allowlist, 0));
+		assert_ok!(Did::add_user_to_list(
+			RuntimeOrigin::signed(SCOPED),
+			b"vip".to_vec(),
+			ALICE,
+			None
+		));
+		let providers_only = ManagerRole {
+			providers: Scope::Only(vec![provider("kyc")].try_into().unwrap()),
+			lists: Scope::None,
+		};
+		assert_ok!(Did::add_did_manager(
+			RuntimeOrigin::root(),
+			SCOPED,
+			providers_only
+		));
+		assert_noop!(
+			Did::add_user_to_list(RuntimeOrigin::signed(SCOPED), b"vip".to_vec(), BOB, None),
+			Error::<Test>::ListNotInScope
+		);
+		assert_noop!(
+			Did::update_list(RuntimeOrigin::signed(SCOPED), b"vip".to_vec(), vec![], 0),
+			Error::<Test>::ListNotInScope
+		);
+
+		assert_ok!(Did::add_did_manager(
+			RuntimeOrigin::root(),
+			SCOPED,
+			ManagerRole::read_only()
+		));
+		assert_noop!(
+			Did::add_user_to_list(RuntimeOrigin::signed(SCOPED), b"vip".to_vec(), BOB, None),
+			Error::<Test>::ListNotInScope
+		);
+
+		assert_ok!(Did::remove_did_manager(RuntimeOrigin::root(), SCOPED));
+		assert_noop!(
+			Did::remove_user_from_list(RuntimeOrigin::signed(SCOPED), b"vip".to_vec(), ALICE),
+			Error::<Test>::InvalidOrigin
+		);
+		assert_noop!(
+			Did::update_list(RuntimeOrigin::signed(SCOPED), b"vip".to_vec(), vec![], 0),
+			Error::<Test>::InvalidOrigin
+		);
+	});
+}
+
+#[test]
+fn full_lists_make_room_by_sweeping_expired_members() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "vip", ListKind::Allowlist, 2));
+		assert_ok!(add_member("vip", ALICE, Some(3)));
+		assert_ok!(add_member("vip", BOB, None));
+
+		System::set_block_number(2);
+		assert_noop!(add_member("vip", CHARLIE, None), Error::<Test>::ListFull);
+
+		// Alice expired but was not swept yet.
+		System::set_block_number(3);
+		assert_eq!(Did::list(list_name("vip")).unwrap().member_count, 2);
+		assert_ok!(add_member("vip", CHARLIE, None));
+		assert!(UserList::<Test>::get(list_name("vip"), ALICE).is_none());
+		assert!(Expiries::<Test>::get(3, membership_record("vip", ALICE)).is_none());
+		assert_eq!(Did::list(list_name("vip")).unwrap().member_count, 2);
+		assert_eq!(
+			held(ADMIN),
+			Did::deposit_for(&membership_record("vip", BOB), 1) * 2
+		);
+		System::assert_has_event(RuntimeEvent::Did(Event::UserListMembershipExpired {
+			who: ALICE,
+			list_name: list_name("vip"),
+		}));
+	});
+}
+
+#[test]
+fn lists_let_members_through_by_kind() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "allow", ListKind::Allowlist, 0));
+		assert_ok!(create_list(ADMIN, "deny", ListKind::Denylist, 0));
+		assert_ok!(add_member("allow", ALICE, Some(5)));
+		assert_ok!(add_member("deny", ALICE, Some(5)));
+
+		assert!(Did::is_allowed(&list_name("allow"), &ALICE));
+		assert!(!Did::is_allowed(&list_name("allow"), &BOB));
+		assert!(!Did::is_allowed(&list_name("deny"), &ALICE));
+		assert!(Did::is_allowed(&list_name("deny"), &BOB));
+		assert!(!Did::is_allowed(&list_name("unknown"), &BOB));
+
+		System::set_block_number(5);
+		assert!(!Did::is_allowed(&list_name("allow"), &ALICE));
+		assert!(Did::is_allowed(&list_name("deny"), &ALICE));
+		assert!(Did::list_members(&list_name("allow")).is_empty());
+	});
+}
+
+parameter_types! {
+	pub AllowList: ListName = list_name("allow");
+	pub DenyList: ListName = list_name("deny");
+	pub Tiers: Vec<TierRequirement> = vec![
+		TierRequirement { providers: vec![provider("kyc")], lists: vec![] },
+		TierRequirement { providers: vec![provider("email")], lists: vec![list_name("allow")] },
+	];
+}
+
+#[test]
+fn list_origins_let_allowed_accounts_through() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "allow", ListKind::Allowlist, 0));
+		assert_ok!(add_member("allow", ALICE, None));
+
+		assert_eq!(
+			EnsureInList::<Test, AllowList>::try_origin(RuntimeOrigin::signed(ALICE)).ok(),
+			Some(ALICE)
+		);
+		assert!(EnsureInList::<Test, AllowList>::try_origin(RuntimeOrigin::signed(BOB)).is_err());
+		assert!(EnsureInList::<Test, AllowList>::try_origin(RuntimeOrigin::root()).is_err());
+		assert!(InList::<Test, AllowList>::contains(&ALICE));
+		assert!(!InList::<Test, AllowList>::contains(&BOB));
+	});
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+#[test]
+fn list_origins_have_a_successful_origin() {
+	ExtBuilder::default().build().execute_with(|| {
+		let origin = EnsureInList::<Test, AllowList>::try_successful_origin().unwrap();
+		assert!(EnsureInList::<Test, AllowList>::try_origin(origin).is_ok());
+		assert_eq!(Did::list(list_name("allow")).unwrap().member_count, 1);
+
+		assert_ok!(create_list(ADMIN, "deny", ListKind::Denylist, 0));
+		let who = frame_benchmarking::account("in_list", 0, 0);
+		assert_ok!(add_member("deny", who, None));
+		let origin = EnsureInList::<Test, DenyList>::try_successful_origin().unwrap();
+		assert_eq!(
+			EnsureInList::<Test, DenyList>::try_origin(origin).ok(),
+			Some(who)
+		);
+	});
+}
+
+#[test]
+fn batches_add_and_remove_every_user_or_none() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "vip", ListKind::Allowlist, 0));
+		assert_noop!(
+			Did::add_users_to_list(
+				RuntimeOrigin::signed(ADMIN),
+				b"vip".to_vec(),
+				vec![(ALICE, None), (BOB, Some(1))].try_into().unwrap()
+			),
+			Error::<Test>::ExpiryInPast
+		);
+		assert_ok!(Did::add_users_to_list(
+			RuntimeOrigin::signed(ADMIN),
+			b"vip".to_vec(),
+			vec![(ALICE, None), (BOB, Some(4))].try_into().unwrap()
+		));
+		assert_eq!(Did::list(list_name("vip")).unwrap().member_count, 2);
+		System::assert_last_event(RuntimeEvent::Did(Event::BatchAddedUsersToList {
+			list_name: list_name("vip"),
+			count: 2,
+		}));
+
+		assert_ok!(Did::remove_users_from_list(
+			RuntimeOrigin::signed(ADMIN),
+			b"vip".to_vec(),
+			vec![ALICE, CHARLIE].try_into().unwrap()
+		));
+		System::assert_last_event(RuntimeEvent::Did(Event::BatchRemovedUsersFromList {
+			list_name: list_name("vip"),
+			count: 1,
+		}));
+		assert_eq!(Did::list_members(&list_name("vip")), vec![BOB]);
+
+		assert_noop!(
+			Did::add_user_addresses(
+				RuntimeOrigin::signed(SCOPED),
+				b"email".to_vec(),
+				vec![(ALICE, b"alice".to_vec().try_into().unwrap(), None)]
+					.try_into()
+					.unwrap()
+			),
+			Error::<Test>::ProviderNotInScope
+		);
+		assert_ok!(Did::add_user_addresses(
+			RuntimeOrigin::signed(SCOPED),
+			b"kyc".to_vec(),
+			vec![
+				(ALICE, b"alice".to_vec().try_into().unwrap(), None),
+				(BOB, b"bob".to_vec().try_into().unwrap(), Some(6)),
+			]
+			.try_into()
+			.unwrap()
+		));
+		assert_eq!(
+			Did::external_id(BOB, provider("kyc")).to_vec(),
+			b"bob".to_vec()
+		);
+		assert_eq!(Expiries::<Test>::get(6, link_record(BOB, "kyc")), Some(()));
+		System::assert_last_event(RuntimeEvent::Did(Event::BatchAddedUserAddresses {
+			provider: provider("kyc"),
+			count: 2,
+		}));
+	});
+}
+
+#[test]
+fn batches_are_bounded() {
+	ExtBuilder::default().build().execute_with(|| {
+		let users: Vec<(AccountId, Option<u64>)> = (0..5).map(|who| (who, None)).collect();
+		assert!(ListEntries::<Test>::try_from(users).is_err());
+	});
+}
+
+#[test]
+fn deposits_are_held_from_the_manager_and_released_on_removal() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "vip", ListKind::Allowlist, 0));
+		// Key: variant, account and "kyc". Value: variant, "alice" and no expiry.
+		assert_eq!(Did::deposit_for(&link_record(ALICE, "kyc"), 8), 10 + 13 + 8);
+
+		assert_ok!(add_link(ALICE, "kyc", "alice", None));
+		assert_eq!(held(ADMIN), 31);
+		assert_eq!(
+			Deposits::<Test>::get(link_record(ALICE, "kyc")),
+			Some((ADMIN, 31))
+		);
+		System::assert_has_event(RuntimeEvent::Did(Event::DepositHeld {
+			who: ADMIN,
+			amount: 31,
+		}));
+
+		// A longer id costs more, and only the new deposit is held.
+		assert_ok!(add_link(ALICE, "kyc", "alice-2", None));
+		assert_eq!(held(ADMIN), 33);
+		System::assert_has_event(RuntimeEvent::Did(Event::DepositReleased {
+			who: ADMIN,
+			amount: 31,
+		}));
+
+		assert_ok!(add_member("vip", ALICE, Some(9)));
+		let membership = Did::deposit_for(&membership_record("vip", ALICE), 9);
+		assert_eq!(held(ADMIN), 33 + membership);
+
+		assert_ok!(Did::remove_user_address(
+			RuntimeOrigin::signed(ADMIN),
+			ALICE,
+			b"kyc".to_vec()
+		));
+		assert_ok!(Did::remove_user_from_list(
+			RuntimeOrigin::signed(ADMIN),
+			b"vip".to_vec(),
+			ALICE
+		));
+		assert_eq!(held(ADMIN), 0);
+		assert_eq!(Balances::free_balance(ADMIN), 10_000);
+		assert!(Deposits::<Test>::iter().next().is_none());
+	});
+}
+
+#[test]
+fn users_pay_the_deposits_of_their_own_records() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "vip", ListKind::Allowlist, 0));
+		assert_ok!(add_link(ALICE, "kyc", "alice", None));
+		assert_ok!(add_member("vip", ALICE, Some(5)));
+		let membership = Did::deposit_for(&membership_record("vip", ALICE), 9);
+
+		assert_ok!(Did::pay_deposit(
+			RuntimeOrigin::signed(ALICE),
+			link_record(ALICE, "kyc")
+		));
+		assert_eq!(held(ADMIN), membership);
+		assert_eq!(held(ALICE), 31);
+		assert_eq!(
+			Deposits::<Test>::get(link_record(ALICE, "kyc")),
+			Some((ALICE, 31))
+		);
+		System::assert_has_event(RuntimeEvent::Did(Event::DepositReleased {
+			who: ADMIN,
+			amount: 31,
+		}));
+		System::assert_last_event(RuntimeEvent::Did(Event::DepositHeld {
+			who: ALICE,
+			amount: 31,
+		}));
+
+		assert_noop!(
+			Did::pay_deposit(RuntimeOrigin::signed(BOB), membership_record("vip", ALICE)),
+			Error::<Test>::NotAllowedToMutate
+		);
+		assert_noop!(
+			Did::pay_deposit(RuntimeOrigin::signed(BOB), link_record(BOB, "kyc")),
+			Error::<Test>::RecordNotFound
+		);
+
+		// Whoever paid last gets the deposit back.
+		assert_ok!(Did::remove_user_address(
+			RuntimeOrigin::signed(ADMIN),
+			ALICE,
+			b"kyc".to_vec()
+		));
+		assert_eq!(held(ALICE), 0);
+
+		System::set_block_number(5);
+		assert_noop!(
+			Did::pay_deposit(
+				RuntimeOrigin::signed(ALICE),
+				membership_record("vip", ALICE)
+			),
+			Error::<Test>::RecordNotFound
+		);
+	});
+}
+
+#[test]
+fn managers_without_funds_cannot_write() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Did::add_did_manager(
+			RuntimeOrigin::root(),
+			POOR,
+			ManagerRole::full()
+		));
+		assert_noop!(
+			Did::add_user_address(
+				RuntimeOrigin::signed(POOR),
+				ALICE,
+				b"kyc".to_vec(),
+				b"alice".to_vec(),
+				None
+			),
+			TokenError::CannotCreateHold
+		);
+	});
+}
+
+#[test]
+fn genesis_seeds_managers_lists_and_external_ids() {
+	let mut t = frame_system::GenesisConfig::<Test>::default()
+		.build_storage()
+		.unwrap();
+	crate::GenesisConfig::<Test> {
+		managers: vec![(ADMIN, ManagerRole::full())],
+		providers: vec![
+			(provider("kyc"), vec![SCOPED]),
+			(provider("email"), vec![SCOPED]),
+		],
+		lists: vec![genesis_list(
+			"vip",
+			ADMIN,
+			ListKind::Allowlist,
+			3,
+			vec![ALICE, BOB, ALICE],
+		)],
+		external_ids: vec![
+			(
+				ALICE,
+				provider("kyc"),
+				ExternalIdValue::Plain(b"alice".to_vec().try_into().unwrap()),
+				None,
+			),
+			(
+				BOB,
+				provider("kyc"),
+				ExternalIdValue::Commitment(H256::zero()),
+				Some(5),
+			),
+		],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	sp_io::TestExternalities::new(t).execute_with(|| {
+		System::set_block_number(1);
+		assert_eq!(Did::did_manager(ADMIN), Some(ManagerRole::full()));
+		assert_eq!(
+			Did::did_manager(SCOPED),
+			Some(ManagerRole {
+				providers: Scope::Only(
+					vec![provider("kyc"), provider("email")].try_into().unwrap()
+				),
+				lists: Scope::None,
+			})
+		);
+		assert_eq!(Did::list(list_name("vip")).unwrap().member_count, 2);
+		assert!(Did::user_list(list_name("vip"), BOB));
+		assert_eq!(
+			Did::accounts_of(provider("kyc"), b"alice".to_vec().try_into().unwrap()),
+			vec![ALICE]
+		);
+		assert_eq!(Expiries::<Test>::get(5, link_record(BOB, "kyc")), Some(()));
+		assert!(Deposits::<Test>::iter().next().is_none());
+	});
+}
+
+#[test]
+fn list_members_are_paged() {
+	ExtBuilder::default()
+		.with_lists(vec![genesis_list(
+			"vip",
+			ADMIN,
+			ListKind::Allowlist,
+			0,
+			vec![ALICE, BOB, CHARLIE],
+		)])
+		.build()
+		.execute_with(|| {
+			let first = Did::list_members_page(&list_name("vip"), None, 2);
+			assert_eq!(first.len(), 2);
+			let rest = Did::list_members_page(&list_name("vip"), Some(first[1].0), 2);
+			assert_eq!(rest.len(), 1);
+			let mut members: Vec<_> = first.iter().chain(&rest).map(|(who, _)| *who).collect();
+			members.sort();
+			assert_eq!(members, vec![ALICE, BOB, CHARLIE]);
+		});
+}
+
+#[test]
+fn migrations_are_approved_cancelled_and_accepted() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			Did::approve_migration(RuntimeOrigin::signed(ALICE), ALICE),
+			Error::<Test>::MigrationToSelf
+		);
+		assert_noop!(
+			Did::cancel_migration(RuntimeOrigin::signed(ALICE)),
+			Error::<Test>::MigrationNotApproved
+		);
+		assert_ok!(Did::approve_migration(RuntimeOrigin::signed(ALICE), BOB));
+		System::assert_last_event(RuntimeEvent::Did(Event::MigrationApproved {
+			from: ALICE,
+			to: BOB,
+		}));
+		assert_ok!(Did::cancel_migration(RuntimeOrigin::signed(ALICE)));
+		System::assert_last_event(RuntimeEvent::Did(Event::MigrationCancelled {
+			from: ALICE,
+			to: BOB,
+		}));
+		assert_noop!(
+			Did::accept_migration(
+				RuntimeOrigin::signed(BOB),
+				ALICE,
+				MigrationWitness::default()
+			),
+			Error::<Test>::MigrationNotApproved
+		);
+	});
+}
+
+#[test]
+fn accepting_a_migration_moves_every_record() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "deny", ListKind::Denylist, 0));
+		assert_ok!(add_link(ALICE, "kyc", "alice", Some(6)));
+		assert_ok!(add_member("deny", ALICE, None));
+		let deposit = Deposits::<Test>::get(link_record(ALICE, "kyc")).unwrap();
+		assert_ok!(Did::approve_migration(
+			RuntimeOrigin::signed(ALICE),
+			CHARLIE
+		));
+
+		assert_noop!(
+			Did::accept_migration(
+				RuntimeOrigin::signed(CHARLIE),
+				ALICE,
+				MigrationWitness {
+					external_ids: 1,
+					memberships: 0
+				}
+			),
+			Error::<Test>::BadWitness
+		);
+		assert_ok!(Did::accept_migration(
+			RuntimeOrigin::signed(CHARLIE),
+			ALICE,
+			MigrationWitness {
+				external_ids: 1,
+				memberships: 1
+			}
+		));
+		System::assert_last_event(RuntimeEvent::Did(Event::AccountMigrated {
+			from: ALICE,
+			to: CHARLIE,
+			external_ids: 1,
+			memberships: 1,
+		}));
+
+		assert!(PendingMigrations::<Test>::get(ALICE).is_none());
+		assert!(link(ALICE, "kyc").is_none());
+		assert_eq!(
+			Did::external_id(CHARLIE, provider("kyc")).to_vec(),
+			b"alice".to_vec()
+		);
+		assert_eq!(
+			Did::accounts_of(provider("kyc"), b"alice".to_vec().try_into().unwrap()),
+			vec![CHARLIE]
+		);
+		assert!(!Did::user_list(list_name("deny"), ALICE));
+		assert!(!Did::is_allowed(&list_name("deny"), &CHARLIE));
+		assert_eq!(Memberships::<Test>::iter_prefix(CHARLIE).count(), 1);
+		assert_eq!(
+			Expiries::<Test>::get(6, link_record(CHARLIE, "kyc")),
+			Some(())
+		);
+		assert!(Expiries::<Test>::get(6, link_record(ALICE, "kyc")).is_none());
+		assert_eq!(
+			Deposits::<Test>::get(link_record(CHARLIE, "kyc")),
+			Some(deposit)
+		);
+
+		run_to_block(6);
+		assert!(link(CHARLIE, "kyc").is_none());
+		assert_eq!(
+			held(ADMIN),
+			Did::deposit_for(&membership_record("deny", CHARLIE), 1)
+		);
+	});
+}
+
+#[test]
+fn migrations_are_only_accepted_by_accounts_without_records() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(add_link(ALICE, "kyc", "alice", None));
+		assert_ok!(add_link(BOB, "email", "bob", None));
+		assert_ok!(Did::approve_migration(RuntimeOrigin::signed(ALICE), BOB));
+		assert_noop!(
+			Did::accept_migration(
+				RuntimeOrigin::signed(BOB),
+				ALICE,
+				MigrationWitness {
+					external_ids: 1,
+					memberships: 0
+				}
+			),
+			Error::<Test>::AccountHasRecords
+		);
+	});
+}
+
+#[test]
+fn migrations_check_the_witness_first() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(add_link(ALICE, "kyc", "alice", None));
+		assert_ok!(add_link(ALICE, "email", "alice", None));
+		assert_ok!(add_link(BOB, "email", "bob", None));
+		assert_ok!(Did::approve_migration(RuntimeOrigin::signed(ALICE), BOB));
+		assert_noop!(
+			Did::accept_migration(
+				RuntimeOrigin::signed(BOB),
+				ALICE,
+				MigrationWitness {
+					external_ids: 1,
+					memberships: 0
+				}
+			),
+			Error::<Test>::BadWitness
+		);
+	});
+}
+
+#[test]
+fn expired_records_do_not_block_a_migration() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(ADMIN, "vip", ListKind::Allowlist, 0));
+		assert_ok!(add_link(ALICE, "kyc", "alice", None));
+		assert_ok!(add_link(BOB, "kyc", "bob", Some(3)));
+		assert_ok!(add_member("vip", BOB, Some(3)));
+		let alice_deposit = Deposits::<Test>::get(link_record(ALICE, "kyc")).unwrap().1;
+		assert_ok!(Did::approve_migration(RuntimeOrigin::signed(ALICE), BOB));
+
+		System::set_block_number(3);
+		assert_ok!(Did::accept_migration(
+			RuntimeOrigin::signed(BOB),
+			ALICE,
+			MigrationWitness {
+				external_ids: 1,
+				memberships: 0
+			}
+		));
+		assert_eq!(
+			Did::external_id(BOB, provider("kyc")).to_vec(),
+			b"alice".to_vec()
+		);
+		assert!(UserList::<Test>::get(list_name("vip"), BOB).is_none());
+		assert_eq!(Did::list(list_name("vip")).unwrap().member_count, 0);
+		assert!(Expiries::<Test>::iter().next().is_none());
+		assert_eq!(held(ADMIN), alice_deposit);
+		System::assert_has_event(RuntimeEvent::Did(Event::UserAddressExpired {
+			who: BOB,
+			provider: provider("kyc"),
+		}));
+		System::assert_has_event(RuntimeEvent::Did(Event::UserListMembershipExpired {
+			who: BOB,
+			list_name: list_name("vip"),
+		}));
+	});
+}
+
+#[test]
+fn migrations_leave_roles_and_list_ownership_behind() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(create_list(SCOPED, "vip", ListKind::Allowlist, 0));
+		assert_ok!(Did::approve_migration(
+			RuntimeOrigin::signed(SCOPED),
+			CHARLIE
+		));
+		assert_ok!(Did::accept_migration(
+			RuntimeOrigin::signed(CHARLIE),
+			SCOPED,
+			MigrationWitness::default()
+		));
+		assert!(Did::is_manager(&SCOPED));
+		assert!(!Did::is_manager(&CHARLIE));
+		assert_eq!(Did::list(list_name("vip")).unwrap().owner, SCOPED);
+	});
+}
+
+#[test]
+fn did_tiers_are_reached_in_order() {
+	ExtBuilder::default().build().execute_with(|| {
+		type Identity = DidTiers<Test, Tiers>;
+		assert_ok!(create_list(ADMIN, "allow", ListKind::Allowlist, 0));
+		assert_eq!(Identity::max_tier(), 2);
+		assert_eq!(Identity::tier(&ALICE), 0);
+
+		assert_ok!(add_link(ALICE, "email", "alice@example.com", None));
+		assert_ok!(add_member("allow", ALICE, None));
+		// The second tier is met, but not the first one.
+		assert_eq!(Identity::tier(&ALICE), 0);
+
+		assert_ok!(add_link(ALICE, "kyc", "alice", Some(5)));
+		assert_eq!(Identity::tier(&ALICE), 2);
+		assert!(Identity::meets_tier(&ALICE, 2));
+		assert!(Identity::has_provider(&ALICE, &provider("kyc")));
+		assert!(Identity::in_list(&list_name("allow"), &ALICE));
+		assert!(Identity::is_member(&list_name("allow"), &ALICE));
+
+		System::set_block_number(5);
+		assert_eq!(Identity::tier(&ALICE), 0);
+		assert!(!Identity::meets_tier(&ALICE, 1));
+	});
+}
+
+#[test]
+fn nobody_is_verified_without_identities() {
+	assert_eq!(<() as VerifyIdentity<AccountId>>::tier(&ALICE), 0);
+	assert_eq!(<() as VerifyIdentity<AccountId>>::max_tier(), 0);
+	assert!(<() as VerifyIdentity<AccountId>>::meets_tier(&ALICE, 0));
+	assert!(!<() as VerifyIdentity<AccountId>>::meets_tier(&ALICE, 1));
+	assert!(!<() as VerifyIdentity<AccountId>>::has_provider(
+		&ALICE,
+		&provider("kyc")
+	));
+	assert!(!<() as VerifyIdentity<AccountId>>::in_list(
+		&list_name("vip"),
+		&ALICE
+	));
+}
+
+#[test]
+fn v0_storage_migrates_to_v1() {
+	use frame_support::{
+		storage::unhashed,
+		traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	};
+	parameter_types! {
+		pub const ListOwner: AccountId = ADMIN;
+	}
+	type MigrateToV1 = migrations::v1::MigrateToV1<Test, ListOwner>;
+
+	sp_io::TestExternalities::default().execute_with(|| {
+		let external_id = ExternalId::truncate_from(b"bob".to_vec());
+		unhashed::put(&PalletManager::<Test>::hashed_key_for(ALICE), &true);
+		unhashed::put(&PalletManager::<Test>::hashed_key_for(BOB), &false);
+		unhashed::put(
+			&ExternalIdAddress::<Test>::hashed_key_for(BOB, provider("kyc")),
+			&external_id,
+		);
+		unhashed::put(
+			&UserList::<Test>::hashed_key_for(list_name("old"), CHARLIE),
+			&true,
+		);
+		unhashed::put(
+			&UserList::<Test>::hashed_key_for(list_name("old"), BOB),
+			&false,
+		);
+
+		MigrateToV1::on_runtime_upgrade();
+
+		assert_eq!(Did::on_chain_storage_version(), StorageVersion::new(1));
+		assert_eq!(Did::did_manager(ALICE), Some(ManagerRole::full()));
+		assert_eq!(Did::did_manager(BOB), None);
+		assert_eq!(
+			link(BOB, "kyc"),
+			Some(ExternalIdRecord {
+				value: ExternalIdValue::Plain(external_id.clone()),
+				expires_at: None
+			})
+		);
+		assert!(ExternalIdOwners::<Test>::contains_key(
+			(provider("kyc"), external_id),
+			BOB
+		));
+		assert!(Did::is_allowed(&list_name("old"), &CHARLIE));
+		assert!(!Did::is_allowed(&list_name("old"), &BOB));
+		assert!(Memberships::<Test>::contains_key(CHARLIE, list_name("old")));
+		let list = Did::list(list_name("old")).unwrap();
+		assert_eq!(
+			(list.owner, list.kind, list.member_count),
+			(ADMIN, ListKind::Allowlist, 1)
+		);
+
+		// It runs once.
+		assert_eq!(
+			MigrateToV1::on_runtime_upgrade(),
+			<Test as frame_system::Config>::DbWeight::get().reads(1)
+		);
+	});
+}
@@ -0,0 +1,434 @@
+//! Weights for pallet_did.
+//!
+//! Regenerate with `scripts/benchmark.sh pallet_did <extrinsic>` on the reference machine
+//! after changing any dispatchable.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_did.
+pub trait WeightInfo {
+	fn add_user_address() -> Weight;
+	fn remove_user_address() -> Weight;
+	fn add_user_to_list() -> Weight;
+	fn remove_user_from_list() -> Weight;
+	fn add_did_manager() -> Weight;
+	fn remove_did_manager() -> Weight;
+	fn renew_user_address() -> Weight;
+	fn renew_user_in_list() -> Weight;
+	fn add_user_commitment() -> Weight;
+	fn verify_user_address() -> Weight;
+	fn create_list() -> Weight;
+	fn update_list() -> Weight;
+	fn destroy_list() -> Weight;
+	fn add_users_to_list(n: u32) -> Weight;
+	fn remove_users_from_list(n: u32) -> Weight;
+	fn add_user_addresses(n: u32) -> Weight;
+	fn approve_migration() -> Weight;
+	fn cancel_migration() -> Weight;
+	fn accept_migration(e: u32, m: u32) -> Weight;
+	fn pay_deposit() -> Weight;
+}
+
+/// Weights for pallet_did using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:1)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:2)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn add_user_address() -> Weight {
+		Weight::from_parts(21_000_000, 3719)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:1)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:1)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn remove_user_address() -> Weight {
+		Weight::from_parts(19_000_000, 3719)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:1 w:1)
+	/// Storage: `Did::Memberships` (r:0 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn add_user_to_list() -> Weight {
+		Weight::from_parts(27_000_000, 3813)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:1 w:1)
+	/// Storage: `Did::Memberships` (r:0 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:1)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn remove_user_from_list() -> Weight {
+		Weight::from_parts(25_000_000, 3813)
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:0 w:1)
+	fn add_did_manager() -> Weight {
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:0 w:1)
+	fn remove_did_manager() -> Weight {
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	fn renew_user_address() -> Weight {
+		Weight::from_parts(20_000_000, 3719)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:0)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:1 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	fn renew_user_in_list() -> Weight {
+		Weight::from_parts(23_000_000, 3813)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:1)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn add_user_commitment() -> Weight {
+		Weight::from_parts(21_000_000, 3719)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:0)
+	fn verify_user_address() -> Weight {
+		Weight::from_parts(17_000_000, 3719)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::Lists` (r:1 w:1)
+	fn create_list() -> Weight {
+		Weight::from_parts(16_000_000, 3813)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	fn update_list() -> Weight {
+		Weight::from_parts(17_000_000, 3813)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	fn destroy_list() -> Weight {
+		Weight::from_parts(17_000_000, 3813)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:n w:n)
+	/// Storage: `Did::Memberships` (r:0 w:n)
+	/// Storage: `Did::Expiries` (r:0 w:2n)
+	/// Storage: `Did::Deposits` (r:n w:n)
+	/// Storage: `Balances::Holds` (r:n w:n)
+	fn add_users_to_list(n: u32) -> Weight {
+		Weight::from_parts(14_000_000, 3813)
+			.saturating_add(Weight::from_parts(13_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes((6_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2533).saturating_mul(n.into()))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:n w:n)
+	/// Storage: `Did::Memberships` (r:0 w:n)
+	/// Storage: `Did::Expiries` (r:0 w:n)
+	/// Storage: `Did::Deposits` (r:n w:n)
+	/// Storage: `Balances::Holds` (r:n w:n)
+	fn remove_users_from_list(n: u32) -> Weight {
+		Weight::from_parts(14_000_000, 3813)
+			.saturating_add(Weight::from_parts(12_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes((5_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2533).saturating_mul(n.into()))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:n w:n)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:2n)
+	/// Storage: `Did::Expiries` (r:0 w:2n)
+	/// Storage: `Did::Deposits` (r:n w:n)
+	/// Storage: `Balances::Holds` (r:n w:n)
+	fn add_user_addresses(n: u32) -> Weight {
+		Weight::from_parts(12_000_000, 3559)
+			.saturating_add(Weight::from_parts(11_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes((7_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2687).saturating_mul(n.into()))
+	}
+	/// Storage: `Did::PendingMigrations` (r:0 w:1)
+	fn approve_migration() -> Weight {
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::PendingMigrations` (r:1 w:1)
+	fn cancel_migration() -> Weight {
+		Weight::from_parts(11_000_000, 3529)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::PendingMigrations` (r:1 w:1)
+	/// Storage: `Did::ExternalIdAddress` (r:e+1 w:2e)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:2e)
+	/// Storage: `Did::Memberships` (r:m+1 w:2m)
+	/// Storage: `Did::UserList` (r:m w:2m)
+	/// Storage: `Did::Expiries` (r:0 w:2e+2m)
+	/// Storage: `Did::Deposits` (r:e+m w:2e+2m)
+	fn accept_migration(e: u32, m: u32) -> Weight {
+		Weight::from_parts(18_000_000, 3529)
+			.saturating_add(Weight::from_parts(14_000_000, 0).saturating_mul(e.into()))
+			.saturating_add(Weight::from_parts(16_000_000, 0).saturating_mul(m.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(e.into())))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(m.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes((8_u64).saturating_mul(e.into())))
+			.saturating_add(T::DbWeight::get().writes((8_u64).saturating_mul(m.into())))
+			.saturating_add(Weight::from_parts(0, 2687).saturating_mul(e.into()))
+			.saturating_add(Weight::from_parts(0, 2533).saturating_mul(m.into()))
+	}
+	/// Storage: `Did::UserList` (r:1 w:0)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:2 w:2)
+	fn pay_deposit() -> Weight {
+		Weight::from_parts(26_000_000, 3813)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:1)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:2)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn add_user_address() -> Weight {
+		Weight::from_parts(21_000_000, 3719)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:1)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:1)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn remove_user_address() -> Weight {
+		Weight::from_parts(19_000_000, 3719)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(5_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:1 w:1)
+	/// Storage: `Did::Memberships` (r:0 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn add_user_to_list() -> Weight {
+		Weight::from_parts(27_000_000, 3813)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:1 w:1)
+	/// Storage: `Did::Memberships` (r:0 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:1)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn remove_user_from_list() -> Weight {
+		Weight::from_parts(25_000_000, 3813)
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:0 w:1)
+	fn add_did_manager() -> Weight {
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:0 w:1)
+	fn remove_did_manager() -> Weight {
+		Weight::from_parts(9_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	fn renew_user_address() -> Weight {
+		Weight::from_parts(20_000_000, 3719)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:0)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:1 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	fn renew_user_in_list() -> Weight {
+		Weight::from_parts(23_000_000, 3813)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:1)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:1)
+	/// Storage: `Did::Expiries` (r:0 w:2)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	fn add_user_commitment() -> Weight {
+		Weight::from_parts(21_000_000, 3719)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+	}
+	/// Storage: `Did::ExternalIdAddress` (r:1 w:0)
+	fn verify_user_address() -> Weight {
+		Weight::from_parts(17_000_000, 3719)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::Lists` (r:1 w:1)
+	fn create_list() -> Weight {
+		Weight::from_parts(16_000_000, 3813)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	fn update_list() -> Weight {
+		Weight::from_parts(17_000_000, 3813)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	fn destroy_list() -> Weight {
+		Weight::from_parts(17_000_000, 3813)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:n w:n)
+	/// Storage: `Did::Memberships` (r:0 w:n)
+	/// Storage: `Did::Expiries` (r:0 w:2n)
+	/// Storage: `Did::Deposits` (r:n w:n)
+	/// Storage: `Balances::Holds` (r:n w:n)
+	fn add_users_to_list(n: u32) -> Weight {
+		Weight::from_parts(14_000_000, 3813)
+			.saturating_add(Weight::from_parts(13_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes((6_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2533).saturating_mul(n.into()))
+	}
+	/// Storage: `Did::Lists` (r:1 w:1)
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::UserList` (r:n w:n)
+	/// Storage: `Did::Memberships` (r:0 w:n)
+	/// Storage: `Did::Expiries` (r:0 w:n)
+	/// Storage: `Did::Deposits` (r:n w:n)
+	/// Storage: `Balances::Holds` (r:n w:n)
+	fn remove_users_from_list(n: u32) -> Weight {
+		Weight::from_parts(14_000_000, 3813)
+			.saturating_add(Weight::from_parts(12_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes((5_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2533).saturating_mul(n.into()))
+	}
+	/// Storage: `Did::PalletManager` (r:1 w:0)
+	/// Storage: `Did::ExternalIdAddress` (r:n w:n)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:2n)
+	/// Storage: `Did::Expiries` (r:0 w:2n)
+	/// Storage: `Did::Deposits` (r:n w:n)
+	/// Storage: `Balances::Holds` (r:n w:n)
+	fn add_user_addresses(n: u32) -> Weight {
+		Weight::from_parts(12_000_000, 3559)
+			.saturating_add(Weight::from_parts(11_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes((7_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2687).saturating_mul(n.into()))
+	}
+	/// Storage: `Did::PendingMigrations` (r:0 w:1)
+	fn approve_migration() -> Weight {
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::PendingMigrations` (r:1 w:1)
+	fn cancel_migration() -> Weight {
+		Weight::from_parts(11_000_000, 3529)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `Did::PendingMigrations` (r:1 w:1)
+	/// Storage: `Did::ExternalIdAddress` (r:e+1 w:2e)
+	/// Storage: `Did::ExternalIdOwners` (r:0 w:2e)
+	/// Storage: `Did::Memberships` (r:m+1 w:2m)
+	/// Storage: `Did::UserList` (r:m w:2m)
+	/// Storage: `Did::Expiries` (r:0 w:2e+2m)
+	/// Storage: `Did::Deposits` (r:e+m w:2e+2m)
+	fn accept_migration(e: u32, m: u32) -> Weight {
+		Weight::from_parts(18_000_000, 3529)
+			.saturating_add(Weight::from_parts(14_000_000, 0).saturating_mul(e.into()))
+			.saturating_add(Weight::from_parts(16_000_000, 0).saturating_mul(m.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(e.into())))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(m.into())))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes((8_u64).saturating_mul(e.into())))
+			.saturating_add(RocksDbWeight::get().writes((8_u64).saturating_mul(m.into())))
+			.saturating_add(Weight::from_parts(0, 2687).saturating_mul(e.into()))
+			.saturating_add(Weight::from_parts(0, 2533).saturating_mul(m.into()))
+	}
+	/// Storage: `Did::UserList` (r:1 w:0)
+	/// Storage: `Did::Deposits` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:2 w:2)
+	fn pay_deposit() -> Weight {
+		Weight::from_parts(26_000_000, 3813)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+}
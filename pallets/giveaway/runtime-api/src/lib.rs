@@ -0,0 +1,33 @@
+//! Runtime API for reading the giveaways of `pallet-ocw-giveaway`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use pallet_ocw_giveaway::{EntryRequirement, GiveawayStatus};
+use scale_codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	/// Reads of giveaway statuses and entry requirements.
+	pub trait GiveawayApi<AccountId, BlockNumber, Balance, AssetId, NftCollectionId>
+	where
+		AccountId: Codec,
+		BlockNumber: Codec,
+		Balance: Codec,
+		AssetId: Codec,
+		NftCollectionId: Codec,
+	{
+		/// The status of giveaway `index` in the current block, if it exists.
+		fn status(index: u32) -> Option<GiveawayStatus>;
+
+		/// The entry requirements of giveaway `index`, if it exists.
+		fn entry_requirements(
+			index: u32,
+		) -> Option<Vec<EntryRequirement<BlockNumber, Balance, AssetId, NftCollectionId>>>;
+
+		/// The entry requirements of giveaway `index` that `who` does not meet.
+		fn unmet_requirements(
+			index: u32,
+			who: AccountId,
+		) -> Vec<EntryRequirement<BlockNumber, Balance, AssetId, NftCollectionId>>;
+	}
+}
@@ -0,0 +1,61 @@
+//! Benchmarking setup for pallet-ocw-giveaway
+
+use super::*;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller, BenchmarkError};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+use sp_std::vec;
+
+const SEED: u32 = 0;
+
+/// A giveaway of `MAX_WINNERS` winners, weighted by tier, that ended with `participants`
+/// participants of one entry each.
+fn ended_giveaway<T: Config>(participants: u32) -> Result<u32, BenchmarkError> {
+	let creator: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&creator, BalanceOf::<T>::max_value() / 2u32.into());
+	frame_system::Pallet::<T>::set_block_number(0u32.into());
+	let index = GiveawayIndex::<T>::get();
+	Pallet::<T>::create_give_away(
+		RawOrigin::Signed(creator).into(),
+		vec![0u8; 32],
+		1u32.into(),
+		2u32.into(),
+		0,
+		RandomType::LocalChain,
+		AssetType::FungibleToken,
+		Some(TokenInfo {
+			asset_id: T::NativeAssetId::get(),
+			amount: 1_000u32.into(),
+		}),
+		None,
+		PrizeSplit::Equal(MAX_WINNERS),
+		Default::default(),
+		None,
+		WeightRule::Tier,
+		RandomnessFallback::Refund,
+		u32::MAX,
+	)?;
+	for position in 0..participants {
+		let who: T::AccountId = account("participant", position, SEED);
+		Participants::<T>::insert(index, position, who);
+		CumulativeEntries::<T>::insert(index, position, u64::from(position) + 1);
+	}
+	TotalParticipantByGiveaway::<T>::insert(index, participants);
+	frame_system::Pallet::<T>::set_block_number(3u32.into());
+	Ok(index)
+}
+
+benchmarks! {
+	settle {
+		// The depth of the search for each winner, see `Pallet::search_depth`.
+		let d in 1 .. 12;
+		let index = ended_giveaway::<T>((1 << d) - 1)?;
+	}: {
+		Pallet::<T>::settle(index, U256::from(7), &RequestId::default())
+	}
+	verify {
+		assert_eq!(Pallet::<T>::giveaway_status(index), Some(GiveawayStatus::Drawn));
+	}
+
+	impl_benchmark_test_suite!(Pallet, crate::mock::ExtBuilder::default().build(), crate::mock::Test);
+}
@@ -1,23 +1,68 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+pub mod vrf;
+pub mod weights;
+
 use frame_support::{
 	dispatch::DispatchResult,
 	ensure,
 	pallet_prelude::MaxEncodedLen,
 	traits::{
 		tokens::{
-			fungible::Mutate as MutateFungible,
 			fungibles::{Create, Inspect, Mutate},
-			nonfungibles_v2::{Inspect as NonFungiblesInspect, Transfer},
-			AssetId, Balance as AssetBalance,
+			nonfungibles_v2::{Inspect as NonFungiblesInspect, InspectEnumerable, Transfer},
+			AssetId, Balance as AssetBalance, Fortitude, Preservation,
 		},
-		Currency, ExistenceRequirement, Get, Randomness, ReservableCurrency,
+		Currency, ExistenceRequirement, Get, Imbalance, OnKilledAccount, OnNewAccount, Randomness,
+		ReservableCurrency,
 	},
 	PalletId,
 };
-use sp_core::{crypto::KeyTypeId, U256};
+use sp_core::{crypto::KeyTypeId, hexdisplay::HexDisplay, U256};
+use vrf::{VrfOutput, VrfProof, VrfPublic};
+pub use weights::WeightInfo;
 
 pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"ga!!");
-use pallet_did::Provider;
+
+/// Offchain storage key of the URL offchain workers fetch randomness from. Node operators set
+/// it through the `offchain_localStorageSet` RPC; workers without it submit nothing.
+pub const RANDOMNESS_ENDPOINT_KEY: &[u8] = b"giveaway::randomness-endpoint";
+
+/// Prefix of the offchain locks taken while committing the results of a block.
+const RESULT_LOCK_PREFIX: &[u8] = b"giveaway::result-lock::";
+
+/// Prefix of the offchain storage keys holding the committed results of a block and their salt
+/// until they are revealed.
+const REVEAL_PREFIX: &[u8] = b"giveaway::reveal::";
+
+/// How long a randomness request may take.
+const HTTP_TIMEOUT_MS: u64 = 5_000;
+
+/// How long, besides `ResultLockBlocks`, a result lock is held.
+const RESULT_LOCK_TIMEOUT_MS: u64 = 20_000;
+
+/// How many blocks, starting with the one it is due in, a draw is queued in when the buckets of
+/// the earlier ones are full.
+const MAX_DEFER_BLOCKS: u32 = 8;
+
+/// How many giveaways are drawn with each page of results.
+pub const RESULTS_PAGE: u32 = 32;
+
+/// How many winners a giveaway may have.
+pub const MAX_WINNERS: u32 = 16;
+
+/// How many entry requirements a giveaway may have.
+pub const MAX_REQUIREMENTS: u32 = 8;
+
+const LOG_TARGET: &str = "runtime::giveaway";
+
+use pallet_did::{ListName, Tier, VerifyIdentity};
 pub mod crypto {
 	use super::KEY_TYPE;
 	use sp_runtime::{
@@ -36,20 +81,34 @@ pub mod crypto {
 	}
 }
 
-use frame_system::offchain::{AppCrypto, CreateSignedTransaction, Signer};
+use frame_system::{
+	offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
+	pallet_prelude::BlockNumberFor,
+};
 pub use pallet::*;
 use scale_codec::{Decode, Encode};
-use sp_runtime::traits::{AccountIdConversion, Saturating, Zero};
-use sp_std::vec::Vec;
+use scale_info::prelude::format;
+use sp_runtime::{
+	offchain::{
+		http,
+		storage_lock::{BlockAndTime, StorageLock},
+		Duration, StorageKind,
+	},
+	traits::{
+		AccountIdConversion, CheckedSub, Hash as HashT, Saturating, UniqueSaturatedInto, Zero,
+	},
+	Percent,
+};
+use sp_std::{collections::btree_map::BTreeMap, ops::Range, vec::Vec};
 type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 #[frame_support::pallet]
+#[allow(clippy::too_many_arguments)]
 pub mod pallet {
 	use super::*;
 	use frame_support::{
 		pallet_prelude::{OptionQuery, *},
 		traits::DefensiveTruncateFrom,
-		BoundedBTreeSet,
 	};
 	use frame_system::pallet_prelude::*;
 	use sp_std::{fmt::Display, prelude::*};
@@ -59,23 +118,26 @@ pub mod pallet {
 	pub type GiveawayName = BoundedVec<u8, ConstU32<128>>;
 	pub type RequestId = BoundedVec<u8, ConstU32<128>>;
 	pub type RandomResult = U256;
-	pub type Results = BoundedVec<RandomResult, ConstU32<32>>;
+	pub type Results = BoundedVec<RandomResult, ConstU32<RESULTS_PAGE>>;
+	pub type PrizeShares = BoundedVec<Percent, ConstU32<MAX_WINNERS>>;
+	pub type WinnersOf<T> =
+		BoundedVec<WinnerInfo<<T as frame_system::Config>::AccountId>, ConstU32<MAX_WINNERS>>;
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config:
-		CreateSignedTransaction<Call<Self>> + frame_system::Config + pallet_did::Config
-	{
+	pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		/// The manager origin.
 		type GiveawayOrigin: EnsureOrigin<Self::RuntimeOrigin, Success = Self::AccountId>;
+		/// The identity tiers a giveaway may require of its participants.
+		type Identity: VerifyIdentity<Self::AccountId>;
 
 		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
-		/// The currency trait.
-		type Currency: ReservableCurrency<Self::AccountId>;
+		/// The currency trait, the native asset of `Assets`.
+		type Currency: ReservableCurrency<Self::AccountId, Balance = Self::AssetBalance>;
 		/// Something that provides randomness in the runtime.
 		type Randomness: Randomness<Self::Hash, BlockNumberFor<Self>>;
 		#[pallet::constant]
@@ -83,6 +145,10 @@ pub mod pallet {
 
 		#[pallet::constant]
 		type MaxSet: Get<u32>;
+		/// How many blocks an offchain worker holds the lock on the results of a block after
+		/// submitting them, so they are not sent again while the transaction is pending.
+		#[pallet::constant]
+		type ResultLockBlocks: Get<u32>;
 		/// Identifier for the collection of NFT.
 		type NftCollectionId: Member + Parameter + MaxEncodedLen + Copy + Display;
 
@@ -93,7 +159,8 @@ pub mod pallet {
 				Self::AccountId,
 				ItemId = Self::NftId,
 				CollectionId = Self::NftCollectionId,
-			> + Transfer<Self::AccountId>;
+			> + InspectEnumerable<Self::AccountId>
+			+ Transfer<Self::AccountId>;
 
 		/// The type used to describe the amount of fractions converted into assets.
 		type AssetBalance: AssetBalance;
@@ -104,45 +171,278 @@ pub mod pallet {
 		type Assets: Create<Self::AccountId, AssetId = Self::AssetId, Balance = Self::AssetBalance>
 			+ Mutate<Self::AccountId, AssetId = Self::AssetId, Balance = Self::AssetBalance>
 			+ Inspect<Self::AccountId>;
+		/// The asset id that stands for `Currency` in token prizes. `Assets` should not use it.
+		#[pallet::constant]
+		type NativeAssetId: Get<Self::AssetId>;
+		/// The platform treasury entry fees are split with.
+		type Treasury: Get<Self::AccountId>;
+		/// The part of the entry fees split with the treasury that goes to it.
+		#[pallet::constant]
+		type TreasuryShare: Get<Percent>;
+		/// The bond reserved from randomness oracles while they are registered.
+		#[pallet::constant]
+		type OracleBond: Get<BalanceOf<Self>>;
+		/// How many randomness oracles may be registered, and how many applications may be
+		/// pending.
+		#[pallet::constant]
+		type MaxOracles: Get<u32>;
+		/// How many oracles must reveal their results for them to be combined.
+		#[pallet::constant]
+		type OracleThreshold: Get<u32>;
+		/// How many blocks after giveaways end oracles may commit to their results.
+		#[pallet::constant]
+		type CommitBlocks: Get<BlockNumberFor<Self>>;
+		/// How many blocks after the commit phase oracles may reveal their results.
+		#[pallet::constant]
+		type RevealBlocks: Get<BlockNumberFor<Self>>;
+		/// How many blocks after giveaways drawn by the oracles end they fall back to their
+		/// `RandomnessFallback` if no results came. Never before the reveal phase is over, and
+		/// shorter than `BlockHashCount`.
+		#[pallet::constant]
+		type RandomnessTimeout: Get<BlockNumberFor<Self>>;
+		/// How many giveaways drawn by the oracles are drawn in `on_initialize` of each block.
+		/// The rest are drawn in `on_idle` or the next blocks.
+		#[pallet::constant]
+		type MaxDrawsPerBlock: Get<u32>;
+		/// Weight information for the draws in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	pub type TokenInfoOf<T> = TokenInfo<<T as Config>::AssetId, BalanceOf<T>>;
+	pub type NftInfoOf<T> = NftInfo<<T as Config>::NftCollectionId, <T as Config>::NftId>;
+	pub type EntryFeeOf<T> = EntryFee<<T as Config>::AssetId, BalanceOf<T>>;
+	pub type WeightRuleOf<T> = WeightRule<<T as Config>::AssetId, BalanceOf<T>>;
+	pub type OracleInfoOf<T> = OracleInfo<BalanceOf<T>, BlockNumberFor<T>>;
+	pub type EntryRequirementOf<T> = EntryRequirement<
+		BlockNumberFor<T>,
+		BalanceOf<T>,
+		<T as Config>::AssetId,
+		<T as Config>::NftCollectionId,
+	>;
+	pub type EntryRequirementsOf<T> = BoundedVec<EntryRequirementOf<T>, ConstU32<MAX_REQUIREMENTS>>;
+	pub type GiveawayConfigOf<T> = GiveawayConfig<
+		BlockNumberFor<T>,
+		BalanceOf<T>,
+		<T as frame_system::Config>::AccountId,
+		<T as Config>::AssetId,
+		<T as Config>::NftCollectionId,
+		<T as Config>::NftId,
+	>;
+
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+	#[derive(Default)]
+	pub enum RandomType {
+		/// Drawn from the results the oracles commit to and reveal, see `commit_block_result`.
+		#[default]
+		Chainlink,
+		/// Drawn from `T::Randomness` in the first block after the giveaway ends.
+		LocalChain,
+		/// Drawn from `T::Randomness` once it returns an output that was not yet known when the
+		/// giveaway ended, as VRF sources like BABE do.
+		Vrf,
+	}
+
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+	#[derive(Default)]
+	pub enum AssetType {
+		/// `token` is escrowed and paid out.
+		#[default]
+		FungibleToken,
+		/// The `nft` item is escrowed and paid out.
+		NonFungibleToken,
 	}
 
+	/// How the prize of a giveaway is split among its winners.
 	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
-	pub enum KYCStatus {
-		Tier0,
-		Tier1,
+	pub enum PrizeSplit {
+		/// This many winners share the prize equally.
+		Equal(u32),
+		/// One winner per share, in the order they are drawn. The shares add up to 100%.
+		Tiered(PrizeShares),
 	}
 
-	impl Default for KYCStatus {
+	impl Default for PrizeSplit {
 		fn default() -> Self {
-			KYCStatus::Tier0
+			PrizeSplit::Equal(1)
+		}
+	}
+
+	impl PrizeSplit {
+		pub fn winners(&self) -> u32 {
+			match self {
+				PrizeSplit::Equal(winners) => *winners,
+				PrizeSplit::Tiered(shares) => shares.len() as u32,
+			}
+		}
+
+		pub fn is_valid(&self) -> bool {
+			match self {
+				PrizeSplit::Equal(winners) => (1..=MAX_WINNERS).contains(winners),
+				PrizeSplit::Tiered(shares) => {
+					!shares.is_empty()
+						&& shares.iter().all(|share| !share.is_zero())
+						&& shares
+							.iter()
+							.map(|share| share.deconstruct() as u32)
+							.sum::<u32>() == 100
+				}
+			}
 		}
 	}
 
+	/// Where the entry fees of a giveaway go once its winners are drawn.
 	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
-	pub enum RandomType {
-		// LocalChain,
-		// Babe,
-		Chainlink,
+	pub enum FeeDestination {
+		/// Added to the token prize, which must be of the fee token.
+		Prize,
+		/// Paid to the creator.
+		Creator,
+		/// `T::TreasuryShare` paid to `T::Treasury`, the rest to the creator.
+		CreatorAndTreasury,
 	}
 
-	impl Default for RandomType {
-		fn default() -> Self {
-			RandomType::Chainlink
-		}
+	/// What participants pay to join a giveaway. Fees are refunded if it is cancelled.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+	pub struct EntryFee<AssetId, Balance> {
+		pub token: TokenInfo<AssetId, Balance>,
+		pub destination: FeeDestination,
 	}
 
+	/// How many entries each participant of a giveaway gets. Winners are drawn in proportion to
+	/// their entries.
 	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
-	pub enum AssetType {
-		FungibleToken,
-		// NonFungibleToken,
+	#[derive(Default)]
+	pub enum WeightRule<AssetId, Balance> {
+		/// One entry each.
+		#[default]
+		Flat,
+		/// One entry per `unit` of the token the participant can spare when joining. The tokens
+		/// counted are staked in the escrow until the giveaway is drawn or refunded. At most
+		/// `max` entries.
+		Holdings {
+			asset_id: AssetId,
+			unit: Balance,
+			max: u32,
+		},
+		/// One entry, plus the extra entries bought when joining at the entry fee each. At most
+		/// `max` entries.
+		Purchased { max: u32 },
+		/// One entry, plus one per identity tier reached when joining.
+		Tier,
 	}
 
-	impl Default for AssetType {
-		fn default() -> Self {
-			AssetType::FungibleToken
+	/// What happens to a giveaway drawn by the oracles when no results came before
+	/// `RandomnessTimeout`. Other giveaways never wait for randomness.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+	#[derive(Default)]
+	pub enum RandomnessFallback {
+		/// Draw it from the local randomness instead.
+		LocalRandomness,
+		/// Return the prize to the creator and the entry fees to the participants.
+		#[default]
+		Refund,
+	}
+
+	/// A bucket of giveaways queued in `DrawQueue`, by the block it belongs to.
+	#[derive(
+		Encode,
+		Decode,
+		Clone,
+		Copy,
+		PartialEq,
+		Eq,
+		Debug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
+	pub enum Draw<BlockNumber> {
+		/// The giveaways of `BlockToGiveaway`, drawn from the output of the oracle round.
+		Round(BlockNumber),
+		/// The giveaways of `LocalDraws`, drawn from `T::Randomness`.
+		Local(BlockNumber),
+		/// The giveaways of `BlockToGiveaway` the oracles gave no output for in time, which get
+		/// their `RandomnessFallback`.
+		TimedOut(BlockNumber),
+	}
+
+	/// Something a participant must satisfy to join a giveaway.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+	pub enum EntryRequirement<BlockNumber, Balance, AssetId, NftCollectionId> {
+		/// The `pallet_did` list lets the participant through: members of an allowlist, and
+		/// everyone missing from a denylist.
+		InList(ListName),
+		/// The participant is not a member of the `pallet_did` list, whatever its kind.
+		NotInList(ListName),
+		/// The participant holds at least `amount` of the token.
+		MinBalance(TokenInfo<AssetId, Balance>),
+		/// The participant holds an item of the collection.
+		HoldsNft(NftCollectionId),
+		/// The account of the participant is at least this many blocks old.
+		MinAccountAge(BlockNumber),
+	}
+
+	/// Where a giveaway is in its life. `Scheduled` turns `Open` at the start block and
+	/// `AwaitingRandomness` after the end block without being stored, see
+	/// [`Pallet::giveaway_status`].
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+	#[derive(Default)]
+	pub enum GiveawayStatus {
+		/// Created, not started yet.
+		#[default]
+		Scheduled,
+		/// Taking participants.
+		Open,
+		/// Ended, waiting for the randomness its winners are drawn with.
+		AwaitingRandomness,
+		/// Winners drawn, some prizes not claimed yet.
+		Drawn,
+		/// Every prize claimed.
+		Claimed,
+		/// Cancelled, the prize went back to the creator.
+		Cancelled,
+		/// Ended without winners to pay, the prize went back to the creator.
+		Expired,
+	}
+
+	impl GiveawayStatus {
+		/// Whether a giveaway may go from `self` to `next`.
+		pub fn can_become(&self, next: &GiveawayStatus) -> bool {
+			use GiveawayStatus::*;
+			matches!(
+				(self, next),
+				(Scheduled, Open)
+					| (Scheduled, Cancelled)
+					| (Open, AwaitingRandomness)
+					| (Open, Cancelled) | (AwaitingRandomness, Drawn)
+					| (AwaitingRandomness, Expired)
+					| (Drawn, Claimed)
+			)
 		}
 	}
 
+	/// A drawn winner of a giveaway, the creator for the prizes nobody won.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+	pub struct WinnerInfo<AccountId> {
+		pub who: AccountId,
+		pub claimed: bool,
+	}
+
+	/// A registered randomness oracle and the rounds it let down since governance last looked.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo, MaxEncodedLen)]
+	pub struct OracleInfo<Balance, BlockNumber> {
+		/// The reserved bond, slashable by `GiveawayOrigin` and lost in full when the oracle does
+		/// not reveal a result it committed to.
+		pub bond: Balance,
+		/// The key the oracle proves its results with.
+		pub key: VrfPublic,
+		/// The block the oracle registered in. It only takes part in rounds ending after it.
+		pub since: BlockNumber,
+		/// The rounds the oracle committed to that are not closed yet.
+		pub commitments: u32,
+		/// The rounds the oracle did not commit to.
+		pub missed: u32,
+	}
+
 	#[derive(
 		Encode,
 		Decode,
@@ -155,8 +455,8 @@ pub mod pallet {
 		MaxEncodedLen
 	)]
 	pub struct NftInfo<NftCollectionId, NftId> {
-		collection_id: NftCollectionId,
-		nft_id: NftId,
+		pub collection_id: NftCollectionId,
+		pub nft_id: NftId,
 	}
 
 	#[derive(
@@ -170,8 +470,9 @@ pub mod pallet {
 		TypeInfo,
 		MaxEncodedLen
 	)]
-	pub struct TokenInfo<Balance> {
-		pub asset_id: u32,
+	pub struct TokenInfo<AssetId, Balance> {
+		/// `T::NativeAssetId` for the native currency.
+		pub asset_id: AssetId,
 		pub amount: Balance,
 	}
 
@@ -185,19 +486,29 @@ pub mod pallet {
 		TypeInfo,
 		MaxEncodedLen
 	)]
-	pub struct GiveawayConfig<BlockNumber, Balance, AccountId> {
+	pub struct GiveawayConfig<BlockNumber, Balance, AccountId, AssetId, NftCollectionId, NftId> {
 		name: GiveawayName,
 		start: BlockNumber,
 		end: BlockNumber,
-		kyc: KYCStatus,
+		/// The block whose bucket in `BlockToGiveaway` or `LocalDraws` holds the giveaway, see
+		/// [`Pallet::schedule`].
+		draw_at: BlockNumber,
+		tier: Tier,
 		random_type: RandomType,
-		// pay_fee: bool,
-		// fee: Balance,
+		fee: Option<EntryFee<AssetId, Balance>>,
+		weight_rule: WeightRule<AssetId, Balance>,
+		fallback: RandomnessFallback,
 		creator: AccountId,
 		asset_type: AssetType,
-		token: Option<TokenInfo<Balance>>,
-		// nft: Option<NftInfo<NftCollectonId, NftId>>,
+		token: Option<TokenInfo<AssetId, Balance>>,
+		nft: Option<NftInfo<NftCollectionId, NftId>>,
+		prize_split: PrizeSplit,
 		max_join: u32,
+		status: GiveawayStatus,
+		requirements: BoundedVec<
+			EntryRequirement<BlockNumber, Balance, AssetId, NftCollectionId>,
+			ConstU32<MAX_REQUIREMENTS>,
+		>,
 	}
 
 	#[pallet::error]
@@ -214,25 +525,85 @@ pub mod pallet {
 		GiveawayEnded,
 		GiveawayNotStarted,
 		UserIsNotVerified,
+		UnknownTier,
+		/// The prize of the asset type is missing.
+		PrizeMissing,
+		/// The creator does not own the NFT to give away.
+		NotNftOwner,
+		/// The token to give away does not exist.
+		UnknownAsset,
+		/// The creator cannot spare the tokens to give away.
+		InsufficientBalance,
+		/// The prize split has no winners, too many, or shares that do not add up to 100%. NFTs
+		/// have a single winner.
+		InvalidPrizeSplit,
+		/// The caller did not win the giveaway.
+		NotWinner,
+		/// The caller did not create the giveaway.
+		NotCreator,
+		/// Open giveaways are only cancelled by `GiveawayOrigin`.
+		CancelNeedsApproval,
+		/// The caller does not meet an entry requirement of the giveaway.
+		RequirementNotMet,
+		/// The entry fee is zero, or goes to a prize of another token.
+		InvalidFee,
+		/// The weight rule allows no entries, or sells them without an entry fee.
+		InvalidWeightRule,
+		/// The caller would get no entries, or more than the weight rule allows.
+		InvalidEntries,
+		/// The caller already claimed their prizes.
+		AlreadyClaimed,
+		/// The giveaway cannot go from its status to the requested one.
+		InvalidStatus,
+		/// The caller is not a registered oracle.
+		NotOracle,
+		/// The caller is already a registered oracle.
+		AlreadyOracle,
+		/// `MaxOracles` oracles are registered.
+		TooManyOracles,
+		/// The caller already applied to be an oracle.
+		AlreadyApplied,
+		/// The account has no pending application to be an oracle.
+		NotApplied,
+		/// `MaxOracles` applications to be an oracle are pending.
+		TooManyApplications,
+		/// The oracle has commitments to reveal or strikes to be settled by `GiveawayOrigin`.
+		OracleBusy,
+		/// The commit phase of the block is not open.
+		NotCommitPhase,
+		/// The reveal phase of the block is not open.
+		NotRevealPhase,
+		/// The oracle already committed to results for the block.
+		AlreadyCommitted,
+		/// The oracle did not commit to results for the block.
+		NotCommitted,
+		/// The oracle already revealed its results for the block.
+		AlreadyRevealed,
+		/// The proof and salt do not match the commitment.
+		InvalidReveal,
+		/// The VRF key is not a compressed secp256k1 point.
+		InvalidVrfKey,
+		/// The VRF key is not the one the oracle registered.
+		VrfKeyMismatch,
+		/// The VRF proof is not valid for the key and the input of the block.
+		InvalidVrfProof,
+		/// The giveaway has no stalled refund.
+		RefundNotStalled,
 	}
 
 	#[pallet::storage]
 	pub type PalletManager<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, bool, ValueQuery>;
 
+	/// The winners of each drawn giveaway, in the order of the prize tiers.
 	#[pallet::storage]
-	pub type RoundWinner<T: Config> = StorageMap<_, Twox64Concat, u32, T::AccountId>;
+	pub type Winners<T: Config> = StorageMap<_, Twox64Concat, u32, WinnersOf<T>>;
 
 	#[pallet::storage]
 	pub type GiveawayIndex<T: Config> = StorageValue<_, u32, ValueQuery>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn give_away)]
-	pub type Giveaway<T: Config> = StorageMap<
-		_,
-		Twox64Concat,
-		u32,
-		GiveawayConfig<BlockNumberFor<T>, BalanceOf<T>, T::AccountId>,
-	>;
+	pub type Giveaway<T: Config> = StorageMap<_, Twox64Concat, u32, GiveawayConfigOf<T>>;
 
 	#[pallet::storage]
 	#[pallet::getter(fn participants)]
@@ -240,20 +611,111 @@ pub mod pallet {
 		StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, u32, T::AccountId>;
 
 	#[pallet::storage]
-	pub type TotalParticipantByGiveaway<T: Config> = StorageMap<_, Twox64Concat, u32, u32, ValueQuery>;
+	pub type TotalParticipantByGiveaway<T: Config> =
+		StorageMap<_, Twox64Concat, u32, u32, ValueQuery>;
 
 	#[pallet::storage]
-	pub type GiveawayToUser<T: Config> = StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, T::AccountId, bool, ValueQuery>;
+	pub type GiveawayToUser<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, T::AccountId, bool, ValueQuery>;
 
+	/// Giveaways drawn by the oracles, by the block whose round draws them. That is the block
+	/// they end in, or a later one if its bucket was full.
 	#[pallet::storage]
 	#[pallet::getter(fn get_giveaways_by_block)]
 	pub type BlockToGiveaway<T: Config> =
 		StorageMap<_, Twox64Concat, BlockNumberFor<T>, BoundedVec<u32, T::MaxSet>, ValueQuery>;
 
+	/// Giveaways drawn from `T::Randomness`, by the block they are drawn in.
+	#[pallet::storage]
+	pub type LocalDraws<T: Config> =
+		StorageMap<_, Twox64Concat, BlockNumberFor<T>, BoundedVec<u32, T::MaxSet>, ValueQuery>;
+
+	/// The entries of the participants of each weighted giveaway, summed up to and including
+	/// each position of `Participants`. Giveaways with `WeightRule::Flat` leave it empty.
+	#[pallet::storage]
+	pub type CumulativeEntries<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, u32, Twox64Concat, u32, u64, ValueQuery>;
+
+	/// The entry fees collected by each giveaway and not paid out yet.
+	#[pallet::storage]
+	pub type CollectedFees<T: Config> = StorageMap<_, Twox64Concat, u32, BalanceOf<T>, ValueQuery>;
+
+	/// The block accounts were created in. Accounts created before it was tracked are missing.
+	#[pallet::storage]
+	pub type AccountCreatedAt<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, BlockNumberFor<T>, OptionQuery>;
+
+	/// The registered randomness oracles.
+	#[pallet::storage]
+	pub type Oracles<T: Config> = CountedStorageMap<_, Twox64Concat, T::AccountId, OracleInfoOf<T>>;
+
+	/// The bond and key of the accounts waiting for `GiveawayOrigin` to approve them as
+	/// randomness oracles.
+	#[pallet::storage]
+	pub type OracleApplications<T: Config> =
+		CountedStorageMap<_, Twox64Concat, T::AccountId, (BalanceOf<T>, VrfPublic)>;
+
+	/// The commitments of the oracles to the results of each block, and whether they were
+	/// revealed.
+	#[pallet::storage]
+	pub type Commitments<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		Twox64Concat,
+		T::AccountId,
+		(T::Hash, bool),
+		OptionQuery,
+	>;
+
+	/// How many oracles revealed results for each block, and the XOR of their outputs.
+	#[pallet::storage]
+	pub type RoundReveals<T: Config> =
+		StorageMap<_, Twox64Concat, BlockNumberFor<T>, (u32, VrfOutput), ValueQuery>;
+
+	/// The combined output of the rounds enough oracles revealed results for. The results of
+	/// their giveaways are derived from it, see [`vrf::random_word`].
+	#[pallet::storage]
+	pub type RoundOutputs<T: Config> =
+		StorageMap<_, Twox64Concat, BlockNumberFor<T>, VrfOutput, OptionQuery>;
+
+	/// The buckets whose giveaways are being drawn, by their position in the queue, with the
+	/// next page of results.
+	#[pallet::storage]
+	pub type DrawQueue<T: Config> =
+		StorageMap<_, Twox64Concat, u32, (Draw<BlockNumberFor<T>>, u32), OptionQuery>;
+
+	/// The position of the first bucket in `DrawQueue`, and the one after the last.
+	#[pallet::storage]
+	pub type DrawQueueRange<T: Config> = StorageValue<_, (u32, u32), ValueQuery>;
+
+	/// The giveaways whose participants get their stakes and entry fees back, by their position
+	/// in the queue, with the position of the next participant to refund. Giveaways that timed out get
+	/// their prize back after the last participant.
+	#[pallet::storage]
+	pub type RefundQueue<T: Config> = StorageMap<_, Twox64Concat, u32, (u32, u32), OptionQuery>;
+
+	/// The position of the first giveaway in `RefundQueue`, and the one after the last.
+	#[pallet::storage]
+	pub type RefundQueueRange<T: Config> = StorageValue<_, (u32, u32), ValueQuery>;
+
+	/// The refunds that failed and were rolled back, with the position of the next participant
+	/// to refund. Anyone may queue them again with `retry_refund`.
+	#[pallet::storage]
+	pub type StalledRefunds<T: Config> = StorageMap<_, Twox64Concat, u32, u32, OptionQuery>;
+
+	/// The results each page of a round drew its giveaways with.
 	#[pallet::storage]
 	#[pallet::getter(fn get_results_by_block)]
-	pub type BlockToResults<T: Config> =
-		StorageMap<_, Twox64Concat, BlockNumberFor<T>, (RequestId, Results), OptionQuery>;
+	pub type BlockToResults<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		BlockNumberFor<T>,
+		Twox64Concat,
+		u32,
+		(RequestId, Results),
+		OptionQuery,
+	>;
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -266,7 +728,7 @@ pub mod pallet {
 			who: T::AccountId,
 			status: bool,
 			request_id: RequestId,
-			result: RandomResult
+			result: RandomResult,
 		},
 		Participated {
 			index: u32,
@@ -274,54 +736,188 @@ pub mod pallet {
 		},
 		Results {
 			block: BlockNumberFor<T>,
+			page: u32,
 			results: (RequestId, Results),
 		},
 		RewardClaimed {
 			index: u32,
 			winner: T::AccountId,
 		},
+		StatusChanged {
+			index: u32,
+			status: GiveawayStatus,
+		},
+		GiveawayEdited {
+			index: u32,
+		},
+		OracleApplied {
+			who: T::AccountId,
+		},
+		OracleRejected {
+			who: T::AccountId,
+		},
+		OracleRegistered {
+			who: T::AccountId,
+		},
+		OracleUnregistered {
+			who: T::AccountId,
+		},
+		OracleSlashed {
+			who: T::AccountId,
+			amount: BalanceOf<T>,
+		},
+		ResultCommitted {
+			block: BlockNumberFor<T>,
+			who: T::AccountId,
+		},
+		ResultRevealed {
+			block: BlockNumberFor<T>,
+			who: T::AccountId,
+			output: VrfOutput,
+		},
+		/// Too few oracles revealed results for the giveaways ending at `block`.
+		OracleRoundFailed {
+			block: BlockNumberFor<T>,
+			reveals: u32,
+		},
+		/// No results came for giveaway `index` in time, so it fell back to `fallback`.
+		RandomnessTimedOut {
+			index: u32,
+			fallback: RandomnessFallback,
+		},
+		/// Refunding giveaway `index` failed with `error` and waits for `retry_refund`.
+		RefundStalled {
+			index: u32,
+			error: DispatchError,
+		},
 	}
 
-	// #[pallet::hooks]
-	// impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-	// fn offchain_worker(block_number: BlockNumberFor<T>) {
-	// 	let signer = Signer::<T, T::AuthorityId>::all_accounts();
-	// 	// The entry point of your code called by offchain worker
-	// }
-
-	// fn on_initialize(n: BlockNumberFor<T>) -> Weight {
-	// let giveaways = BlockToGiveaway::<T>::get(n);
-	// for giveaway_index in giveaways.iter() {
-	// 	let giveaway = Giveaway::<T>::get(giveaway_index);
-	// 	let participants = Participants::<T>::get(giveaway_index);
-	// 	let number: usize = Self::random_number(
-	// 		giveaway_index.clone(),
-	// 		participants.len().try_into().unwrap(),
-	// 	)
-	// 	.try_into()
-	// 	.unwrap();
-	// 	Self::deposit_event(Event::<T>::Winner {
-	// 		index: *giveaway_index,
-	// 		who: participants.into_iter().nth(number).unwrap(),
-	// 	});
-	// }
-	// T::DbWeight::get().reads(2)
-	// }
-	// }
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			let mut weight = T::DbWeight::get().reads(4);
+			if LocalDraws::<T>::contains_key(n) {
+				Self::queue_draw(Draw::Local(n));
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 2));
+			}
+			// The rounds of the oracles close in the block after their reveal phase.
+			let round =
+				n.checked_sub(&Self::reveal_closes(Zero::zero()).saturating_add(1u32.into()));
+			if let Some(round) = round.filter(|round| BlockToGiveaway::<T>::contains_key(round)) {
+				Self::close_round(round);
+				let oracles = T::MaxOracles::get() as u64;
+				weight = weight.saturating_add(
+					T::DbWeight::get().reads_writes(3 + oracles * 4, 5 + oracles * 3),
+				);
+			}
+			// Giveaways still awaiting randomness after the timeout fall back.
+			let round = n.checked_sub(&Self::randomness_timeout());
+			let timed_out = |round: &BlockNumberFor<T>| {
+				BlockToGiveaway::<T>::contains_key(round) && !RoundOutputs::<T>::contains_key(round)
+			};
+			if let Some(round) = round.filter(timed_out) {
+				Self::queue_draw(Draw::TimedOut(round));
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 2));
+			}
+			// The queued giveaways are drawn `MaxDrawsPerBlock` at a time, the rest in later
+			// blocks.
+			let mut budget = T::MaxDrawsPerBlock::get();
+			while budget > 0 {
+				let Some((drawn, used)) = Self::draw_page(budget) else { break };
+				budget = budget.saturating_sub(drawn);
+				weight = weight.saturating_add(used);
+			}
+			// Refunds share what is left of the budget, the rest are paid in later blocks.
+			while budget > 0 {
+				let Some(refunded) = Self::refund_page(budget) else { break };
+				budget = budget.saturating_sub(refunded);
+				weight = weight.saturating_add(Self::refund_page_weight(refunded));
+			}
+			weight
+		}
+
+		/// Draw more pages of the buckets in `DrawQueue`, then refund more pages of the
+		/// giveaways in `RefundQueue`, with the weight left in the block.
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let mut weight = T::DbWeight::get().reads(2);
+			loop {
+				// As many giveaways as fit, should they all have the most participants.
+				let limit = Self::draws_within(remaining_weight.saturating_sub(weight));
+				if limit == 0 {
+					break;
+				}
+				let Some((_, used)) = Self::draw_page(limit) else { break };
+				weight = weight.saturating_add(used);
+			}
+			let page_weight = Self::refund_page_weight(RESULTS_PAGE.saturating_add(1));
+			while remaining_weight.all_gte(weight.saturating_add(page_weight)) {
+				let Some(refunded) = Self::refund_page(RESULTS_PAGE) else { break };
+				weight = weight.saturating_add(Self::refund_page_weight(refunded));
+			}
+			weight
+		}
+
+		/// [`Pallet::vrf_input`] reads the hash of the block giveaways end in, which is only
+		/// kept for `BlockHashCount` blocks. It must last until they time out.
+		fn integrity_test() {
+			assert!(
+				Self::randomness_timeout() < T::BlockHashCount::get(),
+				"`CommitBlocks`, `RevealBlocks` and `RandomnessTimeout` must end before the block \
+				 hash is pruned, raise `BlockHashCount` or shorten them"
+			);
+		}
+
+		/// Fetch VRF proofs for the giveaways that ended before `now` and commit to them with
+		/// `commit_block_result`, then reveal it with `reveal_block_result` once the commit
+		/// phase is over.
+		fn offchain_worker(now: BlockNumberFor<T>) {
+			let endpoint = Self::randomness_endpoint();
+			for block_number in BlockToGiveaway::<T>::iter_keys() {
+				if block_number >= now || RoundOutputs::<T>::contains_key(block_number) {
+					continue;
+				}
+				let submitted = if now < Self::reveal_opens(block_number) {
+					let Some(endpoint) = &endpoint else {
+						log::debug!(target: LOG_TARGET, "No randomness endpoint set, skipping");
+						continue
+					};
+					Self::commit_results(endpoint, block_number)
+				} else if now < Self::reveal_closes(block_number) {
+					Self::reveal_results(block_number)
+				} else {
+					continue;
+				};
+				if let Err(error) = submitted {
+					log::warn!(
+						target: LOG_TARGET,
+						"Results of block {:?} not submitted: {:?}",
+						block_number,
+						error,
+					);
+				}
+			}
+		}
+	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		#[pallet::call_index(0)]
-		#[pallet::weight((10_100, DispatchClass::Normal, Pays::No))]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
 		pub fn create_give_away(
 			origin: OriginFor<T>,
 			name: Vec<u8>,
 			start_block: BlockNumberFor<T>,
 			end_block: BlockNumberFor<T>,
-			kyc: KYCStatus,
+			tier: Tier,
 			random_type: RandomType,
 			asset_type: AssetType,
-			token: Option<TokenInfo<BalanceOf<T>>>,
+			token: Option<TokenInfoOf<T>>,
+			nft: Option<NftInfoOf<T>>,
+			prize_split: PrizeSplit,
+			requirements: EntryRequirementsOf<T>,
+			fee: Option<EntryFeeOf<T>>,
+			weight_rule: WeightRuleOf<T>,
+			fallback: RandomnessFallback,
 			max_join: u32,
 		) -> DispatchResult {
 			// Get user
@@ -330,10 +926,60 @@ pub mod pallet {
 			let block_number = frame_system::Pallet::<T>::block_number();
 			ensure!(block_number < start_block, Error::<T>::StartBlockInvalid);
 			ensure!(end_block > start_block, Error::<T>::EndBlockInvalid);
+			ensure!(tier <= T::Identity::max_tier(), Error::<T>::UnknownTier);
+			ensure!(
+				prize_split.is_valid()
+					&& (asset_type == AssetType::FungibleToken || prize_split.winners() == 1),
+				Error::<T>::InvalidPrizeSplit
+			);
+			for requirement in requirements.iter() {
+				if let EntryRequirement::MinBalance(token) = requirement {
+					ensure!(
+						token.asset_id == T::NativeAssetId::get()
+							|| T::Assets::asset_exists(token.asset_id.clone()),
+						Error::<T>::UnknownAsset
+					);
+				}
+			}
+			if let Some(fee) = &fee {
+				ensure!(!fee.token.amount.is_zero(), Error::<T>::InvalidFee);
+				ensure!(
+					fee.token.asset_id == T::NativeAssetId::get()
+						|| T::Assets::asset_exists(fee.token.asset_id.clone()),
+					Error::<T>::UnknownAsset
+				);
+				if fee.destination == FeeDestination::Prize {
+					ensure!(
+						asset_type == AssetType::FungibleToken
+							&& token.as_ref().map(|token| &token.asset_id)
+								== Some(&fee.token.asset_id),
+						Error::<T>::InvalidFee
+					);
+				}
+			}
+			match &weight_rule {
+				WeightRule::Flat | WeightRule::Tier => {}
+				WeightRule::Holdings {
+					asset_id,
+					unit,
+					max,
+				} => {
+					ensure!(!unit.is_zero() && *max > 0, Error::<T>::InvalidWeightRule);
+					ensure!(
+						*asset_id == T::NativeAssetId::get()
+							|| T::Assets::asset_exists(asset_id.clone()),
+						Error::<T>::UnknownAsset
+					);
+				}
+				WeightRule::Purchased { max } => {
+					ensure!(fee.is_some() && *max > 0, Error::<T>::InvalidWeightRule)
+				}
+			}
 			let name_bounded: GiveawayName = GiveawayName::defensive_truncate_from(name.clone());
 			let index = GiveawayIndex::<T>::get();
 			let next_index = index.saturating_add(1);
 			GiveawayIndex::<T>::put(next_index);
+			let draw_at = Self::schedule(index, &random_type, end_block)?;
 			// Attempt to update the lottery with the given kind
 			Giveaway::<T>::insert(
 				index,
@@ -341,31 +987,50 @@ pub mod pallet {
 					name: name_bounded,
 					start: start_block,
 					end: end_block,
-					kyc,
-					random_type,
-					// pay_fee,
-					// fee,
+					draw_at,
+					tier,
+					random_type: random_type.clone(),
+					fee,
+					weight_rule,
+					fallback,
 					creator: who.clone(),
 					asset_type: asset_type.clone(),
 					token: token.clone(),
-					// nft: nft.clone(),
+					nft: nft.clone(),
+					prize_split,
 					max_join,
+					status: GiveawayStatus::Scheduled,
+					requirements,
 				},
 			);
-			BlockToGiveaway::<T>::try_append(end_block, index).map_err(|_| Error::<T>::TooMany)?;
-			// Get the account for the lottery pot
-			let pallet_account = Self::account_id();
-
-			T::Currency::deposit_creating(&pallet_account, T::PotDeposit::get());
+			// Get the escrow account of the giveaway, the creator funds its existence.
+			let escrow_account = Self::escrow_account(index);
+			T::Currency::transfer(
+				&who,
+				&escrow_account,
+				T::PotDeposit::get(),
+				ExistenceRequirement::KeepAlive,
+			)?;
 
 			match asset_type {
-				// AssetType::NonFungibleToken => {
-				// 	let nft_info = nft.unwrap();
-				// 	Self::transfer_nft(nft_info.collection_id, nft_info.nft_id, &pallet_account)?;
-				// }
+				AssetType::NonFungibleToken => {
+					let nft_info = nft.ok_or(Error::<T>::PrizeMissing)?;
+					ensure!(
+						T::Nfts::owner(&nft_info.collection_id, &nft_info.nft_id).as_ref()
+							== Some(&who),
+						Error::<T>::NotNftOwner
+					);
+					Self::transfer_nft(nft_info.collection_id, nft_info.nft_id, &escrow_account)?;
+				}
 				AssetType::FungibleToken => {
-					let token_info = token.unwrap();
-					Self::transfer_asset(&who, &pallet_account, token_info.amount)?;
+					let token_info = token.ok_or(Error::<T>::PrizeMissing)?;
+					Self::ensure_can_escrow(&who, &token_info)?;
+					Self::transfer_asset(
+						&who,
+						&escrow_account,
+						&token_info,
+						Preservation::Preserve,
+					)?;
 				}
 			}
 			// Deposit an event to indicate that the lottery has started
@@ -377,131 +1042,1188 @@ pub mod pallet {
 		#[pallet::weight((10_100, DispatchClass::Normal))]
 		pub fn participate(origin: OriginFor<T>, index: u32) -> DispatchResult {
 			let who = ensure_signed(origin)?;
-			let giveaways = Giveaway::<T>::get(index).unwrap();
-			let current_block = frame_system::Pallet::<T>::block_number();
-			ensure!(giveaways.end >= current_block, Error::<T>::GiveawayEnded);
+			Self::join(who, index, 0)
+		}
+
+		#[pallet::call_index(3)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn claim_reward(origin: OriginFor<T>, round: u32) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut giveaway = Giveaway::<T>::get(round).ok_or(Error::<T>::InvalidRound)?;
+			let mut winners = Winners::<T>::get(round).ok_or(Error::<T>::InvalidRound)?;
+			ensure!(
+				winners.iter().any(|winner| winner.who == who),
+				Error::<T>::NotWinner
+			);
+			ensure!(
+				giveaway.status == GiveawayStatus::Drawn,
+				Error::<T>::AlreadyClaimed
+			);
+			let escrow_account = Self::escrow_account(round);
+			let mut claimed = false;
+			for (rank, winner) in winners.iter_mut().enumerate() {
+				if winner.who != who || winner.claimed {
+					continue;
+				}
+				match giveaway.asset_type {
+					AssetType::NonFungibleToken => {
+						let nft_info = giveaway.nft.clone().ok_or(Error::<T>::PrizeMissing)?;
+						Self::transfer_nft(nft_info.collection_id, nft_info.nft_id, &who)?;
+					}
+					AssetType::FungibleToken => {
+						let mut token_info =
+							giveaway.token.clone().ok_or(Error::<T>::PrizeMissing)?;
+						let prize = match &giveaway.fee {
+							Some(fee) if fee.destination == FeeDestination::Prize => token_info
+								.amount
+								.saturating_add(CollectedFees::<T>::get(round)),
+							_ => token_info.amount,
+						};
+						token_info.amount = Self::prize_amount(&giveaway.prize_split, prize, rank);
+						if !token_info.amount.is_zero() {
+							Self::transfer_asset(
+								&escrow_account,
+								&who,
+								&token_info,
+								Preservation::Expendable,
+							)?;
+						}
+					}
+				}
+				winner.claimed = true;
+				claimed = true;
+			}
+			ensure!(claimed, Error::<T>::AlreadyClaimed);
+			let all_claimed = winners.iter().all(|winner| winner.claimed);
+			Winners::<T>::insert(round, winners);
+			Self::deposit_event(Event::<T>::RewardClaimed {
+				index: round,
+				winner: who,
+			});
+			if all_claimed {
+				Self::update_status(round, &mut giveaway, GiveawayStatus::Claimed)?;
+			}
+			Ok(())
+		}
+
+		/// Cancel giveaway `index` and return its prize to the creator. The participants get their
+		/// stakes and entry fees back through `RefundQueue`. The creator or `GiveawayOrigin` cancel it before
+		/// it starts, only `GiveawayOrigin` while it is open.
+		#[pallet::call_index(4)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn cancel_give_away(origin: OriginFor<T>, index: u32) -> DispatchResult {
+			let mut giveaway = Giveaway::<T>::get(index).ok_or(Error::<T>::InvalidRound)?;
+			let approved = match T::GiveawayOrigin::try_origin(origin) {
+				Ok(_) => true,
+				Err(origin) => {
+					let who = ensure_signed(origin)?;
+					ensure!(who == giveaway.creator, Error::<T>::NotCreator);
+					false
+				}
+			};
+			match Self::status_of(&giveaway) {
+				GiveawayStatus::Scheduled => {}
+				GiveawayStatus::Open => ensure!(approved, Error::<T>::CancelNeedsApproval),
+				_ => return Err(Error::<T>::InvalidStatus.into()),
+			}
+			Self::unschedule(index, &giveaway);
+			Self::refund(index, &giveaway)?;
+			if Self::refunds_participants(&giveaway)
+				&& TotalParticipantByGiveaway::<T>::get(index) > 0
+			{
+				Self::queue_refund(index, 0);
+			}
+			Self::update_status(index, &mut giveaway, GiveawayStatus::Cancelled)
+		}
+
+		/// Change the name, end block or participant limit of giveaway `index` before it starts.
+		/// The limit cannot drop below the participants already counted.
+		#[pallet::call_index(5)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn edit_give_away(
+			origin: OriginFor<T>,
+			index: u32,
+			name: Option<Vec<u8>>,
+			end_block: Option<BlockNumberFor<T>>,
+			max_join: Option<u32>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut giveaway = Giveaway::<T>::get(index).ok_or(Error::<T>::InvalidRound)?;
+			ensure!(who == giveaway.creator, Error::<T>::NotCreator);
 			ensure!(
-				giveaways.start <= current_block,
-				Error::<T>::GiveawayNotStarted
+				Self::status_of(&giveaway) == GiveawayStatus::Scheduled,
+				Error::<T>::InvalidStatus
 			);
-			if giveaways.kyc == KYCStatus::Tier1 {
-				let provider: Provider =
-					Provider::defensive_truncate_from("Fractal".as_bytes().to_vec());
-				let value = pallet_did::ExternalIdAddress::<T>::get(&who, provider);
-				ensure!(value.len() > 0, Error::<T>::UserIsNotVerified);
-			}
-			
-				ensure!(!(GiveawayToUser::<T>::get(index, &who)) , Error::<T>::AlreadyJoined);
-				let total = TotalParticipantByGiveaway::<T>::get(index);
+			if let Some(name) = name {
+				giveaway.name = GiveawayName::defensive_truncate_from(name);
+			}
+			if let Some(end_block) = end_block {
+				ensure!(end_block > giveaway.start, Error::<T>::EndBlockInvalid);
+				Self::unschedule(index, &giveaway);
+				giveaway.draw_at = Self::schedule(index, &giveaway.random_type, end_block)?;
+				giveaway.end = end_block;
+			}
+			if let Some(max_join) = max_join {
 				ensure!(
-					total < giveaways.max_join,
+					max_join >= TotalParticipantByGiveaway::<T>::get(index),
 					Error::<T>::TooManyParticipants
 				);
-				GiveawayToUser::<T>::insert(index, &who, true);
-				Participants::<T>::insert(index, total, &who);
-				TotalParticipantByGiveaway::<T>::mutate(index, |value| {
-					*value = value.saturating_add(1);
-				});
-			// if giveaways.pay_fee {
-			// 	T::Currency::transfer(
-			// 		&who,
-			// 		&Self::account_id(),
-			// 		giveaways.fee,
-			// 		ExistenceRequirement::AllowDeath,
-			// 	)?;
-			// }
-			Self::deposit_event(Event::<T>::Participated { index, who });
+				giveaway.max_join = max_join;
+			}
+			Giveaway::<T>::insert(index, giveaway);
+			Self::deposit_event(Event::<T>::GiveawayEdited { index });
 			Ok(())
 		}
 
-		#[pallet::call_index(2)]
+		/// Join giveaway `index` with `extra_entries` bought at the entry fee each, when its
+		/// weight rule is `WeightRule::Purchased`.
+		#[pallet::call_index(6)]
 		#[pallet::weight((10_100, DispatchClass::Normal))]
-		pub fn set_block_result(
+		pub fn participate_with_entries(
 			origin: OriginFor<T>,
-			block_number: BlockNumberFor<T>,
-			request_id: Vec<u8>,
-			result: Vec<U256>,
+			index: u32,
+			extra_entries: u32,
 		) -> DispatchResult {
-			T::GiveawayOrigin::ensure_origin(origin)?;
+			let who = ensure_signed(origin)?;
+			Self::join(who, index, extra_entries)
+		}
+
+		/// Apply to be a randomness oracle proving its results with `key`, reserving
+		/// `OracleBond`. The caller only takes part in rounds once `GiveawayOrigin` approves it
+		/// with `approve_oracle`, so nobody fills the oracle set with accounts of their own.
+		#[pallet::call_index(7)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn register_oracle(origin: OriginFor<T>, key: VrfPublic) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(vrf::is_valid_key(&key), Error::<T>::InvalidVrfKey);
+			ensure!(!Oracles::<T>::contains_key(&who), Error::<T>::AlreadyOracle);
 			ensure!(
-				!<BlockToResults<T>>::contains_key(block_number),
-				Error::<T>::CannotSetResultAgain
+				!OracleApplications::<T>::contains_key(&who),
+				Error::<T>::AlreadyApplied
 			);
-			let current_block = frame_system::Pallet::<T>::block_number();
-			ensure!(block_number < current_block, Error::<T>::EndBlockInvalid);
-			let giveaways = BlockToGiveaway::<T>::get(block_number);
-			ensure!(giveaways.len() == result.len(), Error::<T>::InvalidResult);
-			let results_bounded: Results = Results::defensive_truncate_from(result);
-			let request_id_bounded: RequestId = RequestId::defensive_truncate_from(request_id);
-			BlockToResults::<T>::insert(block_number, (&request_id_bounded, &results_bounded));
-			for (giveaway, result_bounded) in giveaways.iter().zip(results_bounded.iter()) {
-				let participants_len = TotalParticipantByGiveaway::<T>::get(giveaway);
-				if participants_len != 0 {
-					let mut index: u32 = (result_bounded.low_u32() % participants_len )
-						.try_into()
-						.unwrap();
-					if index == 0 {
-						index = participants_len;
-					}
-					let winner = Participants::<T>::get(giveaway, index.saturating_sub(1)).unwrap();
-					RoundWinner::<T>::insert(giveaway, &winner);
-					Self::deposit_event(Event::<T>::Winner {
-						index: *giveaway,
-						who: winner,
-						status: true,
-						request_id: request_id_bounded.clone(),
-						result: *result_bounded
-					});
-				} else {
-					let giveaway_info = Giveaway::<T>::get(giveaway).unwrap();
-					RoundWinner::<T>::insert(giveaway, &giveaway_info.creator);
-					Self::deposit_event(Event::<T>::Winner {
-						index: *giveaway,
-						who: giveaway_info.creator,
-						status: false,
-						request_id: request_id_bounded.clone(),
-						result: *result_bounded
-					});
-				}
-			}
-			BlockToGiveaway::<T>::remove(block_number);
-			Self::deposit_event(Event::<T>::Results {
-				block: block_number,
-				results: (request_id_bounded, results_bounded),
-			});
+			ensure!(
+				OracleApplications::<T>::count() < T::MaxOracles::get(),
+				Error::<T>::TooManyApplications
+			);
+			let bond = T::OracleBond::get();
+			T::Currency::reserve(&who, bond)?;
+			OracleApplications::<T>::insert(&who, (bond, key));
+			Self::deposit_event(Event::<T>::OracleApplied { who });
 			Ok(())
 		}
 
-		#[pallet::call_index(3)]
+		/// Withdraw the application of the caller, or unregister it as a randomness oracle, and
+		/// release its bond. Oracles with strikes wait for `GiveawayOrigin` to settle them with
+		/// `slash_oracle`.
+		#[pallet::call_index(8)]
 		#[pallet::weight((10_100, DispatchClass::Normal))]
-		pub fn claim_reward(origin: OriginFor<T>, round: u32) -> DispatchResult {
-			_ = ensure_signed(origin)?;
-			let round_winner = RoundWinner::<T>::get(round);
-			let giveaway = Giveaway::<T>::get(round);
+		pub fn unregister_oracle(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			if let Some((bond, _)) = OracleApplications::<T>::take(&who) {
+				T::Currency::unreserve(&who, bond);
+				Self::deposit_event(Event::<T>::OracleUnregistered { who });
+				return Ok(());
+			}
+			let oracle = Oracles::<T>::get(&who).ok_or(Error::<T>::NotOracle)?;
 			ensure!(
-				(giveaway.is_some() && round_winner.is_some()),
-				Error::<T>::InvalidRound
+				oracle.commitments == 0 && oracle.missed == 0,
+				Error::<T>::OracleBusy
 			);
-			let giveaway = giveaway.unwrap();
-			let round_winner = round_winner.unwrap();
-			let pallet_account = Self::account_id();
-			match giveaway.asset_type {
-				// AssetType::NonFungibleToken => {
-				// 	let nft_info = giveaway.nft.unwrap();
-				// 	Self::transfer_nft(nft_info.collection_id, nft_info.nft_id, &round_winner)?;
-				// }
-				AssetType::FungibleToken => {
-					let token_info = giveaway.token.unwrap();
-					Self::transfer_asset(&pallet_account, &round_winner, token_info.amount)?;
-				}
-			}
-			Self::deposit_event(Event::<T>::RewardClaimed {
-				index: round,
-				winner: round_winner,
-			});
+			T::Currency::unreserve(&who, oracle.bond);
+			Oracles::<T>::remove(&who);
+			Self::deposit_event(Event::<T>::OracleUnregistered { who });
 			Ok(())
 		}
-	}
+
+		/// Remove oracle `who`, paying up to `amount` of its bond to `Treasury` and releasing
+		/// the rest.
+		#[pallet::call_index(9)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn slash_oracle(
+			origin: OriginFor<T>,
+			who: T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			T::GiveawayOrigin::ensure_origin(origin)?;
+			let oracle = Oracles::<T>::get(&who).ok_or(Error::<T>::NotOracle)?;
+			Self::remove_oracle(who, oracle.bond, amount);
+			Ok(())
+		}
+
+		/// Commit to the results of the giveaways ending at `block_number`, see
+		/// [`Pallet::commitment`]. Open for `CommitBlocks` blocks after they end.
+		#[pallet::call_index(10)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn commit_block_result(
+			origin: OriginFor<T>,
+			block_number: BlockNumberFor<T>,
+			commitment: T::Hash,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut oracle = Oracles::<T>::get(&who).ok_or(Error::<T>::NotOracle)?;
+			ensure!(
+				!RoundOutputs::<T>::contains_key(block_number),
+				Error::<T>::CannotSetResultAgain
+			);
+			ensure!(
+				BlockToGiveaway::<T>::contains_key(block_number),
+				Error::<T>::InvalidRound
+			);
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				block_number < now && now <= Self::reveal_opens(block_number),
+				Error::<T>::NotCommitPhase
+			);
+			ensure!(
+				!Commitments::<T>::contains_key(block_number, &who),
+				Error::<T>::AlreadyCommitted
+			);
+			Commitments::<T>::insert(block_number, &who, (commitment, false));
+			oracle.commitments = oracle.commitments.saturating_add(1);
+			Oracles::<T>::insert(&who, oracle);
+			Self::deposit_event(Event::<T>::ResultCommitted {
+				block: block_number,
+				who,
+			});
+			Ok(())
+		}
+
+		/// Reveal the VRF proof committed to for the giveaways ending at `block_number`. The
+		/// proof is checked against the key the oracle registered and [`Pallet::vrf_input`], and
+		/// its output is combined with the ones of the other oracles. Open for `RevealBlocks`
+		/// blocks after the commit phase.
+		#[pallet::call_index(11)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn reveal_block_result(
+			origin: OriginFor<T>,
+			block_number: BlockNumberFor<T>,
+			key: VrfPublic,
+			proof: VrfProof,
+			salt: [u8; 32],
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let oracle = Oracles::<T>::get(&who).ok_or(Error::<T>::NotOracle)?;
+			let (commitment, revealed) =
+				Commitments::<T>::get(block_number, &who).ok_or(Error::<T>::NotCommitted)?;
+			ensure!(!revealed, Error::<T>::AlreadyRevealed);
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				now > Self::reveal_opens(block_number) && now <= Self::reveal_closes(block_number),
+				Error::<T>::NotRevealPhase
+			);
+			ensure!(
+				Self::commitment(&who, block_number, &proof, &salt) == commitment,
+				Error::<T>::InvalidReveal
+			);
+			ensure!(key == oracle.key, Error::<T>::VrfKeyMismatch);
+			let output = vrf::verify(&key, &Self::vrf_input(block_number), &proof)
+				.ok_or(Error::<T>::InvalidVrfProof)?;
+			RoundReveals::<T>::mutate(block_number, |(reveals, combined)| {
+				for (combined, byte) in combined.iter_mut().zip(output) {
+					*combined ^= byte;
+				}
+				*reveals = reveals.saturating_add(1);
+			});
+			Commitments::<T>::insert(block_number, &who, (commitment, true));
+			Self::deposit_event(Event::<T>::ResultRevealed {
+				block: block_number,
+				who,
+				output,
+			});
+			Ok(())
+		}
+
+		/// Queue the stalled refund of giveaway `index` again, once whatever made it fail is
+		/// fixed. Open to anyone.
+		#[pallet::call_index(12)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn retry_refund(origin: OriginFor<T>, index: u32) -> DispatchResult {
+			ensure_signed(origin)?;
+			let position = StalledRefunds::<T>::take(index).ok_or(Error::<T>::RefundNotStalled)?;
+			Self::queue_refund(index, position);
+			Ok(())
+		}
+
+		/// Approve the application of `who` to be a randomness oracle. It takes part in the rounds
+		/// ending after this block.
+		#[pallet::call_index(13)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn approve_oracle(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::GiveawayOrigin::ensure_origin(origin)?;
+			ensure!(
+				Oracles::<T>::count() < T::MaxOracles::get(),
+				Error::<T>::TooManyOracles
+			);
+			let (bond, key) = OracleApplications::<T>::take(&who).ok_or(Error::<T>::NotApplied)?;
+			let oracle = OracleInfo {
+				bond,
+				key,
+				since: frame_system::Pallet::<T>::block_number(),
+				commitments: 0,
+				missed: 0,
+			};
+			Oracles::<T>::insert(&who, oracle);
+			Self::deposit_event(Event::<T>::OracleRegistered { who });
+			Ok(())
+		}
+
+		/// Reject the application of `who` to be a randomness oracle and release its bond.
+		#[pallet::call_index(14)]
+		#[pallet::weight((10_100, DispatchClass::Normal))]
+		pub fn reject_oracle(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::GiveawayOrigin::ensure_origin(origin)?;
+			let (bond, _) = OracleApplications::<T>::take(&who).ok_or(Error::<T>::NotApplied)?;
+			T::Currency::unreserve(&who, bond);
+			Self::deposit_event(Event::<T>::OracleRejected { who });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Queue giveaway `index`, ending at `end`, to be drawn. If the bucket of the block it is
+		/// due in is full, it goes to the first later one with room, see [`MAX_DEFER_BLOCKS`].
+		/// Returns the block it went to.
+		fn schedule(
+			index: u32,
+			random_type: &RandomType,
+			end: BlockNumberFor<T>,
+		) -> Result<BlockNumberFor<T>, DispatchError> {
+			let scheduled = match random_type {
+				RandomType::Chainlink => Self::append_to_agenda(end, |block| {
+					BlockToGiveaway::<T>::try_append(block, index)
+				}),
+				RandomType::LocalChain | RandomType::Vrf => {
+					Self::append_to_agenda(end.saturating_add(1u32.into()), |block| {
+						LocalDraws::<T>::try_append(block, index)
+					})
+				}
+			};
+			scheduled.ok_or_else(|| Error::<T>::TooMany.into())
+		}
+
+		/// Take giveaway `index` out of the bucket it is drawn from.
+		fn unschedule(index: u32, giveaway: &GiveawayConfigOf<T>) {
+			let remove = |giveaways: &mut Option<BoundedVec<u32, T::MaxSet>>| {
+				let Some(queued) = giveaways else { return false };
+				let Some(position) = queued.iter().position(|giveaway| *giveaway == index) else {
+					return false
+				};
+				queued.remove(position);
+				if queued.is_empty() {
+					*giveaways = None;
+				}
+				true
+			};
+			let removed = match giveaway.random_type {
+				RandomType::Chainlink => {
+					BlockToGiveaway::<T>::mutate_exists(giveaway.draw_at, remove)
+				}
+				RandomType::LocalChain | RandomType::Vrf => {
+					LocalDraws::<T>::mutate_exists(giveaway.draw_at, remove)
+				}
+			};
+			if !removed {
+				log::warn!(target: LOG_TARGET, "Giveaway {} was not scheduled", index);
+			}
+		}
+
+		/// The block whose bucket `append` took a draw into, `due` or, if it is full, one of the
+		/// later blocks.
+		fn append_to_agenda(
+			due: BlockNumberFor<T>,
+			append: impl Fn(BlockNumberFor<T>) -> Result<(), ()>,
+		) -> Option<BlockNumberFor<T>> {
+			(0..MAX_DEFER_BLOCKS)
+				.map(|offset| due.saturating_add(offset.into()))
+				.find(|block| append(*block).is_ok())
+		}
+
+		/// Send the escrowed prize of giveaway `index` back to its creator.
+		fn refund(index: u32, giveaway: &GiveawayConfigOf<T>) -> DispatchResult {
+			match giveaway.asset_type {
+				AssetType::NonFungibleToken => {
+					let nft_info = giveaway.nft.as_ref().ok_or(Error::<T>::PrizeMissing)?;
+					Self::transfer_nft(nft_info.collection_id, nft_info.nft_id, &giveaway.creator)
+				}
+				AssetType::FungibleToken => {
+					let token_info = giveaway.token.as_ref().ok_or(Error::<T>::PrizeMissing)?;
+					Self::transfer_asset(
+						&Self::escrow_account(index),
+						&giveaway.creator,
+						token_info,
+						Preservation::Expendable,
+					)
+				}
+			}
+		}
+
+		/// Join giveaway `index` as `who`, buying `extra_entries` if its weight rule sells them.
+		fn join(who: T::AccountId, index: u32, extra_entries: u32) -> DispatchResult {
+			let giveaways = Giveaway::<T>::get(index).ok_or(Error::<T>::InvalidRound)?;
+			match Self::status_of(&giveaways) {
+				GiveawayStatus::Open => {}
+				GiveawayStatus::Scheduled => return Err(Error::<T>::GiveawayNotStarted.into()),
+				_ => return Err(Error::<T>::GiveawayEnded.into()),
+			}
+			ensure!(
+				T::Identity::meets_tier(&who, giveaways.tier),
+				Error::<T>::UserIsNotVerified
+			);
+			ensure!(
+				giveaways
+					.requirements
+					.iter()
+					.all(|requirement| Self::meets(&who, requirement)),
+				Error::<T>::RequirementNotMet
+			);
+
+			ensure!(
+				!(GiveawayToUser::<T>::get(index, &who)),
+				Error::<T>::AlreadyJoined
+			);
+			let total = TotalParticipantByGiveaway::<T>::get(index);
+			ensure!(total < giveaways.max_join, Error::<T>::TooManyParticipants);
+			if let Some(fee) = &giveaways.fee {
+				// Bought entries are paid for like the first one.
+				let paid = match giveaways.weight_rule {
+					WeightRule::Purchased { .. } => extra_entries.saturating_add(1),
+					_ => 1,
+				};
+				let token = TokenInfo {
+					asset_id: fee.token.asset_id.clone(),
+					amount: fee.token.amount.saturating_mul(BalanceOf::<T>::from(paid)),
+				};
+				Self::ensure_can_escrow(&who, &token)?;
+				Self::transfer_asset(
+					&who,
+					&Self::escrow_account(index),
+					&token,
+					Preservation::Preserve,
+				)?;
+				CollectedFees::<T>::mutate(index, |fees| {
+					*fees = fees.saturating_add(token.amount);
+				});
+			}
+			// Holdings are counted after the entry fee is paid, and staked so they count once.
+			let entries = Self::entries_of(&who, &giveaways.weight_rule, extra_entries)?;
+			if let Some(stake) = Self::stake_of(&giveaways.weight_rule, entries.into()) {
+				Self::transfer_asset(
+					&who,
+					&Self::escrow_account(index),
+					&stake,
+					Preservation::Preserve,
+				)?;
+			}
+			if giveaways.weight_rule != WeightRule::Flat {
+				let before = match total {
+					0 => 0,
+					_ => CumulativeEntries::<T>::get(index, total - 1),
+				};
+				CumulativeEntries::<T>::insert(index, total, before.saturating_add(entries as u64));
+			}
+			GiveawayToUser::<T>::insert(index, &who, true);
+			Participants::<T>::insert(index, total, &who);
+			TotalParticipantByGiveaway::<T>::mutate(index, |value| {
+				*value = value.saturating_add(1);
+			});
+			Self::deposit_event(Event::<T>::Participated { index, who });
+			Ok(())
+		}
+
+		/// The entries `who` gets by joining a giveaway with `weight_rule` and buying
+		/// `extra_entries`.
+		fn entries_of(
+			who: &T::AccountId,
+			weight_rule: &WeightRuleOf<T>,
+			extra_entries: u32,
+		) -> Result<u32, DispatchError> {
+			let entries = match weight_rule {
+				WeightRule::Purchased { max } => {
+					let entries = extra_entries.saturating_add(1);
+					ensure!(entries <= *max, Error::<T>::InvalidEntries);
+					entries
+				}
+				_ if extra_entries > 0 => return Err(Error::<T>::InvalidEntries.into()),
+				WeightRule::Flat => 1,
+				WeightRule::Tier => u32::from(T::Identity::tier(who)).saturating_add(1),
+				WeightRule::Holdings {
+					asset_id,
+					unit,
+					max,
+				} => {
+					let held = Self::spendable_balance(who, asset_id);
+					let entries: u32 = (held / *unit).unique_saturated_into();
+					entries.min(*max)
+				}
+			};
+			ensure!(entries > 0, Error::<T>::InvalidEntries);
+			Ok(entries)
+		}
+
+		/// The tokens staked for `entries` entries of a giveaway with `weight_rule`, if it
+		/// stakes any.
+		fn stake_of(weight_rule: &WeightRuleOf<T>, entries: u64) -> Option<TokenInfoOf<T>> {
+			match weight_rule {
+				WeightRule::Holdings { asset_id, unit, .. } => Some(TokenInfo {
+					asset_id: asset_id.clone(),
+					amount: unit.saturating_mul(entries.unique_saturated_into()),
+				}),
+				_ => None,
+			}
+		}
+
+		/// Whether the participants of `giveaway` get anything back when it is cancelled or
+		/// refunded, an entry fee or a stake.
+		fn refunds_participants(giveaway: &GiveawayConfigOf<T>) -> bool {
+			giveaway.fee.is_some() || Self::stake_of(&giveaway.weight_rule, 0).is_some()
+		}
+
+		/// Whether `giveaway` was drawn, so its entry fees are paid out rather than refunded.
+		fn is_drawn(giveaway: &GiveawayConfigOf<T>) -> bool {
+			matches!(
+				giveaway.status,
+				GiveawayStatus::Drawn | GiveawayStatus::Claimed
+			)
+		}
+
+		/// The entries of the participant at `position` of giveaway `index`.
+		pub(crate) fn entries_at(index: u32, position: u32) -> u64 {
+			let before = match position {
+				0 => 0,
+				_ => CumulativeEntries::<T>::get(index, position - 1),
+			};
+			CumulativeEntries::<T>::get(index, position).saturating_sub(before)
+		}
+
+		/// Give the participants at `positions` of giveaway `index` their stake back, and their
+		/// entry fee unless the giveaway was drawn.
+		fn refund_participants(
+			index: u32,
+			giveaway: &GiveawayConfigOf<T>,
+			positions: Range<u32>,
+		) -> DispatchResult {
+			let fee = giveaway.fee.as_ref().filter(|_| !Self::is_drawn(giveaway));
+			let escrow_account = Self::escrow_account(index);
+			for position in positions {
+				let Some(who) = Participants::<T>::get(index, position) else { continue };
+				let entries = Self::entries_at(index, position);
+				if let Some(fee) = fee {
+					let paid = match giveaway.weight_rule {
+						WeightRule::Purchased { .. } => entries,
+						_ => 1,
+					};
+					let token = TokenInfo {
+						asset_id: fee.token.asset_id.clone(),
+						amount: fee
+							.token
+							.amount
+							.saturating_mul(paid.unique_saturated_into()),
+					};
+					Self::transfer_asset(&escrow_account, &who, &token, Preservation::Expendable)?;
+				}
+				if let Some(stake) = Self::stake_of(&giveaway.weight_rule, entries) {
+					Self::transfer_asset(&escrow_account, &who, &stake, Preservation::Expendable)?;
+				}
+			}
+			Ok(())
+		}
+
+		/// Queue the participants of giveaway `index` from `position` on to get their stakes and
+		/// entry fees back, and its prize to go back to the creator if it is still awaiting
+		/// randomness.
+		fn queue_refund(index: u32, position: u32) {
+			RefundQueueRange::<T>::mutate(|(_, end)| {
+				RefundQueue::<T>::insert(*end, (index, position));
+				*end = end.wrapping_add(1);
+			});
+		}
+
+		/// Refund the stakes and entry fees of at most `limit` participants of the first giveaway
+		/// in `RefundQueue`. After the last one, a giveaway still awaiting randomness gets its prize
+		/// back and expires. A page that fails is rolled back and moved to `StalledRefunds`.
+		/// Returns how many transfers were made, or `None` if no giveaway is queued.
+		pub(crate) fn refund_page(limit: u32) -> Option<u32> {
+			let (start, end) = RefundQueueRange::<T>::get();
+			if start == end {
+				return None;
+			}
+			let next = || RefundQueueRange::<T>::put((start.wrapping_add(1), end));
+			let Some((index, position)) = RefundQueue::<T>::take(start) else {
+				next();
+				return Some(0)
+			};
+			let Some(mut giveaway) = Giveaway::<T>::get(index) else {
+				next();
+				return Some(0)
+			};
+			let participants = TotalParticipantByGiveaway::<T>::get(index);
+			let last = participants.min(position.saturating_add(limit.min(RESULTS_PAGE)));
+			let done = last == participants;
+			let expires = done && Self::status_of(&giveaway) == GiveawayStatus::AwaitingRandomness;
+			let refunded = frame_support::storage::with_storage_layer(|| -> DispatchResult {
+				Self::refund_participants(index, &giveaway, position..last)?;
+				if done && !Self::is_drawn(&giveaway) {
+					CollectedFees::<T>::remove(index);
+				}
+				if expires {
+					Self::refund(index, &giveaway)?;
+					Self::update_status(index, &mut giveaway, GiveawayStatus::Expired)?;
+				}
+				Ok(())
+			});
+			match refunded {
+				Ok(()) if !done => RefundQueue::<T>::insert(start, (index, last)),
+				Ok(()) => next(),
+				Err(error) => {
+					log::warn!(
+						target: LOG_TARGET,
+						"Giveaway {} could not be refunded: {:?}",
+						index,
+						error
+					);
+					StalledRefunds::<T>::insert(index, position);
+					Self::deposit_event(Event::<T>::RefundStalled { index, error });
+					next();
+				}
+			}
+			Some(last.saturating_sub(position).saturating_add(expires as u32))
+		}
+
+		/// The weight of refunding a page of `transfers` participants and prizes, see
+		/// [`Self::refund_page`].
+		fn refund_page_weight(transfers: u32) -> Weight {
+			// A participant may get both an entry fee and a stake back.
+			let transfer = T::DbWeight::get().reads_writes(10, 6);
+			T::DbWeight::get()
+				.reads_writes(5, 4)
+				.saturating_add(transfer.saturating_mul(transfers.into()))
+		}
+
+		/// Pay the entry fees of drawn giveaway `index` to the creator and the treasury, unless
+		/// they go to the prize.
+		fn distribute_fees(index: u32, giveaway: &GiveawayConfigOf<T>) -> DispatchResult {
+			let Some(fee) = &giveaway.fee else { return Ok(()) };
+			let to_treasury = match fee.destination {
+				FeeDestination::Prize => return Ok(()),
+				FeeDestination::Creator => Percent::zero(),
+				FeeDestination::CreatorAndTreasury => T::TreasuryShare::get(),
+			};
+			let collected = CollectedFees::<T>::take(index);
+			let escrow_account = Self::escrow_account(index);
+			let treasury_fees = to_treasury.mul_floor(collected);
+			for (to, amount) in [
+				(T::Treasury::get(), treasury_fees),
+				(
+					giveaway.creator.clone(),
+					collected.saturating_sub(treasury_fees),
+				),
+			] {
+				if !amount.is_zero() {
+					let token = TokenInfo {
+						asset_id: fee.token.asset_id.clone(),
+						amount,
+					};
+					Self::transfer_asset(&escrow_account, &to, &token, Preservation::Expendable)?;
+				}
+			}
+			Ok(())
+		}
+
+		/// Draw the winners of giveaway `index` with `random` and record them. Prizes nobody won
+		/// go back to the creator, the whole prize right away when nobody joined.
+		pub(crate) fn settle(index: u32, random: RandomResult, request_id: &RequestId) {
+			let Some(mut giveaway) = Giveaway::<T>::get(index) else { return };
+			if Self::status_of(&giveaway) != GiveawayStatus::AwaitingRandomness {
+				log::warn!(target: LOG_TARGET, "Giveaway {} is not awaiting randomness", index);
+				return;
+			}
+			let prizes = giveaway.prize_split.winners();
+			let participants = TotalParticipantByGiveaway::<T>::get(index);
+			let drawn = match giveaway.weight_rule {
+				WeightRule::Flat => Self::draw_winners(index, random, participants, prizes),
+				_ => Self::draw_weighted_winners(index, random, participants, prizes),
+			};
+			let returned = drawn.is_empty()
+				&& Self::refund(index, &giveaway)
+					.map_err(|error| {
+						log::warn!(
+							target: LOG_TARGET,
+							"Prize of giveaway {} not returned: {:?}",
+							index,
+							error,
+						)
+					})
+					.is_ok();
+			let unclaimed = prizes.saturating_sub(drawn.len() as u32) as usize;
+			let winners = drawn
+				.into_iter()
+				.map(|who| (who, true))
+				.chain(sp_std::iter::repeat((giveaway.creator.clone(), false)).take(unclaimed))
+				.collect::<Vec<_>>();
+			if !returned {
+				let stored = winners
+					.iter()
+					.map(|(who, _)| WinnerInfo {
+						who: who.clone(),
+						claimed: false,
+					})
+					.collect::<Vec<_>>();
+				Winners::<T>::insert(index, WinnersOf::<T>::defensive_truncate_from(stored));
+			}
+			for (who, status) in winners {
+				Self::deposit_event(Event::<T>::Winner {
+					index,
+					who,
+					status,
+					request_id: request_id.clone(),
+					result: random,
+				});
+			}
+			if let Err(error) = Self::distribute_fees(index, &giveaway) {
+				log::warn!(
+					target: LOG_TARGET,
+					"Entry fees of giveaway {} not paid out: {:?}",
+					index,
+					error,
+				);
+			}
+			let status = if returned {
+				GiveawayStatus::Expired
+			} else {
+				GiveawayStatus::Drawn
+			};
+			// Cannot fail, the giveaway is awaiting randomness.
+			let _ = Self::update_status(index, &mut giveaway, status);
+			if participants > 0 && Self::stake_of(&giveaway.weight_rule, 0).is_some() {
+				Self::queue_refund(index, 0);
+			}
+		}
+
+		/// The last block of the commit phase of the giveaways ending at `round`.
+		pub(crate) fn reveal_opens(round: BlockNumberFor<T>) -> BlockNumberFor<T> {
+			round.saturating_add(T::CommitBlocks::get())
+		}
+
+		/// The last block of the reveal phase of the giveaways ending at `round`. They are
+		/// drawn in the block after.
+		pub(crate) fn reveal_closes(round: BlockNumberFor<T>) -> BlockNumberFor<T> {
+			Self::reveal_opens(round).saturating_add(T::RevealBlocks::get())
+		}
+
+		/// How many blocks after giveaways drawn by the oracles end they time out. At least one
+		/// block after they would be drawn, see [`Self::reveal_closes`].
+		pub(crate) fn randomness_timeout() -> BlockNumberFor<T> {
+			T::RandomnessTimeout::get()
+				.max(Self::reveal_closes(Zero::zero()).saturating_add(1u32.into()))
+		}
+
+		/// The commitment of oracle `who` to `proof` for the giveaways ending at `block_number`.
+		pub fn commitment(
+			who: &T::AccountId,
+			block_number: BlockNumberFor<T>,
+			proof: &VrfProof,
+			salt: &[u8; 32],
+		) -> T::Hash {
+			T::Hashing::hash_of(&(who, block_number, proof, salt))
+		}
+
+		/// The VRF input of the giveaways ending at `block_number`. It includes the hash of that
+		/// block, so oracles cannot know their outputs before the giveaways close.
+		pub fn vrf_input(block_number: BlockNumberFor<T>) -> Vec<u8> {
+			(
+				T::PalletId::get(),
+				block_number,
+				frame_system::Pallet::<T>::block_hash(block_number),
+			)
+				.encode()
+		}
+
+		/// Queue the giveaways of `round` to be drawn with the XOR of the revealed outputs, and
+		/// give a strike to the oracles that did not commit. The oracles that committed but did not
+		/// reveal lose their bond. With fewer than `OracleThreshold` reveals the giveaways keep
+		/// awaiting randomness.
+		pub(crate) fn close_round(round: BlockNumberFor<T>) {
+			let (reveals, output) = RoundReveals::<T>::take(round);
+			let oracles = Oracles::<T>::iter().collect::<Vec<_>>();
+			for (who, mut oracle) in oracles {
+				match Commitments::<T>::get(round, &who) {
+					// Withholding a committed result could bias the round, so it costs the bond.
+					Some((_, false)) => {
+						Self::remove_oracle(who, oracle.bond, oracle.bond);
+						continue;
+					}
+					Some((_, true)) => oracle.commitments = oracle.commitments.saturating_sub(1),
+					None if oracle.since <= round => {
+						oracle.missed = oracle.missed.saturating_add(1)
+					}
+					None => continue,
+				}
+				Oracles::<T>::insert(&who, oracle);
+			}
+			let _ = Commitments::<T>::clear_prefix(round, T::MaxOracles::get(), None);
+
+			if reveals < T::OracleThreshold::get().max(1) {
+				Self::deposit_event(Event::<T>::OracleRoundFailed {
+					block: round,
+					reveals,
+				});
+				return;
+			}
+			RoundOutputs::<T>::insert(round, output);
+			Self::queue_draw(Draw::Round(round));
+		}
+
+		/// Queue the giveaways of `draw` to be drawn a page at a time, see [`Self::draw_page`].
+		fn queue_draw(draw: Draw<BlockNumberFor<T>>) {
+			DrawQueueRange::<T>::mutate(|(_, end)| {
+				DrawQueue::<T>::insert(*end, (draw, 0));
+				*end = end.wrapping_add(1);
+			});
+		}
+
+		/// Remove oracle `who`, paying up to `amount` of its `bond` to `Treasury` and releasing
+		/// the rest.
+		fn remove_oracle(who: T::AccountId, bond: BalanceOf<T>, amount: BalanceOf<T>) {
+			let (slashed, _) = T::Currency::slash_reserved(&who, amount.min(bond));
+			let amount = slashed.peek();
+			T::Currency::resolve_creating(&T::Treasury::get(), slashed);
+			T::Currency::unreserve(&who, bond.saturating_sub(amount));
+			Oracles::<T>::remove(&who);
+			Self::deposit_event(Event::<T>::OracleSlashed { who, amount });
+		}
+
+		/// Draw the next page of at most `limit` giveaways of the first bucket in `DrawQueue`.
+		/// Pages of oracle rounds store the results they used. Returns how many giveaways were
+		/// drawn and the weight it took, or `None` if no bucket is queued.
+		pub(crate) fn draw_page(limit: u32) -> Option<(u32, Weight)> {
+			let (start, end) = DrawQueueRange::<T>::get();
+			if start == end {
+				return None;
+			}
+			let Some((draw, page)) = DrawQueue::<T>::get(start) else {
+				DrawQueueRange::<T>::put((start.wrapping_add(1), end));
+				return Some((0, Self::draw_page_weight(0)))
+			};
+			let (Draw::Round(block) | Draw::Local(block) | Draw::TimedOut(block)) = draw;
+			let mut queued = match draw {
+				Draw::Round(_) | Draw::TimedOut(_) => BlockToGiveaway::<T>::take(block),
+				Draw::Local(_) => LocalDraws::<T>::take(block),
+			}
+			.into_inner();
+			let size = queued.len().min(limit.min(RESULTS_PAGE) as usize);
+			let giveaways = queued.drain(..size).collect::<Vec<_>>();
+			if queued.is_empty() {
+				DrawQueue::<T>::remove(start);
+				DrawQueueRange::<T>::put((start.wrapping_add(1), end));
+			} else {
+				let queued = BoundedVec::defensive_truncate_from(queued);
+				match draw {
+					Draw::Round(_) | Draw::TimedOut(_) => {
+						BlockToGiveaway::<T>::insert(block, queued)
+					}
+					Draw::Local(_) => LocalDraws::<T>::insert(block, queued),
+				}
+				DrawQueue::<T>::insert(start, (draw, page.saturating_add(1)));
+			}
+			if giveaways.is_empty() {
+				return Some((0, Self::draw_page_weight(0)));
+			}
+			let giveaways = giveaways
+				.into_iter()
+				.filter(|index| Self::drawn_at(*index, block))
+				.collect::<Vec<_>>();
+			let weight = giveaways
+				.iter()
+				.map(|index| Self::settle_weight(TotalParticipantByGiveaway::<T>::get(index)))
+				.fold(Self::draw_page_weight(size as u32), Weight::saturating_add);
+			let now = frame_system::Pallet::<T>::block_number();
+			let round = match draw {
+				Draw::Round(round) => round,
+				Draw::Local(_) => {
+					giveaways
+						.iter()
+						.for_each(|index| Self::draw_locally(*index, now));
+					return Some((size as u32, weight));
+				}
+				Draw::TimedOut(_) => {
+					giveaways
+						.iter()
+						.for_each(|index| Self::time_out(*index, now));
+					return Some((size as u32, weight));
+				}
+			};
+
+			let output = RoundOutputs::<T>::get(round).unwrap_or_default();
+			let results = giveaways
+				.iter()
+				.map(|index| vrf::random_word(&output, *index))
+				.collect::<Vec<_>>();
+			let request_id = RequestId::default();
+			for (giveaway, result) in giveaways.iter().zip(results.iter()) {
+				Self::settle(*giveaway, *result, &request_id);
+			}
+			let results = Results::defensive_truncate_from(results);
+			BlockToResults::<T>::insert(round, page, (&request_id, &results));
+			Self::deposit_event(Event::<T>::Results {
+				block: round,
+				page,
+				results: (request_id, results),
+			});
+			Some((size as u32, weight))
+		}
+
+		/// Whether giveaway `index` is drawn from the bucket of block `block`.
+		fn drawn_at(index: u32, block: BlockNumberFor<T>) -> bool {
+			Giveaway::<T>::get(index).map(|giveaway| giveaway.draw_at) == Some(block)
+		}
+
+		/// The weight of settling a giveaway of `participants` participants, see
+		/// [`Self::settle`]. Weighted draws search the participants for each winner, so it grows
+		/// with the depth of the search.
+		fn settle_weight(participants: u32) -> Weight {
+			T::WeightInfo::settle(Self::search_depth(participants))
+		}
+
+		/// The depth of a binary search over `participants` positions.
+		pub(crate) fn search_depth(participants: u32) -> u32 {
+			u32::BITS - participants.leading_zeros()
+		}
+
+		/// The weight of drawing a page of `giveaways` giveaways, see [`Self::draw_page`],
+		/// besides settling them.
+		fn draw_page_weight(giveaways: u32) -> Weight {
+			// Each giveaway is read once more to check it is still drawn from its bucket and to
+			// count its participants, and local draws read the randomness too.
+			T::DbWeight::get()
+				.reads_writes(4, 4)
+				.saturating_add(T::DbWeight::get().reads(3).saturating_mul(giveaways.into()))
+		}
+
+		/// How many giveaways of the most participants a page drawn with `weight` can hold.
+		fn draws_within(weight: Weight) -> u32 {
+			let giveaway = Self::draw_page_weight(1)
+				.saturating_sub(Self::draw_page_weight(0))
+				.saturating_add(Self::settle_weight(u32::MAX));
+			let left = weight.saturating_sub(Self::draw_page_weight(0));
+			let times = |left: u64, giveaway: u64| left.checked_div(giveaway).unwrap_or(u64::MAX);
+			times(left.ref_time(), giveaway.ref_time())
+				.min(times(left.proof_size(), giveaway.proof_size()))
+				.unique_saturated_into()
+		}
+
+		/// Apply the fallback of giveaway `index`, which got no results in time.
+		fn time_out(index: u32, n: BlockNumberFor<T>) {
+			let Some(giveaway) = Giveaway::<T>::get(index) else { return };
+			Self::deposit_event(Event::<T>::RandomnessTimedOut {
+				index,
+				fallback: giveaway.fallback.clone(),
+			});
+			match giveaway.fallback {
+				RandomnessFallback::LocalRandomness => Self::draw_locally(index, n),
+				RandomnessFallback::Refund => Self::queue_refund(index, 0),
+			}
+		}
+
+		/// The status of `giveaway` in the current block.
+		pub(crate) fn status_of(giveaway: &GiveawayConfigOf<T>) -> GiveawayStatus {
+			let now = frame_system::Pallet::<T>::block_number();
+			match giveaway.status {
+				GiveawayStatus::Scheduled | GiveawayStatus::Open if now > giveaway.end => {
+					GiveawayStatus::AwaitingRandomness
+				}
+				GiveawayStatus::Scheduled if now >= giveaway.start => GiveawayStatus::Open,
+				ref status => status.clone(),
+			}
+		}
+
+		/// Move `giveaway` to `next`, if its current status allows it, and store it.
+		pub(crate) fn update_status(
+			index: u32,
+			giveaway: &mut GiveawayConfigOf<T>,
+			next: GiveawayStatus,
+		) -> DispatchResult {
+			ensure!(
+				Self::status_of(giveaway).can_become(&next),
+				Error::<T>::InvalidStatus
+			);
+			giveaway.status = next.clone();
+			Giveaway::<T>::insert(index, &*giveaway);
+			Self::deposit_event(Event::<T>::StatusChanged {
+				index,
+				status: next,
+			});
+			Ok(())
+		}
+
+		/// The status of giveaway `index` in the current block.
+		pub fn giveaway_status(index: u32) -> Option<GiveawayStatus> {
+			Giveaway::<T>::get(index).map(|giveaway| Self::status_of(&giveaway))
+		}
+
+		/// The entry requirements of giveaway `index`.
+		pub fn entry_requirements(index: u32) -> Option<Vec<EntryRequirementOf<T>>> {
+			Giveaway::<T>::get(index).map(|giveaway| giveaway.requirements.into_inner())
+		}
+
+		/// The entry requirements of giveaway `index` that `who` does not meet.
+		pub fn unmet_requirements(index: u32, who: &T::AccountId) -> Vec<EntryRequirementOf<T>> {
+			Self::entry_requirements(index)
+				.unwrap_or_default()
+				.into_iter()
+				.filter(|requirement| !Self::meets(who, requirement))
+				.collect()
+		}
+
+		/// Draw up to `count` of the `len` participants of giveaway `index` without replacement,
+		/// by a Fisher–Yates shuffle of the first `count` positions. Every draw takes a fresh
+		/// `U256`, `random` and then its successive hashes.
+		pub(crate) fn draw_winners(
+			index: u32,
+			random: RandomResult,
+			len: u32,
+			count: u32,
+		) -> Vec<T::AccountId> {
+			let count = count.min(len);
+			// The positions moved by the shuffle so far; the others hold their own index.
+			let mut moved = BTreeMap::<u32, u32>::new();
+			let mut value = random;
+			let mut winners = Vec::with_capacity(count as usize);
+			for position in 0..count {
+				let pick = position + Self::unbiased_index(value, len - position);
+				let drawn = moved.get(&pick).copied().unwrap_or(pick);
+				moved.insert(pick, moved.get(&position).copied().unwrap_or(position));
+				winners.extend(Participants::<T>::get(index, drawn));
+				value = U256::from_big_endian(&sp_io::hashing::blake2_256(&value.encode()));
+			}
+			winners
+		}
+
+		/// Draw up to `count` of the `len` participants of weighted giveaway `index` without
+		/// replacement, each in proportion to its entries. Every draw picks one of the entries
+		/// left with a fresh `U256` and finds its participant by binary search over
+		/// `CumulativeEntries`, skipping the entries of the participants drawn before.
+		pub(crate) fn draw_weighted_winners(
+			index: u32,
+			random: RandomResult,
+			len: u32,
+			count: u32,
+		) -> Vec<T::AccountId> {
+			if len == 0 {
+				return Vec::new();
+			}
+			let mut left = CumulativeEntries::<T>::get(index, len - 1);
+			// The positions drawn so far, with their entries.
+			let mut drawn: Vec<(u32, u64)> = Vec::new();
+			let mut value = random;
+			let mut winners = Vec::new();
+			while (drawn.len() as u32) < count.min(len) && left > 0 {
+				let entry = Self::unbiased_below(value, U256::from(left)).low_u64();
+				// The entries up to `position` that are still in the draw.
+				let entries_left = |position: u32| {
+					let taken: u64 = drawn
+						.iter()
+						.filter(|(drawn, _)| *drawn <= position)
+						.map(|(_, entries)| entries)
+						.sum();
+					CumulativeEntries::<T>::get(index, position).saturating_sub(taken)
+				};
+				let (mut low, mut high) = (0, len - 1);
+				while low < high {
+					let middle = low + (high - low) / 2;
+					if entries_left(middle) > entry {
+						high = middle;
+					} else {
+						low = middle + 1;
+					}
+				}
+				let entries = Self::entries_at(index, low);
+				drawn.push((low, entries));
+				left = left.saturating_sub(entries);
+				winners.extend(Participants::<T>::get(index, low));
+				value = U256::from_big_endian(&sp_io::hashing::blake2_256(&value.encode()));
+			}
+			winners
+		}
+
+		/// The part of `amount` won by the winner of prize `rank`. Rounding leftovers go to the
+		/// first prize.
+		pub(crate) fn prize_amount(
+			split: &PrizeSplit,
+			amount: BalanceOf<T>,
+			rank: usize,
+		) -> BalanceOf<T> {
+			let shares: Vec<BalanceOf<T>> = match split {
+				PrizeSplit::Equal(winners) => {
+					let share = amount / BalanceOf::<T>::from((*winners).max(1));
+					sp_std::iter::repeat(share)
+						.take(*winners as usize)
+						.collect()
+				}
+				PrizeSplit::Tiered(shares) => {
+					shares.iter().map(|share| share.mul_floor(amount)).collect()
+				}
+			};
+			match rank {
+				0 => shares
+					.iter()
+					.skip(1)
+					.fold(amount, |left, share| left.saturating_sub(*share)),
+				_ => shares.get(rank).copied().unwrap_or_else(Zero::zero),
+			}
+		}
+
+		/// Draw giveaway `index` from `T::Randomness` in block `n`. A VRF giveaway whose
+		/// randomness was already known when it ended is tried again in a later block.
+		fn draw_locally(index: u32, n: BlockNumberFor<T>) {
+			let Some(giveaway) = Giveaway::<T>::get(index) else { return };
+			let (output, known_since) =
+				T::Randomness::random(&(T::PalletId::get(), index).encode());
+			if giveaway.random_type == RandomType::Vrf && known_since <= giveaway.end {
+				Self::defer_draw(index, giveaway, n);
+				return;
+			}
+			let random = U256::from_big_endian(&sp_io::hashing::blake2_256(output.as_ref()));
+			Self::settle(index, random, &RequestId::default());
+		}
+
+		/// Move the draw of giveaway `index` to a later block. If the buckets of the next
+		/// `MAX_DEFER_BLOCKS` blocks are full, it is refunded instead.
+		fn defer_draw(index: u32, mut giveaway: GiveawayConfigOf<T>, n: BlockNumberFor<T>) {
+			let next = n.saturating_add(1u32.into());
+			match Self::append_to_agenda(next, |block| LocalDraws::<T>::try_append(block, index)) {
+				Some(draw_at) => {
+					giveaway.draw_at = draw_at;
+					Giveaway::<T>::insert(index, giveaway);
+				}
+				None => {
+					log::warn!(
+						target: LOG_TARGET,
+						"Draw of giveaway {} could not be deferred, refunding it",
+						index
+					);
+					Self::queue_refund(index, 0);
+				}
+			}
+		}
+
+		/// A uniformly distributed index below `len`, see [`Self::unbiased_below`].
+		pub(crate) fn unbiased_index(random: U256, len: u32) -> u32 {
+			Self::unbiased_below(random, U256::from(len)).low_u32()
+		}
+
+		/// A uniformly distributed number below `bound`. Values in the top of the `U256` range
+		/// that `bound` does not divide evenly are rehashed rather than reduced, so no number is
+		/// favoured.
+		pub(crate) fn unbiased_below(random: U256, bound: U256) -> U256 {
+			let biased = (U256::MAX % bound + U256::one()) % bound;
+			let mut value = random;
+			// Each round is rejected with a probability below `bound / 2^256`.
+			while value > U256::MAX - biased {
+				value = U256::from_big_endian(&sp_io::hashing::blake2_256(&value.encode()));
+			}
+			value % bound
+		}
+	}
 }
 
 impl<T: Config> Pallet<T> {
@@ -513,6 +2235,11 @@ impl<T: Config> Pallet<T> {
 		T::PalletId::get().into_account_truncating()
 	}
 
+	/// The account holding the prize of giveaway `index`, so no giveaway pays out of another's.
+	pub fn escrow_account(index: u32) -> T::AccountId {
+		T::PalletId::get().into_sub_account_truncating(index)
+	}
+
 	fn transfer_nft(
 		nft_collection_id: T::NftCollectionId,
 		nft_id: T::NftId,
@@ -521,25 +2248,243 @@ impl<T: Config> Pallet<T> {
 		T::Nfts::transfer(&nft_collection_id, &nft_id, account)
 	}
 
+	/// Move `token` through `T::Currency` when it is the native asset, and `T::Assets` otherwise.
+	/// The native currency always keeps `from` alive.
 	fn transfer_asset(
 		from: &T::AccountId,
 		to: &T::AccountId,
-		amount: BalanceOf<T>,
+		token: &TokenInfoOf<T>,
+		preservation: Preservation,
 	) -> DispatchResult {
-		T::Currency::transfer(from, to, amount, ExistenceRequirement::KeepAlive)
+		if token.asset_id == T::NativeAssetId::get() {
+			T::Currency::transfer(from, to, token.amount, ExistenceRequirement::KeepAlive)
+		} else {
+			T::Assets::transfer(token.asset_id.clone(), from, to, token.amount, preservation)
+				.map(|_| ())
+		}
+	}
+
+	/// Whether `who` meets the entry `requirement`.
+	fn meets(who: &T::AccountId, requirement: &EntryRequirementOf<T>) -> bool {
+		match requirement {
+			EntryRequirement::InList(list_name) => T::Identity::in_list(list_name, who),
+			EntryRequirement::NotInList(list_name) => !T::Identity::is_member(list_name, who),
+			EntryRequirement::MinBalance(token) => {
+				let balance = if token.asset_id == T::NativeAssetId::get() {
+					T::Currency::free_balance(who)
+				} else {
+					T::Assets::balance(token.asset_id.clone(), who)
+				};
+				balance >= token.amount
+			}
+			EntryRequirement::HoldsNft(collection_id) => {
+				T::Nfts::owned_in_collection(collection_id, who)
+					.next()
+					.is_some()
+			}
+			EntryRequirement::MinAccountAge(age) => {
+				let created_at = AccountCreatedAt::<T>::get(who).unwrap_or_else(Zero::zero);
+				frame_system::Pallet::<T>::block_number().saturating_sub(created_at) >= *age
+			}
+		}
+	}
+
+	/// Check that `who` can put `token` in escrow and keep its account alive.
+	fn ensure_can_escrow(who: &T::AccountId, token: &TokenInfoOf<T>) -> DispatchResult {
+		ensure!(
+			token.asset_id == T::NativeAssetId::get()
+				|| T::Assets::asset_exists(token.asset_id.clone()),
+			Error::<T>::UnknownAsset
+		);
+		ensure!(
+			Self::spendable_balance(who, &token.asset_id) >= token.amount,
+			Error::<T>::InsufficientBalance
+		);
+		Ok(())
+	}
+
+	/// How much of `asset_id` `who` can put in escrow and keep its account alive.
+	fn spendable_balance(who: &T::AccountId, asset_id: &T::AssetId) -> BalanceOf<T> {
+		if *asset_id == T::NativeAssetId::get() {
+			T::Currency::free_balance(who).saturating_sub(T::Currency::minimum_balance())
+		} else {
+			T::Assets::reducible_balance(
+				asset_id.clone(),
+				who,
+				Preservation::Preserve,
+				Fortitude::Polite,
+			)
+		}
+	}
+}
+
+/// Record when accounts are created, for [`EntryRequirement::MinAccountAge`]. Set it as the
+/// `OnNewAccount` and `OnKilledAccount` of `frame_system`.
+impl<T: Config> OnNewAccount<T::AccountId> for Pallet<T> {
+	fn on_new_account(who: &T::AccountId) {
+		AccountCreatedAt::<T>::insert(who, frame_system::Pallet::<T>::block_number());
+	}
+}
+
+impl<T: Config> OnKilledAccount<T::AccountId> for Pallet<T> {
+	fn on_killed_account(who: &T::AccountId) {
+		AccountCreatedAt::<T>::remove(who);
+	}
+}
+
+/// Why an offchain worker did not submit the results of a block.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum OffchainError {
+	/// Another worker is submitting them, or did so recently.
+	Locked,
+	/// The endpoint is not valid UTF-8.
+	InvalidEndpoint,
+	/// The randomness request failed.
+	Http(http::Error),
+	/// The endpoint answered with something other than a randomness response.
+	InvalidResponse,
+	/// No `AuthorityId` key is in the keystore.
+	NoSigner,
+	/// The stored results to reveal do not decode.
+	CorruptReveal,
+	/// The transaction pool refused the transaction.
+	SubmitFailed,
+}
+
+impl<T: Config> Pallet<T> {
+	fn randomness_endpoint() -> Option<Vec<u8>> {
+		sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, RANDOMNESS_ENDPOINT_KEY)
+			.filter(|endpoint| !endpoint.is_empty())
+	}
+
+	/// Fetch a VRF proof for the giveaways ending at `block_number` and commit to it. The proof
+	/// and the salt are kept in offchain storage until they are revealed.
+	///
+	/// The lock on the block is kept once the transaction is sent, and only released early
+	/// when the fetch or the submission fails.
+	pub(crate) fn commit_results(
+		endpoint: &[u8],
+		block_number: BlockNumberFor<T>,
+	) -> Result<(), OffchainError> {
+		let reveal_key = [REVEAL_PREFIX, &block_number.encode()].concat();
+		if sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &reveal_key).is_some() {
+			return Ok(());
+		}
+		let lock_key = [RESULT_LOCK_PREFIX, &block_number.encode()].concat();
+		let mut lock =
+			StorageLock::<BlockAndTime<frame_system::Pallet<T>>>::with_block_and_time_deadline(
+				&lock_key,
+				T::ResultLockBlocks::get(),
+				Duration::from_millis(RESULT_LOCK_TIMEOUT_MS),
+			);
+		let guard = lock.try_lock().map_err(|_| OffchainError::Locked)?;
+
+		let (request_id, key, proof) = Self::fetch_randomness(endpoint, block_number)?;
+		let salt = sp_io::offchain::random_seed();
+		let signer = Signer::<T, T::AuthorityId>::any_account();
+		let (_, sent) = signer
+			.send_signed_transaction(|account| Call::commit_block_result {
+				block_number,
+				commitment: Self::commitment(&account.id, block_number, &proof, &salt),
+			})
+			.ok_or(OffchainError::NoSigner)?;
+		sent.map_err(|_| OffchainError::SubmitFailed)?;
+		sp_io::offchain::local_storage_set(
+			StorageKind::PERSISTENT,
+			&reveal_key,
+			&(key, proof, salt).encode(),
+		);
+		log::debug!(
+			target: LOG_TARGET,
+			"Committed to request {:?} for block {:?}",
+			sp_std::str::from_utf8(&request_id),
+			block_number,
+		);
+
+		guard.forget();
+		Ok(())
+	}
+
+	/// Reveal the proof committed to for the giveaways ending at `block_number`, if any.
+	pub(crate) fn reveal_results(block_number: BlockNumberFor<T>) -> Result<(), OffchainError> {
+		let reveal_key = [REVEAL_PREFIX, &block_number.encode()].concat();
+		let Some(stored) =
+			sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &reveal_key)
+		else {
+			return Ok(())
+		};
+		let (key, proof, salt) = <(VrfPublic, VrfProof, [u8; 32])>::decode(&mut &stored[..])
+			.map_err(|_| OffchainError::CorruptReveal)?;
+		let signer = Signer::<T, T::AuthorityId>::any_account();
+		let (_, sent) = signer
+			.send_signed_transaction(|_| Call::reveal_block_result {
+				block_number,
+				key,
+				proof,
+				salt,
+			})
+			.ok_or(OffchainError::NoSigner)?;
+		sent.map_err(|_| OffchainError::SubmitFailed)?;
+		sp_io::offchain::local_storage_clear(StorageKind::PERSISTENT, &reveal_key);
+		Ok(())
 	}
 
-	fn random_number(index: u32, length: u32) -> u32 {
-		// Get the current block's random seed
-		let random_number = Self::generate_random_number(index);
-		let random_number = random_number % length;
-		random_number
+	/// `GET <endpoint>?block=<block_number>&alpha=0x<input>` with the hex encoded
+	/// [`Pallet::vrf_input`] of the block, see [`Self::parse_randomness`] for the expected
+	/// response.
+	fn fetch_randomness(
+		endpoint: &[u8],
+		block_number: BlockNumberFor<T>,
+	) -> Result<(Vec<u8>, VrfPublic, VrfProof), OffchainError> {
+		let endpoint =
+			sp_std::str::from_utf8(endpoint).map_err(|_| OffchainError::InvalidEndpoint)?;
+		let alpha = Self::vrf_input(block_number);
+		let block_number: u64 = block_number.unique_saturated_into();
+		let url = format!(
+			"{}?block={}&alpha=0x{}",
+			endpoint,
+			block_number,
+			HexDisplay::from(&alpha)
+		);
+
+		let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(HTTP_TIMEOUT_MS));
+		let pending = http::Request::get(&url)
+			.deadline(deadline)
+			.send()
+			.map_err(|_| OffchainError::Http(http::Error::IoError))?;
+		let response = pending
+			.try_wait(deadline)
+			.map_err(|_| OffchainError::Http(http::Error::DeadlineReached))?
+			.map_err(OffchainError::Http)?;
+		if response.code != 200 {
+			return Err(OffchainError::Http(http::Error::Unknown));
+		}
+		let body = response.body().collect::<Vec<u8>>();
+		Self::parse_randomness(&body).ok_or(OffchainError::InvalidResponse)
 	}
 
-	fn generate_random_number(seed: u32) -> u32 {
-		let (random_seed, _) = T::Randomness::random(&(T::PalletId::get(), seed).encode());
-		let random_number = <u32>::decode(&mut random_seed.as_ref())
-			.expect("secure hashes should always be bigger than u32; qed");
-		random_number
+	/// Parse a randomness response: the request id on the first line, then the compressed
+	/// secp256k1 VRF key and the proof in hex, `0x` prefix optional. Blank lines are skipped.
+	pub(crate) fn parse_randomness(body: &[u8]) -> Option<(Vec<u8>, VrfPublic, VrfProof)> {
+		let body = sp_std::str::from_utf8(body).ok()?;
+		let mut lines = body.lines().map(str::trim).filter(|line| !line.is_empty());
+		let request_id = lines.next()?.as_bytes().to_vec();
+		let key = Self::parse_hex(lines.next()?)?;
+		let proof = Self::parse_hex(lines.next()?)?;
+		(request_id.len() <= RequestId::bound() && lines.next().is_none())
+			.then_some((request_id, key, proof))
+	}
+
+	/// Decode exactly `N` bytes of hex, `0x` prefix optional.
+	fn parse_hex<const N: usize>(line: &str) -> Option<[u8; N]> {
+		let digits = line.strip_prefix("0x").unwrap_or(line);
+		if digits.len() != N * 2 || !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+			return None;
+		}
+		let mut bytes = [0u8; N];
+		for (byte, pair) in bytes.iter_mut().zip(digits.as_bytes().chunks(2)) {
+			*byte = u8::from_str_radix(sp_std::str::from_utf8(pair).ok()?, 16).ok()?;
+		}
+		Some(bytes)
 	}
 }
@@ -0,0 +1,358 @@
+//! Test utilities
+use super::*;
+use crate as pallet_ocw_giveaway;
+
+use frame_support::{
+	construct_runtime, parameter_types,
+	traits::{AsEnsureOriginWithArg, ConstU32, ConstU64, Everything, SortedMembers},
+};
+use frame_system::{EnsureRoot, EnsureSigned, EnsureSignedBy};
+use pallet_nfts::PalletFeatures;
+use parking_lot::RwLock;
+use sp_core::{
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt, TransactionPoolExt},
+	sr25519, Pair, H256,
+};
+use sp_keystore::{testing::MemoryKeystore, Keystore, KeystoreExt};
+use sp_runtime::{
+	testing::TestXt,
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, Hash, IdentifyAccount, IdentityLookup, Verify},
+	BuildStorage, MultiSignature,
+};
+use std::sync::Arc;
+
+pub type Signature = MultiSignature;
+pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+pub type Balance = u64;
+pub type Extrinsic = TestXt<RuntimeCall, ()>;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+construct_runtime!(
+	pub enum Test {
+		System: frame_system,
+		Balances: pallet_balances,
+		Assets: pallet_assets,
+		Nfts: pallet_nfts,
+		Giveaway: pallet_ocw_giveaway,
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type Nonce = u64;
+	type RuntimeCall = RuntimeCall;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Block = Block;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = Giveaway;
+	type OnKilledAccount = Giveaway;
+	type SystemWeightInfo = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type MaxLocks = ();
+	type Balance = Balance;
+	type RuntimeEvent = RuntimeEvent;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type RuntimeHoldReason = ();
+	type FreezeIdentifier = ();
+	type MaxHolds = ();
+	type MaxFreezes = ();
+}
+
+impl pallet_assets::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type AssetId = u32;
+	type AssetIdParameter = u32;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type AssetDeposit = ConstU64<1>;
+	type AssetAccountDeposit = ConstU64<1>;
+	type MetadataDepositBase = ConstU64<1>;
+	type MetadataDepositPerByte = ConstU64<1>;
+	type ApprovalDeposit = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type CallbackHandle = ();
+	type RemoveItemsLimit = ConstU32<5>;
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
+}
+
+parameter_types! {
+	pub storage Features: PalletFeatures = PalletFeatures::all_enabled();
+}
+
+impl pallet_nfts::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type CollectionId = u32;
+	type ItemId = u32;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type Locker = ();
+	type CollectionDeposit = ConstU64<1>;
+	type ItemDeposit = ConstU64<1>;
+	type MetadataDepositBase = ConstU64<1>;
+	type AttributeDepositBase = ConstU64<1>;
+	type DepositPerByte = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type KeyLimit = ConstU32<50>;
+	type ValueLimit = ConstU32<50>;
+	type ApprovalsLimit = ConstU32<10>;
+	type ItemAttributesApprovalsLimit = ConstU32<2>;
+	type MaxTips = ConstU32<10>;
+	type MaxDeadlineDuration = ConstU64<10_000>;
+	type MaxAttributesPerCall = ConstU32<2>;
+	type Features = Features;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type WeightInfo = ();
+	#[cfg(feature = "runtime-benchmarks")]
+	type Helper = ();
+}
+
+parameter_types! {
+	/// The block randomness is known since, the current one when unset.
+	pub static RandomnessKnownSince: Option<u64> = None;
+}
+
+/// Randomness that only depends on the subject and the block number.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+	fn random(subject: &[u8]) -> (H256, u64) {
+		let block_number = System::block_number();
+		(
+			BlakeTwo256::hash_of(&(subject, block_number)),
+			RandomnessKnownSince::get().unwrap_or(block_number),
+		)
+	}
+}
+
+parameter_types! {
+	/// The members of each `pallet_did` list, all of them allowlists.
+	pub static ListMembers: Vec<(ListName, AccountId)> = vec![];
+}
+
+/// Nobody is verified beyond tier `0`, list members come from [`ListMembers`].
+pub struct TestIdentity;
+
+impl VerifyIdentity<AccountId> for TestIdentity {
+	fn tier(_: &AccountId) -> Tier {
+		0
+	}
+
+	fn max_tier() -> Tier {
+		0
+	}
+
+	fn has_provider(_: &AccountId, _: &pallet_did::Provider) -> bool {
+		false
+	}
+
+	fn in_list(list_name: &ListName, who: &AccountId) -> bool {
+		Self::is_member(list_name, who)
+	}
+
+	fn is_member(list_name: &ListName, who: &AccountId) -> bool {
+		ListMembers::get().contains(&(list_name.clone(), who.clone()))
+	}
+}
+
+/// Only the giveaway key in the keystore may act as `GiveawayOrigin`.
+pub struct GiveawayManagers;
+
+impl SortedMembers<AccountId> for GiveawayManagers {
+	fn sorted_members() -> Vec<AccountId> {
+		vec![authority()]
+	}
+}
+
+parameter_types! {
+	pub const GiveawayPalletId: PalletId = PalletId(*b"giveaway");
+	pub const Treasury: AccountId = TREASURY;
+	pub const TreasuryShare: Percent = Percent::from_percent(10);
+	/// How many oracles must reveal their results.
+	pub static OracleThreshold: u32 = 1;
+}
+
+impl pallet_ocw_giveaway::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type GiveawayOrigin = EnsureSignedBy<GiveawayManagers, AccountId>;
+	type Identity = TestIdentity;
+	type AuthorityId = crypto::TestAuthId;
+	type PalletId = GiveawayPalletId;
+	type Currency = Balances;
+	type Randomness = TestRandomness;
+	type PotDeposit = ConstU64<1>;
+	type MaxSet = ConstU32<8>;
+	type ResultLockBlocks = ConstU32<3>;
+	type NftCollectionId = u32;
+	type NftId = u32;
+	type Nfts = Nfts;
+	type AssetBalance = Balance;
+	type AssetId = u32;
+	type Assets = Assets;
+	type NativeAssetId = ConstU32<0>;
+	type Treasury = Treasury;
+	type TreasuryShare = TreasuryShare;
+	type OracleBond = ConstU64<10>;
+	type MaxOracles = ConstU32<4>;
+	type OracleThreshold = OracleThreshold;
+	type CommitBlocks = ConstU64<2>;
+	type RevealBlocks = ConstU64<2>;
+	type RandomnessTimeout = ConstU64<10>;
+	type MaxDrawsPerBlock = ConstU32<2>;
+	type WeightInfo = ();
+}
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		_public: <Signature as Verify>::Signer,
+		_account: AccountId,
+		nonce: u64,
+	) -> Option<(RuntimeCall, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+pub const CREATOR: AccountId = AccountId::new([1; 32]);
+pub const PARTICIPANT: AccountId = AccountId::new([2; 32]);
+pub const TREASURY: AccountId = AccountId::new([9; 32]);
+pub const RANDOMNESS_ENDPOINT: &str = "https://randomness.example";
+const AUTHORITY_SEED: &str = "//Giveaway";
+
+/// Handles to the offchain state of an [`ExtBuilder::build_offchain`] externality.
+pub(crate) struct OffchainState {
+	pub offchain: Arc<RwLock<testing::OffchainState>>,
+	pub pool: Arc<RwLock<testing::PoolState>>,
+}
+
+pub(crate) struct ExtBuilder {
+	// endowed accounts with balances
+	balances: Vec<(AccountId, Balance)>,
+	// whether the node has a randomness endpoint set
+	endpoint: bool,
+}
+
+impl Default for ExtBuilder {
+	fn default() -> ExtBuilder {
+		ExtBuilder {
+			balances: vec![(CREATOR, 1_000), (PARTICIPANT, 1_000), (authority(), 1_000)],
+			endpoint: true,
+		}
+	}
+}
+
+impl ExtBuilder {
+	pub(crate) fn without_endpoint(mut self) -> Self {
+		self.endpoint = false;
+		self
+	}
+
+	pub(crate) fn build(self) -> sp_io::TestExternalities {
+		let mut t = frame_system::GenesisConfig::<Test>::default()
+			.build_storage()
+			.expect("Frame system builds valid default genesis config");
+
+		pallet_balances::GenesisConfig::<Test> {
+			balances: self.balances,
+		}
+		.assimilate_storage(&mut t)
+		.expect("Pallet balances storage can be assimilated");
+
+		let mut ext = sp_io::TestExternalities::new(t);
+		ext.execute_with(|| System::set_block_number(1));
+		ext
+	}
+
+	/// An externality with mocked HTTP, offchain storage, transaction pool and a keystore holding
+	/// one giveaway key.
+	pub(crate) fn build_offchain(self) -> (sp_io::TestExternalities, OffchainState) {
+		let endpoint = self.endpoint;
+		let mut ext = self.build();
+
+		let (offchain, offchain_state) = testing::TestOffchainExt::new();
+		let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+		let keystore = MemoryKeystore::new();
+		keystore
+			.sr25519_generate_new(KEY_TYPE, Some(AUTHORITY_SEED))
+			.expect("Keys can be generated in a memory keystore");
+
+		ext.register_extension(OffchainDbExt::new(offchain.clone()));
+		ext.register_extension(OffchainWorkerExt::new(offchain));
+		ext.register_extension(TransactionPoolExt::new(pool));
+		ext.register_extension(KeystoreExt::new(Arc::new(keystore)));
+		if endpoint {
+			ext.execute_with(|| {
+				sp_io::offchain::local_storage_set(
+					StorageKind::PERSISTENT,
+					RANDOMNESS_ENDPOINT_KEY,
+					RANDOMNESS_ENDPOINT.as_bytes(),
+				)
+			});
+		}
+
+		(
+			ext,
+			OffchainState {
+				offchain: offchain_state,
+				pool: pool_state,
+			},
+		)
+	}
+}
+
+/// The account of the giveaway key in the keystore.
+pub(crate) fn authority() -> AccountId {
+	let public = sr25519::Pair::from_string(AUTHORITY_SEED, None)
+		.expect("The seed is a valid derivation path")
+		.public();
+	<Signature as Verify>::Signer::from(public).into_account()
+}
@@ -0,0 +1,2338 @@
+use crate::{
+	mock::{Giveaway, *},
+	*,
+};
+use frame_support::{assert_noop, assert_ok, traits::Hooks, weights::Weight, BoundedVec};
+use k256::Scalar;
+use sp_core::offchain::testing;
+use sp_runtime::{traits::Dispatchable, DispatchError, TokenError};
+
+fn create_giveaway(start: u64, end: u64) -> u32 {
+	create_giveaway_with(RandomType::Chainlink, start, end)
+}
+
+fn create_giveaway_with(random_type: RandomType, start: u64, end: u64) -> u32 {
+	create_giveaway_falling_back(random_type, RandomnessFallback::Refund, start, end)
+}
+
+fn create_giveaway_falling_back(
+	random_type: RandomType,
+	fallback: RandomnessFallback,
+	start: u64,
+	end: u64,
+) -> u32 {
+	let index = GiveawayIndex::<Test>::get();
+	assert_ok!(Giveaway::create_give_away(
+		RuntimeOrigin::signed(CREATOR),
+		b"giveaway".to_vec(),
+		start,
+		end,
+		0,
+		random_type,
+		AssetType::FungibleToken,
+		Some(TokenInfo {
+			asset_id: 0,
+			amount: 100
+		}),
+		None,
+		PrizeSplit::default(),
+		Default::default(),
+		None,
+		WeightRule::Flat,
+		fallback,
+		10,
+	));
+	index
+}
+
+const ASSET: u32 = 1;
+
+fn create_asset(owner: AccountId, amount: Balance) {
+	assert_ok!(Assets::force_create(
+		RuntimeOrigin::root(),
+		ASSET,
+		owner.clone(),
+		true,
+		1
+	));
+	assert_ok!(Assets::mint(
+		RuntimeOrigin::signed(owner.clone()),
+		ASSET,
+		owner,
+		amount
+	));
+}
+
+fn create_token_giveaway(asset_id: u32, amount: Balance) -> DispatchResult {
+	Giveaway::create_give_away(
+		RuntimeOrigin::signed(CREATOR),
+		b"giveaway".to_vec(),
+		2,
+		5,
+		0,
+		RandomType::LocalChain,
+		AssetType::FungibleToken,
+		Some(TokenInfo { asset_id, amount }),
+		None,
+		PrizeSplit::default(),
+		Default::default(),
+		None,
+		WeightRule::Flat,
+		RandomnessFallback::Refund,
+		10,
+	)
+}
+
+fn create_split_giveaway(prize_split: PrizeSplit) -> DispatchResult {
+	Giveaway::create_give_away(
+		RuntimeOrigin::signed(CREATOR),
+		b"giveaway".to_vec(),
+		2,
+		5,
+		0,
+		RandomType::LocalChain,
+		AssetType::FungibleToken,
+		Some(TokenInfo {
+			asset_id: 0,
+			amount: 100,
+		}),
+		None,
+		prize_split,
+		Default::default(),
+		None,
+		WeightRule::Flat,
+		RandomnessFallback::Refund,
+		10,
+	)
+}
+
+fn create_gated_giveaway(requirements: Vec<EntryRequirementOf<Test>>) -> DispatchResult {
+	Giveaway::create_give_away(
+		RuntimeOrigin::signed(CREATOR),
+		b"giveaway".to_vec(),
+		2,
+		10,
+		0,
+		RandomType::LocalChain,
+		AssetType::FungibleToken,
+		Some(TokenInfo {
+			asset_id: 0,
+			amount: 100,
+		}),
+		None,
+		PrizeSplit::default(),
+		EntryRequirementsOf::<Test>::truncate_from(requirements),
+		None,
+		WeightRule::Flat,
+		RandomnessFallback::Refund,
+		10,
+	)
+}
+
+fn create_fee_giveaway(asset_id: u32, amount: Balance, destination: FeeDestination) -> u32 {
+	let index = GiveawayIndex::<Test>::get();
+	assert_ok!(create_charging_giveaway(EntryFee {
+		token: TokenInfo { asset_id, amount },
+		destination
+	}));
+	index
+}
+
+fn create_charging_giveaway(fee: EntryFeeOf<Test>) -> DispatchResult {
+	Giveaway::create_give_away(
+		RuntimeOrigin::signed(CREATOR),
+		b"giveaway".to_vec(),
+		2,
+		5,
+		0,
+		RandomType::LocalChain,
+		AssetType::FungibleToken,
+		Some(TokenInfo {
+			asset_id: 0,
+			amount: 100,
+		}),
+		None,
+		PrizeSplit::default(),
+		Default::default(),
+		Some(fee),
+		WeightRule::Flat,
+		RandomnessFallback::Refund,
+		10,
+	)
+}
+
+fn create_weighted_giveaway(
+	weight_rule: WeightRuleOf<Test>,
+	fee: Option<EntryFeeOf<Test>>,
+) -> DispatchResult {
+	Giveaway::create_give_away(
+		RuntimeOrigin::signed(CREATOR),
+		b"giveaway".to_vec(),
+		2,
+		5,
+		0,
+		RandomType::LocalChain,
+		AssetType::FungibleToken,
+		Some(TokenInfo {
+			asset_id: 0,
+			amount: 100,
+		}),
+		None,
+		PrizeSplit::Equal(2),
+		Default::default(),
+		fee,
+		weight_rule,
+		RandomnessFallback::Refund,
+		10,
+	)
+}
+
+fn native_fee(amount: Balance) -> Option<EntryFeeOf<Test>> {
+	Some(EntryFee {
+		token: TokenInfo {
+			asset_id: 0,
+			amount,
+		},
+		destination: FeeDestination::Creator,
+	})
+}
+
+/// The VRF key and proof of the oracle `seed` for the giveaways ending at `block_number`.
+fn vrf_proof(seed: u8, block_number: u64) -> (VrfPublic, VrfProof) {
+	vrf::prove(
+		&Scalar::from(u64::from(seed)),
+		&Giveaway::vrf_input(block_number),
+	)
+}
+
+/// The output the oracle `seed` reveals for the giveaways ending at `block_number`.
+fn vrf_output(seed: u8, block_number: u64) -> VrfOutput {
+	let (key, proof) = vrf_proof(seed, block_number);
+	vrf::verify(&key, &Giveaway::vrf_input(block_number), &proof)
+		.expect("Proofs of the test keys are valid")
+}
+
+fn apply_oracle(seed: u8) -> AccountId {
+	let who = account(seed);
+	assert_ok!(Balances::transfer_allow_death(
+		RuntimeOrigin::signed(CREATOR),
+		who.clone(),
+		100
+	));
+	assert_ok!(Giveaway::register_oracle(
+		RuntimeOrigin::signed(who.clone()),
+		vrf_proof(seed, 0).0
+	));
+	who
+}
+
+fn register_oracle(seed: u8) -> AccountId {
+	let who = apply_oracle(seed);
+	assert_ok!(Giveaway::approve_oracle(
+		RuntimeOrigin::signed(authority()),
+		who.clone()
+	));
+	who
+}
+
+fn commit(seed: u8, block_number: u64, salt: u8) -> DispatchResult {
+	let who = account(seed);
+	let (_, proof) = vrf_proof(seed, block_number);
+	let commitment = Giveaway::commitment(&who, block_number, &proof, &[salt; 32]);
+	Giveaway::commit_block_result(RuntimeOrigin::signed(who), block_number, commitment)
+}
+
+fn reveal(seed: u8, block_number: u64, salt: u8) -> DispatchResult {
+	let (key, proof) = vrf_proof(seed, block_number);
+	Giveaway::reveal_block_result(
+		RuntimeOrigin::signed(account(seed)),
+		block_number,
+		key,
+		proof,
+		[salt; 32],
+	)
+}
+
+fn hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn list(name: &[u8]) -> ListName {
+	ListName::truncate_from(name.to_vec())
+}
+
+fn tiered(shares: &[u8]) -> PrizeSplit {
+	PrizeSplit::Tiered(PrizeShares::truncate_from(
+		shares.iter().copied().map(Percent::from_percent).collect(),
+	))
+}
+
+fn account(seed: u8) -> AccountId {
+	AccountId::new([seed; 32])
+}
+
+const COLLECTION: u32 = 0;
+const ITEM: u32 = 42;
+
+fn mint_nft(owner: AccountId) {
+	assert_ok!(Nfts::force_create(
+		RuntimeOrigin::root(),
+		owner.clone(),
+		pallet_nfts::CollectionConfig {
+			settings: pallet_nfts::CollectionSettings::all_enabled(),
+			max_supply: None,
+			mint_settings: pallet_nfts::MintSettings::default(),
+		},
+	));
+	assert_ok!(Nfts::mint(
+		RuntimeOrigin::signed(owner.clone()),
+		COLLECTION,
+		ITEM,
+		owner,
+		None
+	));
+}
+
+fn create_nft_giveaway(start: u64, end: u64) -> DispatchResult {
+	Giveaway::create_give_away(
+		RuntimeOrigin::signed(CREATOR),
+		b"giveaway".to_vec(),
+		start,
+		end,
+		0,
+		RandomType::LocalChain,
+		AssetType::NonFungibleToken,
+		None,
+		Some(NftInfo {
+			collection_id: COLLECTION,
+			nft_id: ITEM,
+		}),
+		PrizeSplit::default(),
+		Default::default(),
+		None,
+		WeightRule::Flat,
+		RandomnessFallback::Refund,
+		10,
+	)
+}
+
+fn winners(index: u32) -> Vec<AccountId> {
+	Winners::<Test>::get(index)
+		.into_iter()
+		.flatten()
+		.map(|winner| winner.who)
+		.collect()
+}
+
+fn nft_owner() -> Option<AccountId> {
+	<Nfts as NonFungiblesInspect<AccountId>>::owner(&COLLECTION, &ITEM)
+}
+
+/// The seed of the VRF key of the giveaway key in the keystore.
+const AUTHORITY_VRF_SEED: u8 = 42;
+
+fn randomness_response(request_id: &str, block: u64) -> String {
+	let (key, proof) = vrf_proof(AUTHORITY_VRF_SEED, block);
+	format!("{}\n0x{}\n0x{}\n", request_id, hex(&key), hex(&proof))
+}
+
+fn expect_randomness_request(state: &OffchainState, block: u64, response: &str) {
+	state
+		.offchain
+		.write()
+		.expect_request(testing::PendingRequest {
+			method: "GET".into(),
+			uri: format!(
+				"{}?block={}&alpha=0x{}",
+				RANDOMNESS_ENDPOINT,
+				block,
+				hex(&Giveaway::vrf_input(block))
+			),
+			response: Some(response.as_bytes().to_vec()),
+			sent: true,
+			..Default::default()
+		});
+}
+
+fn submitted(state: &OffchainState) -> Vec<Extrinsic> {
+	state
+		.pool
+		.read()
+		.transactions
+		.iter()
+		.map(|tx| Extrinsic::decode(&mut &tx[..]).expect("Pool holds valid extrinsics"))
+		.collect()
+}
+
+fn run_to_block(block: u64) {
+	System::set_block_number(block);
+	Giveaway::on_initialize(block);
+}
+
+fn run_offchain_worker(block: u64) {
+	System::set_block_number(block);
+	Giveaway::offchain_worker(block);
+}
+
+#[test]
+fn parse_randomness_reads_the_request_id_key_and_proof() {
+	let key = format!("02{}", "ab".repeat(32));
+	let proof = "0f".repeat(81);
+	let mut expected_key = [0xab; 33];
+	expected_key[0] = 0x02;
+	assert_eq!(
+		Giveaway::parse_randomness(format!("req-1\n0x{}\n{}\n", key, proof).as_bytes()),
+		Some((b"req-1".to_vec(), expected_key, [0x0f; 81]))
+	);
+	assert_eq!(
+		Giveaway::parse_randomness(
+			format!("  req-1 \r\n\r\n{}\r\n0x{}\r\n", key, proof).as_bytes()
+		)
+		.map(|(request_id, ..)| request_id),
+		Some(b"req-1".to_vec())
+	);
+}
+
+#[test]
+fn parse_randomness_rejects_malformed_responses() {
+	let key = "02".repeat(33);
+	let proof = "0f".repeat(81);
+	let parse = |body: String| Giveaway::parse_randomness(body.as_bytes());
+	// No proof, or more than one.
+	assert_eq!(parse(format!("req\n{}\n", key)), None);
+	assert_eq!(parse(format!("req\n{}\n{}\n{}\n", key, proof, proof)), None);
+	// Not hex, or the wrong length.
+	assert_eq!(parse(format!("req\n{}\n{}zz\n", key, &proof[2..])), None);
+	assert_eq!(parse(format!("req\n{}\n+f{}\n", key, &proof[2..])), None);
+	assert_eq!(parse(format!("req\n{}00\n{}\n", key, proof)), None);
+	assert_eq!(parse(format!("req\n{}\n{}\n", key, &proof[2..])), None);
+	// No request id, or one longer than a `RequestId`.
+	assert_eq!(parse(String::new()), None);
+	assert_eq!(
+		parse(format!("{}\n{}\n{}\n", "r".repeat(129), key, proof)),
+		None
+	);
+	assert_eq!(Giveaway::parse_randomness(&[0xff, 0xfe]), None);
+}
+
+#[test]
+fn vrf_proofs_only_verify_for_their_key_and_input() {
+	let secret = Scalar::from(3u64);
+	let (key, proof) = vrf::prove(&secret, b"input");
+	let output = vrf::verify(&key, b"input", &proof).expect("The proof is valid");
+	assert_eq!(vrf::prove(&secret, b"input"), (key, proof));
+
+	let (other_key, other_proof) = vrf::prove(&Scalar::from(4u64), b"input");
+	assert_ne!(
+		vrf::verify(&other_key, b"input", &other_proof),
+		Some(output)
+	);
+	assert_eq!(vrf::verify(&other_key, b"input", &proof), None);
+	assert_eq!(vrf::verify(&key, b"other", &proof), None);
+	// Gamma, the challenge and the response.
+	for position in [0, 40, 80] {
+		let mut tampered = proof;
+		tampered[position] ^= 1;
+		assert_eq!(vrf::verify(&key, b"input", &tampered), None);
+	}
+
+	assert!(vrf::is_valid_key(&key));
+	assert!(!vrf::is_valid_key(&[0; 33]));
+	assert_ne!(vrf::random_word(&output, 0), vrf::random_word(&output, 1));
+}
+
+#[test]
+fn offchain_worker_commits_and_reveals_results() {
+	let (mut ext, state) = ExtBuilder::default().build_offchain();
+	ext.execute_with(|| {
+		let (key, _) = vrf_proof(AUTHORITY_VRF_SEED, 0);
+		assert_ok!(Giveaway::register_oracle(
+			RuntimeOrigin::signed(authority()),
+			key
+		));
+		assert_ok!(Giveaway::approve_oracle(
+			RuntimeOrigin::signed(authority()),
+			authority()
+		));
+		let index = create_giveaway(2, 5);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		expect_randomness_request(&state, 5, &randomness_response("req-1", 5));
+		run_offchain_worker(6);
+
+		let txs = submitted(&state);
+		assert_eq!(txs.len(), 1);
+		assert_eq!(txs[0].signature, Some((0, ())));
+		assert!(matches!(
+			txs[0].call,
+			RuntimeCall::Giveaway(Call::commit_block_result {
+				block_number: 5,
+				..
+			})
+		));
+		assert_ok!(txs[0]
+			.call
+			.clone()
+			.dispatch(RuntimeOrigin::signed(authority())));
+
+		run_offchain_worker(7);
+
+		let txs = submitted(&state);
+		assert_eq!(txs.len(), 2);
+		let RuntimeCall::Giveaway(Call::reveal_block_result { block_number, key, proof, .. }) =
+			&txs[1].call
+		else {
+			panic!("Expected a reveal, got {:?}", txs[1].call)
+		};
+		assert_eq!(
+			(*block_number, (*key, *proof)),
+			(5, vrf_proof(AUTHORITY_VRF_SEED, 5))
+		);
+		System::set_block_number(8);
+		assert_ok!(txs[1]
+			.call
+			.clone()
+			.dispatch(RuntimeOrigin::signed(authority())));
+
+		run_to_block(9);
+		assert_eq!(winners(index), vec![]);
+
+		run_to_block(10);
+		assert_eq!(winners(index), vec![PARTICIPANT]);
+		assert_eq!(
+			BlockToResults::<Test>::get(5, 0).map(|(_, results)| results.into_inner()),
+			Some(vec![vrf::random_word(
+				&vrf_output(AUTHORITY_VRF_SEED, 5),
+				index
+			)])
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_waits_for_the_end_block_to_pass() {
+	let (mut ext, state) = ExtBuilder::default().build_offchain();
+	ext.execute_with(|| {
+		create_giveaway(2, 5);
+
+		run_offchain_worker(5);
+
+		assert!(submitted(&state).is_empty());
+	});
+}
+
+#[test]
+fn offchain_worker_does_not_submit_twice() {
+	let (mut ext, state) = ExtBuilder::default().build_offchain();
+	ext.execute_with(|| {
+		create_giveaway(2, 5);
+
+		expect_randomness_request(&state, 5, &randomness_response("req-1", 5));
+		run_offchain_worker(6);
+		// The results are kept until they are revealed, later runs do not fetch again.
+		run_offchain_worker(6);
+		assert_eq!(submitted(&state).len(), 1);
+
+		// They are revealed once.
+		run_offchain_worker(7);
+		run_offchain_worker(8);
+		assert_eq!(submitted(&state).len(), 2);
+	});
+}
+
+#[test]
+fn offchain_worker_skips_blocks_with_results() {
+	let (mut ext, state) = ExtBuilder::default().build_offchain();
+	ext.execute_with(|| {
+		create_giveaway(2, 5);
+		RoundOutputs::<Test>::insert(5, [0; 32]);
+
+		run_offchain_worker(6);
+
+		assert!(submitted(&state).is_empty());
+	});
+}
+
+#[test]
+fn failed_fetch_releases_the_lock() {
+	let (mut ext, state) = ExtBuilder::default().build_offchain();
+	ext.execute_with(|| {
+		create_giveaway(2, 5);
+
+		expect_randomness_request(&state, 5, "req-1\n");
+		assert_eq!(
+			Giveaway::commit_results(RANDOMNESS_ENDPOINT.as_bytes(), 5),
+			Err(OffchainError::InvalidResponse)
+		);
+		assert!(submitted(&state).is_empty());
+
+		expect_randomness_request(&state, 5, &randomness_response("req-2", 5));
+		run_offchain_worker(6);
+
+		assert_eq!(submitted(&state).len(), 1);
+	});
+}
+
+#[test]
+fn offchain_worker_needs_an_endpoint() {
+	let (mut ext, state) = ExtBuilder::default().without_endpoint().build_offchain();
+	ext.execute_with(|| {
+		create_giveaway(2, 5);
+
+		run_offchain_worker(6);
+
+		assert!(submitted(&state).is_empty());
+	});
+}
+
+#[test]
+fn local_chain_giveaway_is_drawn_after_it_ends() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway_with(RandomType::LocalChain, 2, 5);
+		assert!(BlockToGiveaway::<Test>::get(5).is_empty());
+		assert_eq!(LocalDraws::<Test>::get(6).into_inner(), vec![index]);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		run_to_block(5);
+		assert_eq!(winners(index), vec![]);
+
+		run_to_block(6);
+		assert_eq!(winners(index), vec![PARTICIPANT]);
+		assert!(LocalDraws::<Test>::get(6).is_empty());
+	});
+}
+
+#[test]
+fn local_draws_and_timeouts_share_the_draw_budget() {
+	ExtBuilder::default().build().execute_with(|| {
+		let local = (0..3)
+			.map(|_| create_giveaway_with(RandomType::LocalChain, 2, 5))
+			.collect::<Vec<_>>();
+		let oracle = (0..3).map(|_| create_giveaway(2, 5)).collect::<Vec<_>>();
+		System::set_block_number(3);
+		for index in &local {
+			assert_ok!(Giveaway::participate(
+				RuntimeOrigin::signed(PARTICIPANT),
+				*index
+			));
+		}
+
+		// `MaxDrawsPerBlock` giveaways are drawn in each block, the rest in later ones.
+		run_to_block(6);
+		assert_eq!(winners(local[0]), vec![PARTICIPANT]);
+		assert_eq!(winners(local[1]), vec![PARTICIPANT]);
+		assert_eq!(winners(local[2]), vec![]);
+		assert_eq!(LocalDraws::<Test>::get(6).into_inner(), vec![local[2]]);
+		run_to_block(7);
+		assert_eq!(winners(local[2]), vec![PARTICIPANT]);
+		assert!(!LocalDraws::<Test>::contains_key(6));
+
+		run_to_block(15);
+		assert_eq!(
+			BlockToGiveaway::<Test>::get(5).into_inner(),
+			vec![oracle[2]]
+		);
+		run_to_block(16);
+		assert!(!BlockToGiveaway::<Test>::contains_key(5));
+		for index in oracle {
+			System::assert_has_event(RuntimeEvent::Giveaway(Event::RandomnessTimedOut {
+				index,
+				fallback: RandomnessFallback::Refund,
+			}));
+		}
+		assert_eq!(Giveaway::draw_page(RESULTS_PAGE), None);
+	});
+}
+
+#[test]
+fn local_draw_without_participants_goes_back_to_the_creator() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway_with(RandomType::LocalChain, 2, 5);
+		assert_eq!(Balances::free_balance(CREATOR), 899);
+
+		run_to_block(6);
+
+		assert_eq!(winners(index), vec![]);
+		assert_eq!(Balances::free_balance(CREATOR), 999);
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Expired)
+		);
+	});
+}
+
+#[test]
+fn asset_prize_goes_back_to_the_creator_when_nobody_joined() {
+	ExtBuilder::default().build().execute_with(|| {
+		create_asset(CREATOR, 500);
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_token_giveaway(ASSET, 200));
+		assert_eq!(Assets::balance(ASSET, CREATOR), 300);
+
+		run_to_block(6);
+
+		assert_eq!(Assets::balance(ASSET, CREATOR), 500);
+		assert_eq!(Winners::<Test>::get(index), None);
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Expired)
+		);
+	});
+}
+
+#[test]
+fn vrf_draw_waits_for_randomness_unknown_at_the_end() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway_with(RandomType::Vrf, 2, 5);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		RandomnessKnownSince::set(Some(5));
+		run_to_block(6);
+		assert_eq!(winners(index), vec![]);
+		assert_eq!(LocalDraws::<Test>::get(7).into_inner(), vec![index]);
+
+		RandomnessKnownSince::set(Some(7));
+		run_to_block(7);
+		assert_eq!(winners(index), vec![PARTICIPANT]);
+	});
+}
+
+#[test]
+fn unbiased_index_rehashes_the_biased_range() {
+	assert_eq!(Giveaway::unbiased_index(U256::from(7), 3), 1);
+	assert_eq!(Giveaway::unbiased_index(U256::MAX, 1), 0);
+	// 2^256 leaves a remainder of 1 by 3, so `U256::MAX` alone is rehashed.
+	let rehashed = U256::from_big_endian(&sp_io::hashing::blake2_256(&U256::MAX.encode()));
+	assert_eq!(
+		Giveaway::unbiased_index(U256::MAX, 3),
+		(rehashed % U256::from(3)).low_u32()
+	);
+	let below = U256::MAX - U256::one();
+	assert_eq!(
+		Giveaway::unbiased_index(below, 3),
+		(below % U256::from(3)).low_u32()
+	);
+	for len in [2, 5, 1_000, u32::MAX] {
+		assert!(Giveaway::unbiased_index(U256::from(u64::MAX), len) < len);
+	}
+}
+
+#[test]
+fn nft_giveaway_escrows_the_item_and_pays_the_winner() {
+	ExtBuilder::default().build().execute_with(|| {
+		mint_nft(CREATOR);
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_nft_giveaway(2, 5));
+		assert_eq!(nft_owner(), Some(Giveaway::escrow_account(index)));
+
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		run_to_block(6);
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		assert_eq!(nft_owner(), Some(PARTICIPANT));
+	});
+}
+
+#[test]
+fn nft_goes_back_to_the_creator_when_nobody_joined() {
+	ExtBuilder::default().build().execute_with(|| {
+		mint_nft(CREATOR);
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_nft_giveaway(2, 5));
+
+		run_to_block(6);
+
+		assert_eq!(nft_owner(), Some(CREATOR));
+		assert_eq!(winners(index), vec![]);
+	});
+}
+
+#[test]
+fn nft_giveaway_needs_the_item_of_the_creator() {
+	ExtBuilder::default().build().execute_with(|| {
+		mint_nft(PARTICIPANT);
+
+		assert_noop!(create_nft_giveaway(2, 5), Error::<Test>::NotNftOwner);
+		assert_noop!(
+			Giveaway::create_give_away(
+				RuntimeOrigin::signed(CREATOR),
+				b"giveaway".to_vec(),
+				2,
+				5,
+				0,
+				RandomType::LocalChain,
+				AssetType::NonFungibleToken,
+				None,
+				None,
+				PrizeSplit::default(),
+				Default::default(),
+				None,
+				WeightRule::Flat,
+				RandomnessFallback::Refund,
+				10,
+			),
+			Error::<Test>::PrizeMissing
+		);
+	});
+}
+
+#[test]
+fn asset_giveaway_escrows_the_asset_and_pays_the_winner() {
+	ExtBuilder::default().build().execute_with(|| {
+		create_asset(CREATOR, 500);
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_token_giveaway(ASSET, 100));
+		assert_eq!(Assets::balance(ASSET, CREATOR), 400);
+		assert_eq!(Assets::balance(ASSET, Giveaway::escrow_account(index)), 100);
+
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		run_to_block(6);
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		assert_eq!(Assets::balance(ASSET, PARTICIPANT), 100);
+		assert_eq!(Assets::balance(ASSET, Giveaway::escrow_account(index)), 0);
+	});
+}
+
+#[test]
+fn native_giveaway_escrows_the_currency() {
+	ExtBuilder::default().build().execute_with(|| {
+		let issuance = Balances::total_issuance();
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_token_giveaway(0, 100));
+
+		// The creator pays the prize on top of the pot deposit, nothing is minted.
+		assert_eq!(Balances::free_balance(CREATOR), 899);
+		assert_eq!(Balances::free_balance(Giveaway::escrow_account(index)), 101);
+		assert_eq!(Balances::total_issuance(), issuance);
+	});
+}
+
+#[test]
+fn token_giveaway_needs_an_existing_asset() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			create_token_giveaway(ASSET, 100),
+			Error::<Test>::UnknownAsset
+		);
+	});
+}
+
+#[test]
+fn token_giveaway_keeps_the_creator_alive() {
+	ExtBuilder::default().build().execute_with(|| {
+		create_asset(CREATOR, 500);
+
+		assert_noop!(
+			create_token_giveaway(ASSET, 500),
+			Error::<Test>::InsufficientBalance
+		);
+		assert_noop!(
+			create_token_giveaway(0, 1_000),
+			Error::<Test>::InsufficientBalance
+		);
+		assert_ok!(create_token_giveaway(ASSET, 499));
+	});
+}
+
+#[test]
+fn tiered_prizes_are_paid_by_rank() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_split_giveaway(tiered(&[50, 30, 20])));
+		System::set_block_number(3);
+		let players = [account(3), account(4), account(5)];
+		for player in &players {
+			assert_ok!(Giveaway::participate(
+				RuntimeOrigin::signed(player.clone()),
+				index
+			));
+		}
+		run_to_block(6);
+
+		let drawn = winners(index);
+		let mut sorted = drawn.clone();
+		sorted.sort();
+		assert_eq!(sorted, players.to_vec());
+		for (winner, prize) in drawn.iter().zip([50, 30, 20]) {
+			assert_ok!(Giveaway::claim_reward(
+				RuntimeOrigin::signed(winner.clone()),
+				index
+			));
+			assert_eq!(Balances::free_balance(winner), prize);
+		}
+		assert!(Winners::<Test>::get(index)
+			.unwrap()
+			.iter()
+			.all(|winner| winner.claimed));
+	});
+}
+
+#[test]
+fn prizes_nobody_won_go_to_the_creator() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_split_giveaway(PrizeSplit::Equal(3)));
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		run_to_block(6);
+
+		assert_eq!(winners(index), vec![PARTICIPANT, CREATOR, CREATOR]);
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(CREATOR),
+			index
+		));
+		assert_eq!(Balances::free_balance(PARTICIPANT), 1_034);
+		assert_eq!(Balances::free_balance(CREATOR), 965);
+	});
+}
+
+#[test]
+fn winners_claim_once() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway_with(RandomType::LocalChain, 2, 5);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		run_to_block(6);
+
+		assert_noop!(
+			Giveaway::claim_reward(RuntimeOrigin::signed(CREATOR), index),
+			Error::<Test>::NotWinner
+		);
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_noop!(
+			Giveaway::claim_reward(RuntimeOrigin::signed(PARTICIPANT), index),
+			Error::<Test>::AlreadyClaimed
+		);
+	});
+}
+
+#[test]
+fn prize_split_is_checked_on_creation() {
+	ExtBuilder::default().build().execute_with(|| {
+		for prize_split in [
+			PrizeSplit::Equal(0),
+			PrizeSplit::Equal(MAX_WINNERS + 1),
+			tiered(&[]),
+			tiered(&[50, 40]),
+			tiered(&[100, 0]),
+		] {
+			assert_noop!(
+				create_split_giveaway(prize_split),
+				Error::<Test>::InvalidPrizeSplit
+			);
+		}
+		mint_nft(CREATOR);
+		assert_noop!(
+			Giveaway::create_give_away(
+				RuntimeOrigin::signed(CREATOR),
+				b"giveaway".to_vec(),
+				2,
+				5,
+				0,
+				RandomType::LocalChain,
+				AssetType::NonFungibleToken,
+				None,
+				Some(NftInfo {
+					collection_id: COLLECTION,
+					nft_id: ITEM
+				}),
+				PrizeSplit::Equal(2),
+				Default::default(),
+				None,
+				WeightRule::Flat,
+				RandomnessFallback::Refund,
+				10,
+			),
+			Error::<Test>::InvalidPrizeSplit
+		);
+	});
+}
+
+#[test]
+fn winners_are_drawn_without_replacement() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway(2, 5);
+		System::set_block_number(3);
+		let players = (3..11).map(account).collect::<Vec<_>>();
+		for player in &players {
+			assert_ok!(Giveaway::participate(
+				RuntimeOrigin::signed(player.clone()),
+				index
+			));
+		}
+
+		for seed in 0..10u64 {
+			let random = U256::from(seed);
+			let mut drawn = Giveaway::draw_winners(index, random, 8, 8);
+			drawn.sort();
+			assert_eq!(drawn, players);
+			assert_eq!(Giveaway::draw_winners(index, random, 8, 3).len(), 3);
+			assert_eq!(
+				Giveaway::draw_winners(index, random, 8, 3),
+				Giveaway::draw_winners(index, random, 8, 8)[..3].to_vec()
+			);
+		}
+	});
+}
+
+#[test]
+fn prize_amounts_add_up_to_the_prize() {
+	assert_eq!(
+		(0..3)
+			.map(|rank| Giveaway::prize_amount(&tiered(&[50, 30, 20]), 101, rank))
+			.collect::<Vec<_>>(),
+		vec![51, 30, 20]
+	);
+	assert_eq!(
+		(0..3)
+			.map(|rank| Giveaway::prize_amount(&PrizeSplit::Equal(3), 100, rank))
+			.collect::<Vec<_>>(),
+		vec![34, 33, 33]
+	);
+}
+
+#[test]
+fn status_follows_the_giveaway_life() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway_with(RandomType::LocalChain, 2, 5);
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Scheduled)
+		);
+		assert_noop!(
+			Giveaway::participate(RuntimeOrigin::signed(PARTICIPANT), index),
+			Error::<Test>::GiveawayNotStarted
+		);
+
+		System::set_block_number(2);
+		assert_eq!(Giveaway::giveaway_status(index), Some(GiveawayStatus::Open));
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		System::set_block_number(6);
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::AwaitingRandomness)
+		);
+		assert_noop!(
+			Giveaway::participate(RuntimeOrigin::signed(CREATOR), index),
+			Error::<Test>::GiveawayEnded
+		);
+
+		Giveaway::on_initialize(6);
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Drawn)
+		);
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Claimed)
+		);
+		System::assert_last_event(
+			Event::StatusChanged {
+				index,
+				status: GiveawayStatus::Claimed,
+			}
+			.into(),
+		);
+	});
+}
+
+#[test]
+fn giveaways_are_settled_once() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway_with(RandomType::LocalChain, 2, 5);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		run_to_block(6);
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		Giveaway::settle(index, U256::zero(), &RequestId::default());
+
+		assert!(Winners::<Test>::get(index).unwrap()[0].claimed);
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Claimed)
+		);
+	});
+}
+
+#[test]
+fn nft_returned_to_the_creator_expires_the_giveaway() {
+	ExtBuilder::default().build().execute_with(|| {
+		mint_nft(CREATOR);
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_nft_giveaway(2, 5));
+
+		run_to_block(6);
+
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Expired)
+		);
+	});
+}
+
+#[test]
+fn each_giveaway_pays_out_of_its_own_escrow() {
+	ExtBuilder::default().build().execute_with(|| {
+		let first = create_giveaway_with(RandomType::LocalChain, 2, 5);
+		let second = create_giveaway_with(RandomType::LocalChain, 2, 8);
+		assert_ne!(
+			Giveaway::escrow_account(first),
+			Giveaway::escrow_account(second)
+		);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			first
+		));
+		run_to_block(6);
+
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(PARTICIPANT),
+			first
+		));
+
+		assert_eq!(Balances::free_balance(Giveaway::escrow_account(first)), 1);
+		assert_eq!(
+			Balances::free_balance(Giveaway::escrow_account(second)),
+			101
+		);
+	});
+}
+
+#[test]
+fn creator_cancels_before_the_start() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway_with(RandomType::LocalChain, 2, 5);
+		assert_eq!(Balances::free_balance(CREATOR), 899);
+
+		assert_noop!(
+			Giveaway::cancel_give_away(RuntimeOrigin::signed(PARTICIPANT), index),
+			Error::<Test>::NotCreator
+		);
+		assert_ok!(Giveaway::cancel_give_away(
+			RuntimeOrigin::signed(CREATOR),
+			index
+		));
+
+		assert_eq!(Balances::free_balance(CREATOR), 999);
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Cancelled)
+		);
+		assert!(LocalDraws::<Test>::get(6).is_empty());
+		run_to_block(6);
+		assert_eq!(winners(index), vec![]);
+	});
+}
+
+#[test]
+fn open_giveaways_are_cancelled_by_governance() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway(2, 5);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		assert_noop!(
+			Giveaway::cancel_give_away(RuntimeOrigin::signed(CREATOR), index),
+			Error::<Test>::CancelNeedsApproval
+		);
+		assert_ok!(Giveaway::cancel_give_away(
+			RuntimeOrigin::signed(authority()),
+			index
+		));
+
+		assert_eq!(Balances::free_balance(CREATOR), 999);
+		assert!(BlockToGiveaway::<Test>::get(5).is_empty());
+		System::set_block_number(6);
+		assert_noop!(
+			Giveaway::cancel_give_away(RuntimeOrigin::signed(authority()), index),
+			Error::<Test>::InvalidStatus
+		);
+	});
+}
+
+#[test]
+fn cancelled_nft_goes_back_to_the_creator() {
+	ExtBuilder::default().build().execute_with(|| {
+		mint_nft(CREATOR);
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_nft_giveaway(2, 5));
+
+		assert_ok!(Giveaway::cancel_give_away(
+			RuntimeOrigin::signed(CREATOR),
+			index
+		));
+
+		assert_eq!(nft_owner(), Some(CREATOR));
+	});
+}
+
+#[test]
+fn creator_edits_before_the_start() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway(2, 5);
+
+		assert_noop!(
+			Giveaway::edit_give_away(
+				RuntimeOrigin::signed(PARTICIPANT),
+				index,
+				None,
+				None,
+				Some(1)
+			),
+			Error::<Test>::NotCreator
+		);
+		assert_noop!(
+			Giveaway::edit_give_away(RuntimeOrigin::signed(CREATOR), index, None, Some(2), None),
+			Error::<Test>::EndBlockInvalid
+		);
+		assert_ok!(Giveaway::edit_give_away(
+			RuntimeOrigin::signed(CREATOR),
+			index,
+			Some(b"renamed".to_vec()),
+			Some(8),
+			Some(1),
+		));
+		assert!(BlockToGiveaway::<Test>::get(5).is_empty());
+		assert_eq!(BlockToGiveaway::<Test>::get(8).into_inner(), vec![index]);
+
+		TotalParticipantByGiveaway::<Test>::insert(index, 2);
+		assert_noop!(
+			Giveaway::edit_give_away(RuntimeOrigin::signed(CREATOR), index, None, None, Some(1)),
+			Error::<Test>::TooManyParticipants
+		);
+		TotalParticipantByGiveaway::<Test>::remove(index);
+
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_noop!(
+			Giveaway::participate(RuntimeOrigin::signed(CREATOR), index),
+			Error::<Test>::TooManyParticipants
+		);
+		assert_noop!(
+			Giveaway::edit_give_away(RuntimeOrigin::signed(CREATOR), index, None, None, Some(5)),
+			Error::<Test>::InvalidStatus
+		);
+	});
+}
+
+#[test]
+fn list_requirements_gate_entry() {
+	ExtBuilder::default().build().execute_with(|| {
+		ListMembers::set(vec![
+			(list(b"holders"), PARTICIPANT),
+			(list(b"banned"), account(4)),
+		]);
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_gated_giveaway(vec![
+			EntryRequirement::InList(list(b"holders")),
+			EntryRequirement::NotInList(list(b"banned")),
+		]));
+		System::set_block_number(3);
+
+		assert_noop!(
+			Giveaway::participate(RuntimeOrigin::signed(account(3)), index),
+			Error::<Test>::RequirementNotMet
+		);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		ListMembers::set(vec![
+			(list(b"holders"), account(4)),
+			(list(b"banned"), account(4)),
+		]);
+		assert_noop!(
+			Giveaway::participate(RuntimeOrigin::signed(account(4)), index),
+			Error::<Test>::RequirementNotMet
+		);
+	});
+}
+
+#[test]
+fn holding_requirements_gate_entry() {
+	ExtBuilder::default().build().execute_with(|| {
+		create_asset(PARTICIPANT, 50);
+		mint_nft(PARTICIPANT);
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_gated_giveaway(vec![
+			EntryRequirement::MinBalance(TokenInfo {
+				asset_id: 0,
+				amount: 500
+			}),
+			EntryRequirement::MinBalance(TokenInfo {
+				asset_id: ASSET,
+				amount: 50
+			}),
+			EntryRequirement::HoldsNft(COLLECTION),
+		]));
+		System::set_block_number(3);
+
+		assert_eq!(Giveaway::unmet_requirements(index, &PARTICIPANT), vec![]);
+		assert_eq!(
+			Giveaway::unmet_requirements(index, &CREATOR),
+			vec![
+				EntryRequirement::MinBalance(TokenInfo {
+					asset_id: ASSET,
+					amount: 50
+				}),
+				EntryRequirement::HoldsNft(COLLECTION),
+			]
+		);
+		assert_noop!(
+			Giveaway::participate(RuntimeOrigin::signed(CREATOR), index),
+			Error::<Test>::RequirementNotMet
+		);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+	});
+}
+
+#[test]
+fn account_age_gates_entry() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_gated_giveaway(vec![
+			EntryRequirement::MinAccountAge(3)
+		]));
+		System::set_block_number(3);
+		assert_ok!(Balances::transfer_allow_death(
+			RuntimeOrigin::signed(PARTICIPANT),
+			account(3),
+			10
+		));
+		assert_eq!(AccountCreatedAt::<Test>::get(account(3)), Some(3));
+
+		assert_noop!(
+			Giveaway::participate(RuntimeOrigin::signed(account(3)), index),
+			Error::<Test>::RequirementNotMet
+		);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		System::set_block_number(6);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(account(3)),
+			index
+		));
+	});
+}
+
+#[test]
+fn balance_requirements_need_an_existing_asset() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			create_gated_giveaway(vec![EntryRequirement::MinBalance(TokenInfo {
+				asset_id: ASSET,
+				amount: 1
+			})]),
+			Error::<Test>::UnknownAsset
+		);
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_gated_giveaway(vec![EntryRequirement::HoldsNft(
+			COLLECTION
+		)]));
+		assert_eq!(
+			Giveaway::entry_requirements(index),
+			Some(vec![EntryRequirement::HoldsNft(COLLECTION)])
+		);
+	});
+}
+
+#[test]
+fn entry_fees_go_to_the_prize() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_fee_giveaway(0, 10, FeeDestination::Prize);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_ok!(Giveaway::participate(RuntimeOrigin::signed(CREATOR), index));
+		assert_eq!(Balances::free_balance(PARTICIPANT), 990);
+		assert_eq!(CollectedFees::<Test>::get(index), 20);
+		run_to_block(6);
+
+		let winner = winners(index)[0].clone();
+		let before = Balances::free_balance(&winner);
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(winner.clone()),
+			index
+		));
+
+		assert_eq!(Balances::free_balance(&winner), before + 120);
+		assert_eq!(Balances::free_balance(Giveaway::escrow_account(index)), 1);
+	});
+}
+
+#[test]
+fn entry_fees_are_split_with_the_treasury() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_fee_giveaway(0, 50, FeeDestination::CreatorAndTreasury);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		run_to_block(6);
+
+		assert_eq!(Balances::free_balance(TREASURY), 5);
+		assert_eq!(Balances::free_balance(CREATOR), 944);
+		assert_eq!(CollectedFees::<Test>::get(index), 0);
+	});
+}
+
+#[test]
+fn asset_entry_fees_go_to_the_creator() {
+	ExtBuilder::default().build().execute_with(|| {
+		create_asset(PARTICIPANT, 50);
+		let index = create_fee_giveaway(ASSET, 20, FeeDestination::Creator);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_noop!(
+			Giveaway::participate(RuntimeOrigin::signed(CREATOR), index),
+			Error::<Test>::InsufficientBalance
+		);
+
+		run_to_block(6);
+
+		assert_eq!(Assets::balance(ASSET, PARTICIPANT), 30);
+		assert_eq!(Assets::balance(ASSET, CREATOR), 20);
+	});
+}
+
+#[test]
+fn entry_fees_are_refunded_on_cancel() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_fee_giveaway(0, 10, FeeDestination::Creator);
+		System::set_block_number(3);
+		let participants = [PARTICIPANT, account(3), account(4)];
+		for who in &participants[1..] {
+			assert_ok!(Balances::transfer_allow_death(
+				RuntimeOrigin::signed(authority()),
+				who.clone(),
+				100
+			));
+		}
+		for who in &participants {
+			assert_ok!(Giveaway::participate(
+				RuntimeOrigin::signed(who.clone()),
+				index
+			));
+		}
+
+		assert_ok!(Giveaway::cancel_give_away(
+			RuntimeOrigin::signed(authority()),
+			index
+		));
+		assert_eq!(Balances::free_balance(CREATOR), 999);
+		assert_eq!(Balances::free_balance(PARTICIPANT), 990);
+
+		// `MaxDrawsPerBlock` participants are refunded in each block.
+		run_to_block(4);
+		assert_eq!(Balances::free_balance(PARTICIPANT), 1_000);
+		assert_eq!(Balances::free_balance(account(3)), 100);
+		assert_eq!(Balances::free_balance(account(4)), 90);
+
+		// The rest are refunded with the weight left in the block.
+		Giveaway::on_idle(4, Weight::MAX);
+		assert_eq!(Balances::free_balance(account(4)), 100);
+		assert_eq!(CollectedFees::<Test>::get(index), 0);
+		assert_eq!(Giveaway::refund_page(RESULTS_PAGE), None);
+	});
+}
+
+#[test]
+fn entry_fees_are_checked_on_creation() {
+	ExtBuilder::default().build().execute_with(|| {
+		let fee = |asset_id, amount, destination| EntryFee {
+			token: TokenInfo { asset_id, amount },
+			destination,
+		};
+		assert_noop!(
+			create_charging_giveaway(fee(0, 0, FeeDestination::Creator)),
+			Error::<Test>::InvalidFee
+		);
+		assert_noop!(
+			create_charging_giveaway(fee(ASSET, 10, FeeDestination::Creator)),
+			Error::<Test>::UnknownAsset
+		);
+		create_asset(CREATOR, 50);
+		assert_noop!(
+			create_charging_giveaway(fee(ASSET, 10, FeeDestination::Prize)),
+			Error::<Test>::InvalidFee
+		);
+	});
+}
+
+#[test]
+fn holdings_give_entries() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_weighted_giveaway(
+			WeightRule::Holdings {
+				asset_id: 0,
+				unit: 100,
+				max: 5
+			},
+			None
+		));
+		assert_ok!(Balances::transfer_allow_death(
+			RuntimeOrigin::signed(PARTICIPANT),
+			account(3),
+			250
+		));
+		assert_ok!(Balances::transfer_allow_death(
+			RuntimeOrigin::signed(PARTICIPANT),
+			account(4),
+			50
+		));
+		System::set_block_number(3);
+
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(account(3)),
+			index
+		));
+		assert_noop!(
+			Giveaway::participate(RuntimeOrigin::signed(account(4)), index),
+			Error::<Test>::InvalidEntries
+		);
+
+		assert_eq!(Giveaway::entries_at(index, 0), 5);
+		assert_eq!(Giveaway::entries_at(index, 1), 2);
+		assert_eq!(CumulativeEntries::<Test>::get(index, 1), 7);
+		assert_eq!(Balances::free_balance(PARTICIPANT), 200);
+		assert_eq!(Balances::free_balance(account(3)), 50);
+	});
+}
+
+#[test]
+fn holdings_are_staked_until_the_draw() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_weighted_giveaway(
+			WeightRule::Holdings {
+				asset_id: 0,
+				unit: 100,
+				max: 5
+			},
+			None
+		));
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		// The staked tokens cannot join again from another account.
+		assert_eq!(Balances::free_balance(PARTICIPANT), 500);
+		assert!(Balances::transfer_allow_death(
+			RuntimeOrigin::signed(PARTICIPANT),
+			account(3),
+			600
+		)
+		.is_err());
+		assert_ok!(Balances::transfer_allow_death(
+			RuntimeOrigin::signed(PARTICIPANT),
+			account(3),
+			400
+		));
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(account(3)),
+			index
+		));
+		assert_eq!(Giveaway::entries_at(index, 1), 3);
+		assert_eq!(Balances::free_balance(account(3)), 100);
+
+		run_to_block(6);
+		run_to_block(7);
+
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Drawn)
+		);
+		assert_eq!(Balances::free_balance(PARTICIPANT), 600);
+		assert_eq!(Balances::free_balance(account(3)), 400);
+		assert_ok!(Giveaway::claim_reward(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_eq!(Balances::free_balance(PARTICIPANT), 650);
+	});
+}
+
+#[test]
+fn holdings_stakes_are_refunded_on_cancel() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_weighted_giveaway(
+			WeightRule::Holdings {
+				asset_id: 0,
+				unit: 100,
+				max: 5
+			},
+			None
+		));
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_eq!(Balances::free_balance(PARTICIPANT), 500);
+
+		assert_ok!(Giveaway::cancel_give_away(
+			RuntimeOrigin::signed(authority()),
+			index
+		));
+		run_to_block(4);
+
+		assert_eq!(Balances::free_balance(PARTICIPANT), 1_000);
+	});
+}
+
+#[test]
+fn extra_entries_are_bought_at_the_entry_fee() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_weighted_giveaway(
+			WeightRule::Purchased { max: 3 },
+			native_fee(10)
+		));
+		let flat = create_giveaway(2, 5);
+		System::set_block_number(3);
+
+		assert_noop!(
+			Giveaway::participate_with_entries(RuntimeOrigin::signed(PARTICIPANT), index, 3),
+			Error::<Test>::InvalidEntries
+		);
+		assert_noop!(
+			Giveaway::participate_with_entries(RuntimeOrigin::signed(PARTICIPANT), flat, 1),
+			Error::<Test>::InvalidEntries
+		);
+		assert_ok!(Giveaway::participate_with_entries(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index,
+			2
+		));
+		assert_eq!(Balances::free_balance(PARTICIPANT), 970);
+		assert_eq!(Giveaway::entries_at(index, 0), 3);
+
+		assert_ok!(Giveaway::cancel_give_away(
+			RuntimeOrigin::signed(authority()),
+			index
+		));
+		run_to_block(4);
+		assert_eq!(Balances::free_balance(PARTICIPANT), 1_000);
+	});
+}
+
+#[test]
+fn weight_rules_are_checked_on_creation() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			create_weighted_giveaway(
+				WeightRule::Holdings {
+					asset_id: 0,
+					unit: 0,
+					max: 5
+				},
+				None
+			),
+			Error::<Test>::InvalidWeightRule
+		);
+		assert_noop!(
+			create_weighted_giveaway(
+				WeightRule::Holdings {
+					asset_id: ASSET,
+					unit: 1,
+					max: 5
+				},
+				None
+			),
+			Error::<Test>::UnknownAsset
+		);
+		assert_noop!(
+			create_weighted_giveaway(WeightRule::Purchased { max: 3 }, None),
+			Error::<Test>::InvalidWeightRule
+		);
+	});
+}
+
+#[test]
+fn weighted_winners_are_drawn_by_entries() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = GiveawayIndex::<Test>::get();
+		assert_ok!(create_weighted_giveaway(
+			WeightRule::Purchased { max: 3 },
+			native_fee(1)
+		));
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(RuntimeOrigin::signed(CREATOR), index));
+		assert_ok!(Giveaway::participate_with_entries(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index,
+			2
+		));
+
+		let mut first = 0;
+		for seed in 0..400u64 {
+			let drawn = Giveaway::draw_weighted_winners(index, U256::from(seed), 2, 2);
+			assert_eq!(drawn.len(), 2);
+			assert_ne!(drawn[0], drawn[1]);
+			if drawn[0] == PARTICIPANT {
+				first += 1;
+			}
+		}
+		// Three entries out of four.
+		assert!((260..340).contains(&first), "drawn first {} times", first);
+
+		run_to_block(6);
+		let mut drawn = winners(index);
+		drawn.sort();
+		assert_eq!(drawn, vec![CREATOR, PARTICIPANT]);
+	});
+}
+
+#[test]
+fn oracles_bond_to_register() {
+	ExtBuilder::default().build().execute_with(|| {
+		let oracle = register_oracle(3);
+		assert_eq!(Balances::reserved_balance(&oracle), 10);
+		assert_noop!(
+			Giveaway::register_oracle(RuntimeOrigin::signed(oracle.clone()), vrf_proof(3, 0).0),
+			Error::<Test>::AlreadyOracle
+		);
+		for seed in 4..7 {
+			register_oracle(seed);
+		}
+		assert_eq!(Oracles::<Test>::count(), 4);
+		assert_noop!(
+			Giveaway::register_oracle(RuntimeOrigin::signed(PARTICIPANT), [0; 33]),
+			Error::<Test>::InvalidVrfKey
+		);
+		// The set is full, so the application waits.
+		assert_ok!(Giveaway::register_oracle(
+			RuntimeOrigin::signed(PARTICIPANT),
+			vrf_proof(2, 0).0
+		));
+		assert_noop!(
+			Giveaway::approve_oracle(RuntimeOrigin::signed(authority()), PARTICIPANT),
+			Error::<Test>::TooManyOracles
+		);
+
+		assert_ok!(Giveaway::unregister_oracle(RuntimeOrigin::signed(
+			oracle.clone()
+		)));
+		assert_eq!(Balances::reserved_balance(&oracle), 0);
+		assert_noop!(
+			Giveaway::unregister_oracle(RuntimeOrigin::signed(oracle)),
+			Error::<Test>::NotOracle
+		);
+	});
+}
+
+#[test]
+fn oracles_need_approval() {
+	ExtBuilder::default().build().execute_with(|| {
+		let applicant = apply_oracle(3);
+		assert_eq!(Balances::reserved_balance(&applicant), 10);
+		assert!(!Oracles::<Test>::contains_key(&applicant));
+		assert_noop!(
+			Giveaway::register_oracle(RuntimeOrigin::signed(applicant.clone()), vrf_proof(3, 0).0),
+			Error::<Test>::AlreadyApplied
+		);
+		assert_noop!(
+			Giveaway::approve_oracle(RuntimeOrigin::signed(applicant.clone()), applicant.clone()),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Giveaway::approve_oracle(RuntimeOrigin::signed(authority()), PARTICIPANT),
+			Error::<Test>::NotApplied
+		);
+
+		System::set_block_number(4);
+		assert_ok!(Giveaway::approve_oracle(
+			RuntimeOrigin::signed(authority()),
+			applicant.clone()
+		));
+
+		System::assert_last_event(RuntimeEvent::Giveaway(Event::OracleRegistered {
+			who: applicant.clone(),
+		}));
+		assert_eq!(
+			Oracles::<Test>::get(&applicant).map(|oracle| (oracle.bond, oracle.since)),
+			Some((10, 4))
+		);
+		assert!(!OracleApplications::<Test>::contains_key(&applicant));
+	});
+}
+
+#[test]
+fn oracle_applications_are_capped_and_can_be_withdrawn() {
+	ExtBuilder::default().build().execute_with(|| {
+		let applicants = (3..7).map(apply_oracle).collect::<Vec<_>>();
+		assert_noop!(
+			Giveaway::register_oracle(RuntimeOrigin::signed(PARTICIPANT), vrf_proof(2, 0).0),
+			Error::<Test>::TooManyApplications
+		);
+
+		assert_ok!(Giveaway::reject_oracle(
+			RuntimeOrigin::signed(authority()),
+			applicants[0].clone()
+		));
+		assert_ok!(Giveaway::unregister_oracle(RuntimeOrigin::signed(
+			applicants[1].clone()
+		)));
+
+		assert_eq!(Balances::reserved_balance(&applicants[0]), 0);
+		assert_eq!(Balances::reserved_balance(&applicants[1]), 0);
+		assert_eq!(OracleApplications::<Test>::count(), 2);
+		assert_noop!(
+			Giveaway::reject_oracle(RuntimeOrigin::signed(authority()), applicants[1].clone()),
+			Error::<Test>::NotApplied
+		);
+		assert_eq!(Oracles::<Test>::count(), 0);
+	});
+}
+
+#[test]
+fn oracle_results_are_combined_by_xor() {
+	ExtBuilder::default().build().execute_with(|| {
+		OracleThreshold::set(2);
+		let first = register_oracle(3);
+		register_oracle(4);
+		let index = create_giveaway(2, 5);
+
+		System::set_block_number(6);
+		assert_ok!(commit(3, 5, 1));
+		assert_ok!(commit(4, 5, 2));
+		System::set_block_number(8);
+		assert_ok!(reveal(3, 5, 1));
+		assert_ok!(reveal(4, 5, 2));
+
+		run_to_block(10);
+		let mut combined = vrf_output(3, 5);
+		for (combined, byte) in combined.iter_mut().zip(vrf_output(4, 5)) {
+			*combined ^= byte;
+		}
+		assert_eq!(RoundOutputs::<Test>::get(5), Some(combined));
+		assert_eq!(
+			BlockToResults::<Test>::get(5, 0).map(|(_, results)| results.into_inner()),
+			Some(vec![vrf::random_word(&combined, index)])
+		);
+		assert!(!BlockToGiveaway::<Test>::contains_key(5));
+		assert_eq!(Commitments::<Test>::iter_prefix(5).count(), 0);
+		assert_eq!(
+			Oracles::<Test>::get(first).map(|oracle| oracle.commitments),
+			Some(0)
+		);
+	});
+}
+
+#[test]
+fn reveals_must_match_their_commitment() {
+	ExtBuilder::default().build().execute_with(|| {
+		register_oracle(3);
+		create_giveaway(2, 5);
+		create_giveaway(2, 6);
+
+		System::set_block_number(5);
+		assert_noop!(commit(3, 5, 1), Error::<Test>::NotCommitPhase);
+		System::set_block_number(6);
+		assert_noop!(commit(2, 5, 1), Error::<Test>::NotOracle);
+		assert_noop!(commit(3, 4, 1), Error::<Test>::InvalidRound);
+		assert_ok!(commit(3, 5, 1));
+		assert_noop!(commit(3, 5, 1), Error::<Test>::AlreadyCommitted);
+		assert_noop!(reveal(3, 5, 1), Error::<Test>::NotRevealPhase);
+		System::set_block_number(8);
+		assert_noop!(reveal(3, 5, 2), Error::<Test>::InvalidReveal);
+		assert_ok!(reveal(3, 5, 1));
+		assert_noop!(reveal(3, 5, 1), Error::<Test>::AlreadyRevealed);
+
+		System::set_block_number(9);
+		assert_noop!(commit(3, 6, 1), Error::<Test>::NotCommitPhase);
+		assert_noop!(reveal(3, 6, 1), Error::<Test>::NotCommitted);
+	});
+}
+
+#[test]
+fn absent_oracles_get_strikes_and_silent_ones_lose_their_bond() {
+	ExtBuilder::default().build().execute_with(|| {
+		let (honest, silent, absent) = (register_oracle(3), register_oracle(4), register_oracle(5));
+		let index = create_giveaway(2, 5);
+
+		System::set_block_number(6);
+		assert_ok!(commit(3, 5, 1));
+		assert_ok!(commit(4, 5, 2));
+		assert_noop!(
+			Giveaway::unregister_oracle(RuntimeOrigin::signed(silent.clone())),
+			Error::<Test>::OracleBusy
+		);
+		System::set_block_number(8);
+		assert_ok!(reveal(3, 5, 1));
+		run_to_block(10);
+
+		// Nobody joined, so the prize went back to the creator.
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Expired)
+		);
+		let strikes = |who: &AccountId| Oracles::<Test>::get(who).map(|oracle| oracle.missed);
+		assert_eq!(strikes(&honest), Some(0));
+		assert_eq!(strikes(&absent), Some(1));
+		assert!(!Oracles::<Test>::contains_key(&silent));
+		assert_eq!(Balances::free_balance(&silent), 90);
+		assert_eq!(Balances::reserved_balance(&silent), 0);
+		assert_eq!(Balances::free_balance(TREASURY), 10);
+		System::assert_has_event(RuntimeEvent::Giveaway(Event::OracleSlashed {
+			who: silent,
+			amount: 10,
+		}));
+		assert_noop!(
+			Giveaway::unregister_oracle(RuntimeOrigin::signed(absent.clone())),
+			Error::<Test>::OracleBusy
+		);
+		assert_ok!(Giveaway::unregister_oracle(RuntimeOrigin::signed(honest)));
+
+		assert_noop!(
+			Giveaway::slash_oracle(RuntimeOrigin::signed(CREATOR), absent.clone(), 4),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(Giveaway::slash_oracle(
+			RuntimeOrigin::signed(authority()),
+			absent.clone(),
+			4
+		));
+		assert_eq!(Balances::free_balance(&absent), 96);
+		assert_eq!(Balances::reserved_balance(&absent), 0);
+		assert_eq!(Balances::free_balance(TREASURY), 14);
+		assert!(!Oracles::<Test>::contains_key(&absent));
+		System::assert_last_event(RuntimeEvent::Giveaway(Event::OracleSlashed {
+			who: absent,
+			amount: 4,
+		}));
+	});
+}
+
+#[test]
+fn too_few_reveals_leave_giveaways_awaiting_randomness() {
+	ExtBuilder::default().build().execute_with(|| {
+		OracleThreshold::set(2);
+		register_oracle(3);
+		register_oracle(4);
+		let index = create_giveaway(2, 5);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		System::set_block_number(6);
+		assert_ok!(commit(3, 5, 1));
+		assert_ok!(commit(4, 5, 2));
+		System::set_block_number(8);
+		assert_ok!(reveal(3, 5, 1));
+		run_to_block(10);
+
+		System::assert_last_event(RuntimeEvent::Giveaway(Event::OracleRoundFailed {
+			block: 5,
+			reveals: 1,
+		}));
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::AwaitingRandomness)
+		);
+		assert_eq!(winners(index), vec![]);
+		assert!(!RoundOutputs::<Test>::contains_key(5));
+	});
+}
+
+#[test]
+fn full_buckets_overflow_to_later_blocks() {
+	ExtBuilder::default().build().execute_with(|| {
+		let indexes = (0..9).map(|_| create_giveaway(2, 5)).collect::<Vec<_>>();
+
+		assert_eq!(
+			BlockToGiveaway::<Test>::get(5).into_inner(),
+			indexes[..8].to_vec()
+		);
+		assert_eq!(
+			BlockToGiveaway::<Test>::get(6).into_inner(),
+			vec![indexes[8]]
+		);
+
+		assert_ok!(Giveaway::cancel_give_away(
+			RuntimeOrigin::signed(CREATOR),
+			indexes[8]
+		));
+		assert!(!BlockToGiveaway::<Test>::contains_key(6));
+	});
+}
+
+#[test]
+fn oracle_rounds_are_drawn_in_pages() {
+	ExtBuilder::default().build().execute_with(|| {
+		register_oracle(3);
+		let indexes = (0..5).map(|_| create_giveaway(2, 5)).collect::<Vec<_>>();
+		System::set_block_number(3);
+		for index in &indexes {
+			assert_ok!(Giveaway::participate(
+				RuntimeOrigin::signed(PARTICIPANT),
+				*index
+			));
+		}
+
+		System::set_block_number(6);
+		assert_ok!(commit(3, 5, 1));
+		System::set_block_number(8);
+		assert_ok!(reveal(3, 5, 1));
+
+		// `MaxDrawsPerBlock` giveaways are drawn in each block.
+		let output = vrf_output(3, 5);
+		run_to_block(10);
+		assert_eq!(
+			BlockToResults::<Test>::get(5, 0).map(|(_, results)| results.into_inner()),
+			Some(vec![
+				vrf::random_word(&output, indexes[0]),
+				vrf::random_word(&output, indexes[1])
+			])
+		);
+		assert_eq!(
+			Giveaway::giveaway_status(indexes[2]),
+			Some(GiveawayStatus::AwaitingRandomness)
+		);
+		run_to_block(11);
+		assert!(BlockToResults::<Test>::contains_key(5, 1));
+		assert_eq!(
+			BlockToGiveaway::<Test>::get(5).into_inner(),
+			vec![indexes[4]]
+		);
+
+		// The rest are drawn with the weight left in the block.
+		Giveaway::on_idle(11, Weight::MAX);
+		assert!(BlockToResults::<Test>::contains_key(5, 2));
+		for index in indexes {
+			assert_eq!(winners(index), vec![PARTICIPANT]);
+		}
+		assert!(!BlockToGiveaway::<Test>::contains_key(5));
+		assert_eq!(Giveaway::draw_page(RESULTS_PAGE), None);
+	});
+}
+
+#[test]
+fn search_depth_counts_the_bits_of_the_participants() {
+	assert_eq!(Giveaway::search_depth(0), 0);
+	assert_eq!(Giveaway::search_depth(1), 1);
+	assert_eq!(Giveaway::search_depth(1_023), 10);
+	assert_eq!(Giveaway::search_depth(1_024), 11);
+	assert_eq!(Giveaway::search_depth(u32::MAX), 32);
+}
+
+#[test]
+fn empty_draw_pages_leave_the_budget_to_the_next_round() {
+	ExtBuilder::default().build().execute_with(|| {
+		register_oracle(3);
+		let indexes = (0..2).map(|_| create_giveaway(2, 5)).collect::<Vec<_>>();
+		System::set_block_number(6);
+		assert_ok!(commit(3, 5, 1));
+		System::set_block_number(8);
+		assert_ok!(reveal(3, 5, 1));
+		// A round whose giveaways are gone by the time it is drawn.
+		DrawQueue::<Test>::insert(0, (Draw::Round(4), 0));
+		DrawQueueRange::<Test>::put((0, 1));
+
+		run_to_block(10);
+
+		assert!(!DrawQueue::<Test>::contains_key(0));
+		for index in indexes {
+			assert_eq!(
+				Giveaway::giveaway_status(index),
+				Some(GiveawayStatus::Expired)
+			);
+		}
+	});
+}
+
+#[test]
+fn giveaways_without_results_are_refunded_on_timeout() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway(2, 5);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		assert_eq!(Balances::free_balance(CREATOR), 899);
+
+		run_to_block(14);
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::AwaitingRandomness)
+		);
+		run_to_block(15);
+
+		System::assert_has_event(RuntimeEvent::Giveaway(Event::RandomnessTimedOut {
+			index,
+			fallback: RandomnessFallback::Refund,
+		}));
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Expired)
+		);
+		assert_eq!(Balances::free_balance(CREATOR), 999);
+		assert_eq!(winners(index), vec![]);
+		assert!(!BlockToGiveaway::<Test>::contains_key(5));
+	});
+}
+
+#[test]
+fn vrf_draw_is_refunded_when_it_cannot_be_deferred() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway_with(RandomType::Vrf, 2, 5);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		// Every block the draw could be deferred to is full.
+		for block in 7..15 {
+			LocalDraws::<Test>::insert(block, BoundedVec::truncate_from(vec![u32::MAX; 8]));
+		}
+
+		RandomnessKnownSince::set(Some(5));
+		run_to_block(6);
+
+		assert_eq!(winners(index), vec![]);
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Expired)
+		);
+		assert_eq!(Balances::free_balance(CREATOR), 999);
+	});
+}
+
+#[test]
+fn failed_refunds_wait_for_a_retry() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway(2, 5);
+		let escrow = Giveaway::escrow_account(index);
+		let escrowed = Balances::free_balance(&escrow);
+		assert_ok!(Balances::force_set_balance(
+			RuntimeOrigin::root(),
+			escrow.clone(),
+			1
+		));
+
+		run_to_block(15);
+
+		System::assert_has_event(RuntimeEvent::Giveaway(Event::RefundStalled {
+			index,
+			error: TokenError::FundsUnavailable.into(),
+		}));
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::AwaitingRandomness)
+		);
+		assert_eq!(StalledRefunds::<Test>::get(index), Some(0));
+		assert_eq!(Giveaway::refund_page(RESULTS_PAGE), None);
+
+		assert_noop!(
+			Giveaway::retry_refund(RuntimeOrigin::signed(PARTICIPANT), index + 1),
+			Error::<Test>::RefundNotStalled
+		);
+		assert_ok!(Balances::force_set_balance(
+			RuntimeOrigin::root(),
+			escrow,
+			escrowed
+		));
+		assert_ok!(Giveaway::retry_refund(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+		run_to_block(16);
+
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Expired)
+		);
+		assert_eq!(Balances::free_balance(CREATOR), 999);
+		assert!(!StalledRefunds::<Test>::contains_key(index));
+	});
+}
+
+#[test]
+fn giveaways_without_results_can_fall_back_to_local_randomness() {
+	ExtBuilder::default().build().execute_with(|| {
+		let index = create_giveaway_falling_back(
+			RandomType::Chainlink,
+			RandomnessFallback::LocalRandomness,
+			2,
+			5,
+		);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		run_to_block(15);
+
+		System::assert_has_event(RuntimeEvent::Giveaway(Event::RandomnessTimedOut {
+			index,
+			fallback: RandomnessFallback::LocalRandomness,
+		}));
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Drawn)
+		);
+		assert_eq!(winners(index), vec![PARTICIPANT]);
+	});
+}
+
+#[test]
+fn drawn_giveaways_do_not_time_out() {
+	ExtBuilder::default().build().execute_with(|| {
+		register_oracle(3);
+		let index = create_giveaway(2, 5);
+		System::set_block_number(3);
+		assert_ok!(Giveaway::participate(
+			RuntimeOrigin::signed(PARTICIPANT),
+			index
+		));
+
+		System::set_block_number(6);
+		assert_ok!(commit(3, 5, 1));
+		System::set_block_number(8);
+		assert_ok!(reveal(3, 5, 1));
+		run_to_block(10);
+		run_to_block(15);
+
+		assert!(!System::events().iter().any(|record| matches!(
+			record.event,
+			RuntimeEvent::Giveaway(Event::RandomnessTimedOut { .. })
+		)));
+		assert_eq!(
+			Giveaway::giveaway_status(index),
+			Some(GiveawayStatus::Drawn)
+		);
+	});
+}
+
+#[test]
+fn reveals_are_checked_against_the_oracle_key() {
+	ExtBuilder::default().build().execute_with(|| {
+		let (mismatched, stale) = (register_oracle(3), register_oracle(5));
+		create_giveaway(2, 5);
+		// A proof of another key, and one of the own key for another input.
+		let (other_key, other_proof) = vrf_proof(4, 5);
+		let (key, stale_proof) = vrf_proof(5, 6);
+
+		System::set_block_number(6);
+		assert_ok!(Giveaway::commit_block_result(
+			RuntimeOrigin::signed(mismatched.clone()),
+			5,
+			Giveaway::commitment(&mismatched, 5, &other_proof, &[1; 32])
+		));
+		assert_ok!(Giveaway::commit_block_result(
+			RuntimeOrigin::signed(stale.clone()),
+			5,
+			Giveaway::commitment(&stale, 5, &stale_proof, &[1; 32])
+		));
+
+		System::set_block_number(8);
+		assert_noop!(
+			Giveaway::reveal_block_result(
+				RuntimeOrigin::signed(mismatched),
+				5,
+				other_key,
+				other_proof,
+				[1; 32]
+			),
+			Error::<Test>::VrfKeyMismatch
+		);
+		assert_noop!(
+			Giveaway::reveal_block_result(
+				RuntimeOrigin::signed(stale),
+				5,
+				key,
+				stale_proof,
+				[1; 32]
+			),
+			Error::<Test>::InvalidVrfProof
+		);
+	});
+}
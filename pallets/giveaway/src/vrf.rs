@@ -0,0 +1,137 @@
+//! ECVRF over secp256k1 with SHA-256 and try-and-increment hashing to the curve, known as
+//! `ECVRF-SECP256K1-SHA256-TAI`: the `ECVRF-P256-SHA256-TAI` suite of RFC 9381 on secp256k1, with
+//! suite string `0xfe`.
+//!
+//! Oracles prove the randomness they submit with it: the output of a proof is fixed by their key
+//! and the input, so they can only choose whether to reveal it.
+
+use k256::{
+	elliptic_curve::{
+		sec1::{FromEncodedPoint, ToEncodedPoint},
+		PrimeField,
+	},
+	AffinePoint, EncodedPoint, ProjectivePoint, Scalar,
+};
+use scale_codec::Encode;
+use sp_core::U256;
+use sp_io::hashing::{blake2_256, sha2_256};
+
+/// The length of a compressed point.
+const POINT_LEN: usize = 33;
+
+/// The length of the challenge of a proof.
+const CHALLENGE_LEN: usize = 16;
+
+/// The length of a proof: `Gamma`, the challenge `c` and the response `s`.
+pub const PROOF_LEN: usize = POINT_LEN + CHALLENGE_LEN + 32;
+
+const SUITE: u8 = 0xfe;
+
+/// A compressed SEC1 public key.
+pub type VrfPublic = [u8; POINT_LEN];
+
+/// A proof of the output for an input, see [`PROOF_LEN`].
+pub type VrfProof = [u8; PROOF_LEN];
+
+/// The output of a proof.
+pub type VrfOutput = [u8; 32];
+
+/// Whether `public` is a compressed point of the curve.
+pub fn is_valid_key(public: &VrfPublic) -> bool {
+	decode_point(public).is_some()
+}
+
+/// The output of `proof` if the holder of `public` made it for `alpha`.
+pub fn verify(public: &VrfPublic, alpha: &[u8], proof: &VrfProof) -> Option<VrfOutput> {
+	let y = ProjectivePoint::from(decode_point(public)?);
+	let h = hash_to_curve(public, alpha)?;
+	let (gamma, rest) = proof.split_at(POINT_LEN);
+	let (c, s) = rest.split_at(CHALLENGE_LEN);
+	let gamma = ProjectivePoint::from(decode_point(gamma)?);
+	let mut c_bytes = [0u8; 32];
+	c_bytes[32 - CHALLENGE_LEN..].copy_from_slice(c);
+	let c_scalar = decode_scalar(c_bytes)?;
+	let s = decode_scalar(s.try_into().ok()?)?;
+
+	let u = ProjectivePoint::GENERATOR * s - y * c_scalar;
+	let v = h * s - gamma * c_scalar;
+	(challenge(&[y, h, gamma, u, v])[..] == *c).then(|| output(&gamma))
+}
+
+/// A uniformly distributed number derived from `output` for giveaway `index`.
+pub fn random_word(output: &VrfOutput, index: u32) -> U256 {
+	U256::from_big_endian(&blake2_256(&(output, index).encode()))
+}
+
+/// Prove `alpha` with `secret`. The nonce is derived from the secret and the hashed input, so
+/// proofs are deterministic.
+#[cfg(test)]
+pub fn prove(secret: &Scalar, alpha: &[u8]) -> (VrfPublic, VrfProof) {
+	let public: VrfPublic = encode(&(ProjectivePoint::GENERATOR * secret))
+		.as_bytes()
+		.try_into()
+		.expect("Public keys are not the identity");
+	let h = hash_to_curve(&public, alpha).expect("Test inputs hash to the curve");
+	let gamma = h * secret;
+	let nonce = decode_scalar(sha2_256(
+		&[&secret.to_bytes()[..], encode(&h).as_bytes()].concat(),
+	))
+	.expect("The nonce is below the order");
+	let c = challenge(&[
+		ProjectivePoint::GENERATOR * secret,
+		h,
+		gamma,
+		ProjectivePoint::GENERATOR * nonce,
+		h * nonce,
+	]);
+	let mut c_bytes = [0u8; 32];
+	c_bytes[32 - CHALLENGE_LEN..].copy_from_slice(&c);
+	let s = nonce + decode_scalar(c_bytes).expect("Challenges are below the order") * secret;
+
+	let mut proof = [0u8; PROOF_LEN];
+	proof[..POINT_LEN].copy_from_slice(encode(&gamma).as_bytes());
+	proof[POINT_LEN..POINT_LEN + CHALLENGE_LEN].copy_from_slice(&c);
+	proof[POINT_LEN + CHALLENGE_LEN..].copy_from_slice(&s.to_bytes());
+	(public, proof)
+}
+
+/// Hash `alpha` to the curve by trying `0x02 || SHA-256(..)` as compressed points.
+fn hash_to_curve(public: &VrfPublic, alpha: &[u8]) -> Option<ProjectivePoint> {
+	(0..=u8::MAX).find_map(|counter| {
+		let hash =
+			sha2_256(&[&[SUITE, 0x01][..], &public[..], alpha, &[counter, 0x00][..]].concat());
+		let mut point = [0x02; POINT_LEN];
+		point[1..].copy_from_slice(&hash);
+		decode_point(&point).map(ProjectivePoint::from)
+	})
+}
+
+fn challenge(points: &[ProjectivePoint; 5]) -> [u8; CHALLENGE_LEN] {
+	let mut input = sp_std::vec![SUITE, 0x02];
+	for point in points {
+		input.extend_from_slice(encode(point).as_bytes());
+	}
+	input.push(0x00);
+	let mut challenge = [0u8; CHALLENGE_LEN];
+	challenge.copy_from_slice(&sha2_256(&input)[..CHALLENGE_LEN]);
+	challenge
+}
+
+fn output(gamma: &ProjectivePoint) -> VrfOutput {
+	sha2_256(&[&[SUITE, 0x03][..], encode(gamma).as_bytes(), &[0x00][..]].concat())
+}
+
+fn decode_point(bytes: &[u8]) -> Option<AffinePoint> {
+	let point = EncodedPoint::from_bytes(bytes)
+		.ok()
+		.filter(|point| point.is_compressed())?;
+	AffinePoint::from_encoded_point(&point).into()
+}
+
+fn decode_scalar(bytes: [u8; 32]) -> Option<Scalar> {
+	Scalar::from_repr(bytes.into()).into()
+}
+
+fn encode(point: &ProjectivePoint) -> EncodedPoint {
+	AffinePoint::from(*point).to_encoded_point(true)
+}
@@ -0,0 +1,53 @@
+//! Weights for pallet_ocw_giveaway.
+//!
+//! Regenerate with `scripts/benchmark.sh pallet_ocw_giveaway <extrinsic>` on the reference
+//! machine after changing the draws.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_ocw_giveaway.
+pub trait WeightInfo {
+	fn settle(d: u32) -> Weight;
+}
+
+/// Weights for pallet_ocw_giveaway using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: `Giveaway::Giveaway` (r:1 w:1)
+	/// Storage: `Giveaway::TotalParticipantByGiveaway` (r:1 w:0)
+	/// Storage: `Giveaway::CumulativeEntries` (r:16d+16 w:0)
+	/// Storage: `Giveaway::Participants` (r:16 w:0)
+	/// Storage: `Giveaway::Winners` (r:0 w:1)
+	/// The range of component `d` is `[1, 12]`.
+	fn settle(d: u32) -> Weight {
+		Weight::from_parts(64_000_000, 10_470)
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(d.into()))
+			.saturating_add(T::DbWeight::get().reads(34_u64))
+			.saturating_add(T::DbWeight::get().reads((16_u64).saturating_mul(d.into())))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(Weight::from_parts(0, 8_160).saturating_mul(d.into()))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	/// Storage: `Giveaway::Giveaway` (r:1 w:1)
+	/// Storage: `Giveaway::TotalParticipantByGiveaway` (r:1 w:0)
+	/// Storage: `Giveaway::CumulativeEntries` (r:16d+16 w:0)
+	/// Storage: `Giveaway::Participants` (r:16 w:0)
+	/// Storage: `Giveaway::Winners` (r:0 w:1)
+	/// The range of component `d` is `[1, 12]`.
+	fn settle(d: u32) -> Weight {
+		Weight::from_parts(64_000_000, 10_470)
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(d.into()))
+			.saturating_add(RocksDbWeight::get().reads(34_u64))
+			.saturating_add(RocksDbWeight::get().reads((16_u64).saturating_mul(d.into())))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(Weight::from_parts(0, 8_160).saturating_mul(d.into()))
+	}
+}
@@ -54,7 +54,6 @@
 // mod tests;
 // pub mod weights;
 
-use scale_codec::{Decode, Encode};
 use frame_support::{
 	dispatch::DispatchResult,
 	ensure,
@@ -63,6 +62,8 @@ use frame_support::{
 	PalletId,
 };
 pub use pallet::*;
+use pallet_did::{Tier, VerifyIdentity};
+use scale_codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use sp_runtime::{
 	traits::{AccountIdConversion, Saturating, Zero},
@@ -110,9 +111,25 @@ pub mod pallet {
 
 		#[pallet::constant]
 		type MaxUserRewardPerRound: Get<u32>;
+
+		/// The identity tiers of ticket buyers.
+		type Identity: VerifyIdentity<Self::AccountId>;
+
+		/// The identity tier an account needs to buy tickets.
+		#[pallet::constant]
+		type MinTier: Get<Tier>;
 	}
 
-	#[derive(Encode, Decode, Default, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[derive(
+		Encode,
+		Decode,
+		Default,
+		Eq,
+		PartialEq,
+		RuntimeDebug,
+		TypeInfo,
+		MaxEncodedLen
+	)]
 	pub struct LotteryConfig<BlockNumber, Balance> {
 		/// Min Price per entry.
 		min_price: Balance,
@@ -178,6 +195,8 @@ pub mod pallet {
 		/// You are already participating in the lottery with this call.
 		InvalidNumber,
 		TooManyParticipants,
+		/// The account does not reach the identity tier needed to buy tickets.
+		UserIsNotVerified,
 	}
 
 	#[pallet::storage]
@@ -272,50 +291,63 @@ pub mod pallet {
 		#[pallet::call_index(0)]
 		#[pallet::weight((10_100, DispatchClass::Normal, Pays::No))]
 		pub fn buy_ticket(
-				origin: OriginFor<T>,
-				selections: Vec<(u8, BalanceOf<T>)>,
+			origin: OriginFor<T>,
+			selections: Vec<(u8, BalanceOf<T>)>,
 		) -> DispatchResult {
-				let caller = ensure_signed(origin.clone())?;
-				ensure!(selections.len() < 100, Error::<T>::InvalidNumber);
-				let (numbers, amounts): (Vec<u8>, Vec<BalanceOf<T>>) = selections.iter().cloned().unzip();
-				ensure!(numbers.iter().all(|&number| number < 100), Error::<T>::InvalidNumber);
-				let round = Round::<T>::get();
-				let config = Lottery::<T>::get(round).ok_or(Error::<T>::NotConfigured)?;
-				let block_number = frame_system::Pallet::<T>::block_number();
-				ensure!(
-						block_number <= config.start.saturating_add(config.length),
-						Error::<T>::AlreadyEnded
-				);
-				for (number, amount) in numbers.iter().zip(amounts.iter()) {
-						Participants::<T>::try_mutate((round, *number), |participants| -> DispatchResult {
-								let check = participants.contains(&caller);
-								match check {
-										false => {
-												participants
-														.try_insert(caller.clone())
-														.map_err(|_| Error::<T>::TooManyParticipants)?;
-												UserPredictionValue::<T>::insert(round, (&caller, *number), *amount)
-										}
-										true => UserPredictionValue::<T>::mutate(round, (&caller, *number), |v| {
-												*v = v.saturating_add(*amount)
-										}),
-								}
-								T::Currency::transfer(
-										&caller,
-										&Self::account_id(),
-										*amount,
-										ExistenceRequirement::KeepAlive,
-								)?;
-								Ok(())
-						})?;
-						Self::deposit_event(Event::<T>::TicketBought {
-							round,
-							who: caller.clone(),
-							amount: *amount,
-							number: *number,
-					});
-				}
-				Ok(())
+			let caller = ensure_signed(origin.clone())?;
+			ensure!(
+				T::Identity::meets_tier(&caller, T::MinTier::get()),
+				Error::<T>::UserIsNotVerified
+			);
+			ensure!(selections.len() < 100, Error::<T>::InvalidNumber);
+			let (numbers, amounts): (Vec<u8>, Vec<BalanceOf<T>>) =
+				selections.iter().cloned().unzip();
+			ensure!(
+				numbers.iter().all(|&number| number < 100),
+				Error::<T>::InvalidNumber
+			);
+			let round = Round::<T>::get();
+			let config = Lottery::<T>::get(round).ok_or(Error::<T>::NotConfigured)?;
+			let block_number = frame_system::Pallet::<T>::block_number();
+			ensure!(
+				block_number <= config.start.saturating_add(config.length),
+				Error::<T>::AlreadyEnded
+			);
+			for (number, amount) in numbers.iter().zip(amounts.iter()) {
+				Participants::<T>::try_mutate(
+					(round, *number),
+					|participants| -> DispatchResult {
+						let check = participants.contains(&caller);
+						match check {
+							false => {
+								participants
+									.try_insert(caller.clone())
+									.map_err(|_| Error::<T>::TooManyParticipants)?;
+								UserPredictionValue::<T>::insert(round, (&caller, *number), *amount)
+							}
+							true => {
+								UserPredictionValue::<T>::mutate(round, (&caller, *number), |v| {
+									*v = v.saturating_add(*amount)
+								})
+							}
+						}
+						T::Currency::transfer(
+							&caller,
+							&Self::account_id(),
+							*amount,
+							ExistenceRequirement::KeepAlive,
+						)?;
+						Ok(())
+					},
+				)?;
+				Self::deposit_event(Event::<T>::TicketBought {
+					round,
+					who: caller.clone(),
+					amount: *amount,
+					number: *number,
+				});
+			}
+			Ok(())
 		}
 
 		/// Start a lottery using the provided configuration.
@@ -437,8 +469,7 @@ impl<T: Config> Pallet<T> {
 	fn random_number(index: u32) -> u8 {
 		// Get the current block's random seed
 		let random_number = Self::generate_random_number(index);
-		let random_number = (random_number % 100) as u8;
-		random_number
+		(random_number % 100) as u8
 	}
 
 	/// Generate a random number from a given seed.
@@ -20,11 +20,12 @@ use sp_core::{
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
 	traits::{
-		BlakeTwo256, Block as BlockT, DispatchInfoOf, Dispatchable, Get, IdentifyAccount,
-		IdentityLookup, NumberFor, One, PostDispatchInfoOf, UniqueSaturatedInto, Verify,
+		AccountIdConversion, BlakeTwo256, Block as BlockT, DispatchInfoOf, Dispatchable,
+		Extrinsic as ExtrinsicT, Get, IdentifyAccount, IdentityLookup, NumberFor, One,
+		PostDispatchInfoOf, SaturatedConversion, UniqueSaturatedInto, Verify,
 	},
 	transaction_validity::{TransactionSource, TransactionValidity, TransactionValidityError},
-	ApplyExtrinsicResult, ConsensusEngineId, Perbill, Permill,
+	ApplyExtrinsicResult, ConsensusEngineId, Perbill, Percent, Permill,
 };
 use sp_std::{marker::PhantomData, prelude::*};
 use sp_version::RuntimeVersion;
@@ -35,12 +36,17 @@ use frame_support::weights::constants::ParityDbWeight as RuntimeDbWeight;
 use frame_support::weights::constants::RocksDbWeight as RuntimeDbWeight;
 use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{ConstBool, ConstU32, ConstU8, FindAuthor, OnFinalize, OnTimestampSet},
+	traits::{
+		AsEnsureOriginWithArg, ConstBool, ConstU32, ConstU8, FindAuthor, OnFinalize, OnTimestampSet,
+	},
 	weights::{constants::WEIGHT_REF_TIME_PER_MILLIS, IdentityFee, Weight},
+	PalletId,
 };
+use frame_system::{EnsureRoot, EnsureSigned};
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
+use pallet_nfts::PalletFeatures;
 use pallet_transaction_payment::{ConstFeeMultiplier, CurrencyAdapter};
 // Frontier
 use fp_account::EthereumSignature;
@@ -60,6 +66,7 @@ pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::Multiplier;
 
+mod oracle;
 mod precompiles;
 use precompiles::FrontierPrecompiles;
 
@@ -83,6 +90,12 @@ pub type Balance = u128;
 /// Index of a transaction in the chain.
 pub type Nonce = u32;
 
+/// Identifier of a fungible asset.
+pub type AssetId = u32;
+
+/// Identifier of an NFT collection.
+pub type CollectionId = u32;
+
 /// A hash of some data used by the chain.
 pub type Hash = H256;
 
@@ -134,6 +147,9 @@ pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+// Balances have 18 decimals, like ether.
+pub const UNITS: Balance = 1_000_000_000_000_000_000;
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> sp_version::NativeVersion {
@@ -273,11 +289,11 @@ impl pallet_balances::Config for Runtime {
 	type ExistentialDeposit = ExistentialDeposit;
 	type AccountStore = System;
 	type ReserveIdentifier = [u8; 8];
-	type RuntimeHoldReason = ();
+	type RuntimeHoldReason = RuntimeHoldReason;
 	type FreezeIdentifier = ();
 	type MaxLocks = MaxLocks;
 	type MaxReserves = ();
-	type MaxHolds = ();
+	type MaxHolds = ConstU32<1>;
 	type MaxFreezes = ();
 }
 
@@ -398,6 +414,221 @@ impl pallet_hotfix_sufficients::Config for Runtime {
 	type WeightInfo = pallet_hotfix_sufficients::weights::SubstrateWeight<Self>;
 }
 
+parameter_types! {
+	pub DidRootAccount: AccountId = AccountId::from([0u8; 20]);
+	pub const DidDepositPerItem: Balance = 1_000_000_000_000_000;
+	pub const DidDepositPerByte: Balance = 10_000_000_000_000;
+}
+
+impl pallet_did::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type ManagerOrigin = frame_system::EnsureRootWithSuccess<AccountId, DidRootAccount>;
+	type MaxBatchSize = ConstU32<64>;
+	type Currency = Balances;
+	type RuntimeHoldReason = RuntimeHoldReason;
+	type DepositPerItem = DidDepositPerItem;
+	type DepositPerByte = DidDepositPerByte;
+	type WeightInfo = pallet_did::weights::SubstrateWeight<Self>;
+}
+
+parameter_types! {
+	/// Tier 1 needs a Fractal external id.
+	pub DidIdentityTiers: Vec<pallet_did::TierRequirement> = vec![pallet_did::TierRequirement {
+		providers: vec![pallet_did::Provider::truncate_from(b"Fractal".to_vec())],
+		lists: vec![],
+	}];
+}
+
+/// The identity tiers pallets gating on identity check against.
+pub type DidIdentity = pallet_did::DidTiers<Runtime, DidIdentityTiers>;
+
+impl pallet_insecure_randomness_collective_flip::Config for Runtime {}
+
+parameter_types! {
+	pub const AssetDeposit: Balance = 10 * UNITS;
+	pub const AssetAccountDeposit: Balance = UNITS / 10;
+	pub const ApprovalDeposit: Balance = UNITS / 10;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: Balance = UNITS;
+	pub const MetadataDepositPerByte: Balance = UNITS / 100;
+}
+
+impl pallet_assets::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type AssetIdParameter = scale_codec::Compact<AssetId>;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type AssetAccountDeposit = AssetAccountDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type CallbackHandle = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Self>;
+	type RemoveItemsLimit = ConstU32<1000>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = ();
+}
+
+parameter_types! {
+	pub const CollectionDeposit: Balance = 10 * UNITS;
+	pub const ItemDeposit: Balance = UNITS / 10;
+	pub const KeyLimit: u32 = 32;
+	pub const ValueLimit: u32 = 256;
+	pub const ApprovalsLimit: u32 = 20;
+	pub const ItemAttributesApprovalsLimit: u32 = 20;
+	pub const MaxTips: u32 = 10;
+	pub const MaxDeadlineDuration: BlockNumber = 12 * 30 * DAYS;
+	pub const MaxAttributesPerCall: u32 = 10;
+	pub Features: PalletFeatures = PalletFeatures::all_enabled();
+}
+
+impl pallet_nfts::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type CollectionId = CollectionId;
+	type ItemId = u32;
+	type Currency = Balances;
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type CreateOrigin = AsEnsureOriginWithArg<EnsureSigned<AccountId>>;
+	type Locker = ();
+	type CollectionDeposit = CollectionDeposit;
+	type ItemDeposit = ItemDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type AttributeDepositBase = MetadataDepositBase;
+	type DepositPerByte = MetadataDepositPerByte;
+	type StringLimit = StringLimit;
+	type KeyLimit = KeyLimit;
+	type ValueLimit = ValueLimit;
+	type ApprovalsLimit = ApprovalsLimit;
+	type ItemAttributesApprovalsLimit = ItemAttributesApprovalsLimit;
+	type MaxTips = MaxTips;
+	type MaxDeadlineDuration = MaxDeadlineDuration;
+	type MaxAttributesPerCall = MaxAttributesPerCall;
+	type Features = Features;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type WeightInfo = pallet_nfts::weights::SubstrateWeight<Self>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type Helper = ();
+}
+
+parameter_types! {
+	pub const GiveawayPalletId: PalletId = PalletId(*b"giveaway");
+	pub GiveawayRootAccount: AccountId = AccountId::from([0u8; 20]);
+	pub GiveawayTreasury: AccountId = PalletId(*b"py/trsry").into_account_truncating();
+	pub const GiveawayTreasuryShare: Percent = Percent::from_percent(10);
+	pub const OracleBond: Balance = 1_000 * UNITS;
+	/// Within `BlockHashCount`, so the hash of the block a giveaway ended in is still known.
+	pub const RandomnessTimeout: BlockNumber = 10 * MINUTES;
+}
+
+impl pallet_ocw_giveaway::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type GiveawayOrigin = frame_system::EnsureRootWithSuccess<AccountId, GiveawayRootAccount>;
+	type Identity = DidIdentity;
+	type AuthorityId = oracle::OracleAuthId;
+	type PalletId = GiveawayPalletId;
+	type Currency = Balances;
+	type Randomness = RandomnessCollectiveFlip;
+	type PotDeposit = ExistentialDeposit;
+	type MaxSet = ConstU32<64>;
+	type ResultLockBlocks = ConstU32<3>;
+	type NftCollectionId = CollectionId;
+	type NftId = u32;
+	type Nfts = Nfts;
+	type AssetBalance = Balance;
+	type AssetId = AssetId;
+	type Assets = Assets;
+	type NativeAssetId = ConstU32<0>;
+	type Treasury = GiveawayTreasury;
+	type TreasuryShare = GiveawayTreasuryShare;
+	type OracleBond = OracleBond;
+	type MaxOracles = ConstU32<16>;
+	type OracleThreshold = ConstU32<3>;
+	type CommitBlocks = ConstU32<5>;
+	type RevealBlocks = ConstU32<5>;
+	type RandomnessTimeout = RandomnessTimeout;
+	type MaxDrawsPerBlock = ConstU32<8>;
+	type WeightInfo = pallet_ocw_giveaway::weights::SubstrateWeight<Self>;
+}
+
+parameter_types! {
+	pub const LotteryPalletId: PalletId = PalletId(*b"plottery");
+	pub LotteryRootAccount: AccountId = AccountId::from([0u8; 20]);
+	pub const MaxParticipants: u32 = u32::MAX / 2;
+}
+
+impl pallet_lucky_number::Config for Runtime {
+	type PalletId = LotteryPalletId;
+	type Currency = Balances;
+	type Randomness = RandomnessCollectiveFlip;
+	type RuntimeEvent = RuntimeEvent;
+	type ManagerOrigin = frame_system::EnsureRootWithSuccess<AccountId, LotteryRootAccount>;
+	type PotDeposit = ExistentialDeposit;
+	type MaxUserRewardPerRound = ConstU32<256>;
+	type MaxSet = MaxParticipants;
+	type Identity = DidIdentity;
+	/// Only accounts with a Fractal external id buy tickets.
+	type MinTier = ConstU8<1>;
+}
+
+impl frame_system::offchain::SigningTypes for Runtime {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+	RuntimeCall: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: RuntimeCall,
+		public: <Signature as Verify>::Signer,
+		account: AccountId,
+		nonce: Nonce,
+	) -> Option<(
+		RuntimeCall,
+		<UncheckedExtrinsic as ExtrinsicT>::SignaturePayload,
+	)> {
+		// Offchain transactions are valid for half of the blocks their era can refer to.
+		let period = BlockHashCount::get()
+			.checked_next_power_of_two()
+			.map(|c| c / 2)
+			.unwrap_or(2) as u64;
+		let current_block = System::block_number()
+			.saturated_into::<u64>()
+			.saturating_sub(1);
+		let extra: SignedExtra = (
+			frame_system::CheckNonZeroSender::<Runtime>::new(),
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+		);
+		let raw_payload = SignedPayload::new(call, extra).ok()?;
+		let signature = raw_payload.using_encoded(|payload| C::sign(payload, public))?;
+		let (call, extra, _) = raw_payload.deconstruct();
+		Some((call, (account, signature, extra)))
+	}
+}
+
 // Create the runtime by composing the FRAME pallets that were previously configured.
 construct_runtime!(
 	pub enum Runtime {
@@ -414,6 +645,12 @@ construct_runtime!(
 		DynamicFee: pallet_dynamic_fee,
 		BaseFee: pallet_base_fee,
 		HotfixSufficients: pallet_hotfix_sufficients,
+		Did: pallet_did,
+		RandomnessCollectiveFlip: pallet_insecure_randomness_collective_flip,
+		Assets: pallet_assets,
+		Nfts: pallet_nfts,
+		Giveaway: pallet_ocw_giveaway,
+		LuckyNumber: pallet_lucky_number,
 	}
 );
 
@@ -471,6 +708,9 @@ pub type CheckedExtrinsic =
 	fp_self_contained::CheckedExtrinsic<AccountId, RuntimeCall, SignedExtra, H160>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Migrations to apply on runtime upgrade. The DID lists of storage version 0 go to the DID
+/// root account.
+pub type Migrations = (pallet_did::migrations::v1::MigrateToV1<Runtime, DidRootAccount>,);
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -478,6 +718,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 impl fp_self_contained::SelfContainedCall for RuntimeCall {
@@ -551,6 +792,8 @@ mod benches {
 		[pallet_timestamp, Timestamp]
 		[pallet_sudo, Sudo]
 		[pallet_evm, EVM]
+		[pallet_did, Did]
+		[pallet_ocw_giveaway, Giveaway]
 	);
 }
 
@@ -858,6 +1101,68 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_did_runtime_api::DidApi<Block, AccountId, BlockNumber> for Runtime {
+		fn external_ids(
+			who: AccountId,
+		) -> Vec<(pallet_did::Provider, pallet_did::ExternalIdRecord<BlockNumber>)> {
+			Did::external_ids_of(&who)
+		}
+
+		fn accounts_of(
+			provider: pallet_did::Provider,
+			external_id: pallet_did::ExternalId,
+		) -> Vec<AccountId> {
+			Did::accounts_of(provider, external_id)
+		}
+
+		fn membership(
+			list_name: pallet_did::ListName,
+			who: AccountId,
+		) -> Option<pallet_did::ListMembership<BlockNumber>> {
+			Did::membership(&list_name, &who)
+		}
+
+		fn list_members(
+			list_name: pallet_did::ListName,
+			start_after: Option<AccountId>,
+			limit: u32,
+		) -> Vec<(AccountId, pallet_did::ListMembership<BlockNumber>)> {
+			Did::list_members_page(&list_name, start_after, limit)
+		}
+
+		fn manager_role(who: AccountId) -> Option<pallet_did::ManagerRole> {
+			Did::did_manager(who)
+		}
+	}
+
+	impl
+		pallet_ocw_giveaway_runtime_api::GiveawayApi<
+			Block,
+			AccountId,
+			BlockNumber,
+			Balance,
+			AssetId,
+			CollectionId,
+		> for Runtime
+	{
+		fn status(index: u32) -> Option<pallet_ocw_giveaway::GiveawayStatus> {
+			Giveaway::giveaway_status(index)
+		}
+
+		fn entry_requirements(
+			index: u32,
+		) -> Option<Vec<pallet_ocw_giveaway::EntryRequirement<BlockNumber, Balance, AssetId, CollectionId>>> {
+			Giveaway::entry_requirements(index)
+		}
+
+		fn unmet_requirements(
+			index: u32,
+			who: AccountId,
+		) -> Vec<pallet_ocw_giveaway::EntryRequirement<BlockNumber, Balance, AssetId, CollectionId>> {
+			Giveaway::unmet_requirements(index, &who)
+		}
+	}
+
 	impl sp_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			opaque::SessionKeys::generate(seed)
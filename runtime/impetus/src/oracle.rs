@@ -0,0 +1,97 @@
+//! The keys giveaway offchain workers sign their submissions with.
+//!
+//! Accounts are Ethereum addresses, so workers hold secp256k1 keys and sign the Keccak-256 hash
+//! of a payload, which is what `EthereumSignature` verifies.
+
+use fp_account::{EthereumSignature, EthereumSigner};
+use pallet_ocw_giveaway::KEY_TYPE;
+use scale_codec::{Decode, Encode};
+use sp_core::ecdsa;
+use sp_runtime::{
+	app_crypto::app_crypto,
+	traits::{IdentifyAccount, Verify},
+	RuntimeAppPublic,
+};
+
+app_crypto!(ecdsa, KEY_TYPE);
+
+/// A worker key unwrapped from the application crypto.
+pub struct OracleKey(ecdsa::Public);
+
+impl From<Public> for OracleKey {
+	fn from(key: Public) -> Self {
+		Self(key.into())
+	}
+}
+
+impl From<OracleKey> for Public {
+	fn from(key: OracleKey) -> Self {
+		key.0.into()
+	}
+}
+
+impl From<OracleKey> for EthereumSigner {
+	fn from(key: OracleKey) -> Self {
+		key.0.into()
+	}
+}
+
+/// An address can't be turned back into its key, [`OracleAuthId`] looks it up in the keystore.
+impl TryFrom<EthereumSigner> for OracleKey {
+	type Error = ();
+
+	fn try_from(_: EthereumSigner) -> Result<Self, Self::Error> {
+		Err(())
+	}
+}
+
+/// A worker signature unwrapped from the application crypto.
+pub struct OracleSignature(ecdsa::Signature);
+
+impl From<Signature> for OracleSignature {
+	fn from(signature: Signature) -> Self {
+		Self(signature.into())
+	}
+}
+
+impl From<OracleSignature> for Signature {
+	fn from(signature: OracleSignature) -> Self {
+		signature.0.into()
+	}
+}
+
+impl From<OracleSignature> for EthereumSignature {
+	fn from(signature: OracleSignature) -> Self {
+		EthereumSignature::new(signature.0)
+	}
+}
+
+impl TryFrom<EthereumSignature> for OracleSignature {
+	type Error = scale_codec::Error;
+
+	fn try_from(signature: EthereumSignature) -> Result<Self, Self::Error> {
+		ecdsa::Signature::decode(&mut &signature.encode()[..]).map(Self)
+	}
+}
+
+pub struct OracleAuthId;
+
+impl frame_system::offchain::AppCrypto<EthereumSigner, EthereumSignature> for OracleAuthId {
+	type RuntimeAppPublic = Public;
+	type GenericPublic = OracleKey;
+	type GenericSignature = OracleSignature;
+
+	fn sign(payload: &[u8], public: EthereumSigner) -> Option<EthereumSignature> {
+		let account = public.into_account();
+		let key = Public::all()
+			.into_iter()
+			.map(ecdsa::Public::from)
+			.find(|key| EthereumSigner::from(*key).into_account() == account)?;
+		let hash = sp_io::hashing::keccak_256(payload);
+		sp_io::crypto::ecdsa_sign_prehashed(KEY_TYPE, &key, &hash).map(EthereumSignature::new)
+	}
+
+	fn verify(payload: &[u8], public: EthereumSigner, signature: EthereumSignature) -> bool {
+		signature.verify(payload, &public.into_account())
+	}
+}
@@ -426,6 +426,8 @@ impl pallet_lucky_number::Config for Runtime {
 	type PotDeposit = PotDeposit;
 	type MaxUserRewardPerRound = MaxUserRewardPerRound;
 	type MaxSet = MaxParticipants;
+	type Identity = ();
+	type MinTier = ConstU8<0>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.